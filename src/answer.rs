@@ -0,0 +1,346 @@
+//! Answers of any type that compare equal by their normalised value.
+
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+use num::{BigInt, BigRational, Rational64, ToPrimitive, Zero};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+/// The answer to one part of a puzzle.
+///
+/// Equality is based on the normalised value, so answers of different variants can be equal,
+/// e.g. `Int(4)`, `BigInt(4)`, `Rational(8/2)` and `Text(" 4 ")`. Art compares equal if the
+/// lines are equal after trimming trailing whitespace and surrounding empty lines.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(try_from = "AnswerRepr", into = "AnswerRepr")]
+pub enum Answer {
+    Int(i64),
+    BigInt(BigInt),
+    Rational(Rational64),
+    Text(String),
+    /// Multi-line output like the letters drawn by day 10.
+    Art(Vec<String>),
+}
+
+impl Answer {
+    /// Creates an [`Answer::Art`] without the empty lines around `art`.
+    pub fn art(art: &str) -> Self {
+        let lines = art.lines().collect::<Vec<_>>();
+        let start = lines
+            .iter()
+            .position(|l| !l.trim().is_empty())
+            .unwrap_or(lines.len());
+        let end = lines
+            .iter()
+            .rposition(|l| !l.trim().is_empty())
+            .map_or(start, |i| i + 1);
+        Answer::Art(lines[start..end].iter().map(|l| l.to_string()).collect())
+    }
+
+    pub fn is_multiline(&self) -> bool {
+        matches!(self, Answer::Art(lines) if lines.len() > 1)
+    }
+
+    /// Renders the answer on a single line, with `\n` and `\\` escaping line breaks and
+    /// backslashes. This is the inverse of [`Answer::from_text`].
+    pub fn to_text(&self) -> String {
+        self.to_string().replace('\\', "\\\\").replace('\n', "\\n")
+    }
+
+    /// Parses the single-line form produced by [`Answer::to_text`].
+    ///
+    /// Integers and fractions like `-3/4` become numbers, text containing line breaks becomes
+    /// art and everything else is kept as trimmed text.
+    pub fn from_text(s: &str) -> Self {
+        let mut text = String::with_capacity(s.len());
+        let mut chars = s.chars();
+        while let Some(c) = chars.next() {
+            match c {
+                '\\' => match chars.next() {
+                    Some('n') => text.push('\n'),
+                    Some(c) => text.push(c),
+                    None => text.push('\\'),
+                },
+                c => text.push(c),
+            }
+        }
+
+        if text.contains('\n') {
+            return Answer::art(&text);
+        }
+
+        let text = text.trim();
+        match parse_number(text) {
+            Some(n) => Answer::from(n),
+            None => Answer::Text(text.to_string()),
+        }
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).expect("answers are always serializable")
+    }
+
+    pub fn from_json(s: &str) -> serde_json::Result<Self> {
+        serde_json::from_str(s)
+    }
+
+    fn normalized(&self) -> Normalized {
+        match self {
+            Answer::Int(n) => Normalized::Number(BigRational::from(BigInt::from(*n))),
+            Answer::BigInt(n) => Normalized::Number(BigRational::from(n.clone())),
+            Answer::Rational(r) => Normalized::Number(BigRational::new(
+                BigInt::from(*r.numer()),
+                BigInt::from(*r.denom()),
+            )),
+            Answer::Text(text) if text.trim().contains('\n') => Answer::art(text).normalized(),
+            Answer::Text(text) => match parse_number(text.trim()) {
+                Some(n) => Normalized::Number(n),
+                None => Normalized::Text(text.trim().to_string()),
+            },
+            Answer::Art(lines) if lines.len() == 1 => Answer::from(lines[0].as_str()).normalized(),
+            Answer::Art(lines) => {
+                Normalized::Art(lines.iter().map(|l| l.trim_end().to_string()).collect())
+            }
+        }
+    }
+}
+
+#[derive(PartialEq)]
+enum Normalized {
+    Number(BigRational),
+    Text(String),
+    Art(Vec<String>),
+}
+
+fn parse_number(s: &str) -> Option<BigRational> {
+    let (numer, denom) = match s.split_once('/') {
+        Some((numer, denom)) => (numer, denom),
+        None => (s, "1"),
+    };
+    let numer = BigInt::from_str(numer).ok()?;
+    let denom = BigInt::from_str(denom).ok()?;
+    (!denom.is_zero()).then(|| BigRational::new(numer, denom))
+}
+
+impl PartialEq for Answer {
+    fn eq(&self, other: &Self) -> bool {
+        self.normalized() == other.normalized()
+    }
+}
+
+impl Eq for Answer {}
+
+impl Display for Answer {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Answer::Int(n) => write!(f, "{n}"),
+            Answer::BigInt(n) => write!(f, "{n}"),
+            Answer::Rational(r) => write!(f, "{r}"),
+            Answer::Text(text) => f.write_str(text),
+            Answer::Art(lines) => f.write_str(&lines.join("\n")),
+        }
+    }
+}
+
+macro_rules! impl_from_small_int {
+    ($($t:ty),*) => {
+        $(
+            impl From<$t> for Answer {
+                fn from(n: $t) -> Self {
+                    Answer::Int(n.into())
+                }
+            }
+        )*
+    };
+}
+
+macro_rules! impl_from_large_int {
+    ($($t:ty),*) => {
+        $(
+            impl From<$t> for Answer {
+                fn from(n: $t) -> Self {
+                    match i64::try_from(n) {
+                        Ok(n) => Answer::Int(n),
+                        Err(_) => Answer::BigInt(n.into()),
+                    }
+                }
+            }
+        )*
+    };
+}
+
+impl_from_small_int!(i8, i16, i32, i64, u8, u16, u32);
+impl_from_large_int!(isize, usize, u64, i128, u128);
+
+impl From<BigInt> for Answer {
+    fn from(n: BigInt) -> Self {
+        match n.to_i64() {
+            Some(n) => Answer::Int(n),
+            None => Answer::BigInt(n),
+        }
+    }
+}
+
+impl From<BigRational> for Answer {
+    fn from(r: BigRational) -> Self {
+        if r.is_integer() {
+            return Answer::from(r.to_integer());
+        }
+
+        match (r.numer().to_i64(), r.denom().to_i64()) {
+            (Some(numer), Some(denom)) => Answer::Rational(Rational64::new(numer, denom)),
+            _ => Answer::Text(r.to_string()),
+        }
+    }
+}
+
+impl From<Rational64> for Answer {
+    fn from(r: Rational64) -> Self {
+        if r.is_integer() {
+            Answer::Int(r.to_integer())
+        } else {
+            Answer::Rational(r)
+        }
+    }
+}
+
+impl From<String> for Answer {
+    fn from(s: String) -> Self {
+        Answer::Text(s)
+    }
+}
+
+impl From<&str> for Answer {
+    fn from(s: &str) -> Self {
+        Answer::Text(s.to_string())
+    }
+}
+
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+#[error("invalid {kind} answer '{value}'")]
+pub struct InvalidAnswer {
+    kind: &'static str,
+    value: String,
+}
+
+/// The JSON form of an [`Answer`], numbers that don't fit into JSON are stored as strings.
+#[derive(Serialize, Deserialize)]
+#[serde(tag = "type", content = "value", rename_all = "lowercase")]
+enum AnswerRepr {
+    Int(i64),
+    BigInt(String),
+    Rational(String),
+    Text(String),
+    Art(Vec<String>),
+}
+
+impl From<Answer> for AnswerRepr {
+    fn from(answer: Answer) -> Self {
+        match answer {
+            Answer::Int(n) => AnswerRepr::Int(n),
+            Answer::BigInt(n) => AnswerRepr::BigInt(n.to_string()),
+            Answer::Rational(r) => AnswerRepr::Rational(r.to_string()),
+            Answer::Text(text) => AnswerRepr::Text(text),
+            Answer::Art(lines) => AnswerRepr::Art(lines),
+        }
+    }
+}
+
+impl TryFrom<AnswerRepr> for Answer {
+    type Error = InvalidAnswer;
+
+    fn try_from(repr: AnswerRepr) -> Result<Self, Self::Error> {
+        Ok(match repr {
+            AnswerRepr::Int(n) => Answer::Int(n),
+            AnswerRepr::BigInt(n) => Answer::BigInt(n.parse().map_err(|_| InvalidAnswer {
+                kind: "bigint",
+                value: n,
+            })?),
+            AnswerRepr::Rational(r) => Answer::Rational(r.parse().map_err(|_| InvalidAnswer {
+                kind: "rational",
+                value: r,
+            })?),
+            AnswerRepr::Text(text) => Answer::Text(text),
+            AnswerRepr::Art(lines) => Answer::Art(lines),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_from() {
+        assert!(matches!(Answer::from(24000u32), Answer::Int(24000)));
+        assert!(matches!(Answer::from(u64::MAX), Answer::BigInt(_)));
+        assert!(matches!(
+            Answer::from(Rational64::new(301, 1)),
+            Answer::Int(301)
+        ));
+        assert!(matches!(
+            Answer::from(Rational64::new(1, 2)),
+            Answer::Rational(_)
+        ));
+        assert_eq!(
+            vec!["#.", ".#"],
+            match Answer::art("\n\n#.\n.#\n") {
+                Answer::Art(lines) => lines,
+                _ => unreachable!(),
+            }
+        );
+    }
+
+    #[test]
+    fn test_eq() {
+        assert_eq!(Answer::Int(4), Answer::BigInt(BigInt::from(4)));
+        assert_eq!(Answer::Int(4), Answer::Rational(Rational64::new(8, 2)));
+        assert_eq!(Answer::Int(4), Answer::from(" 4 "));
+        assert_eq!(Answer::Rational(Rational64::new(1, 2)), Answer::from("2/4"));
+        assert_eq!(Answer::from("CMZ"), Answer::from("CMZ\n"));
+        assert_eq!(Answer::art("\n#.  \n.#\n"), Answer::from("#.\n.#"));
+        assert_ne!(Answer::Int(4), Answer::Int(5));
+        assert_ne!(Answer::from("CMZ"), Answer::from("MCD"));
+        assert_ne!(Answer::art("#.\n.#"), Answer::art(".#\n#."));
+    }
+
+    #[test]
+    fn test_text() {
+        for answer in [
+            Answer::Int(-3),
+            Answer::from(u128::MAX),
+            Answer::Rational(Rational64::new(-3, 4)),
+            Answer::from("2=-1=0"),
+            Answer::from("back\\slash"),
+            Answer::art("#. \n.#\n"),
+        ] {
+            assert_eq!(answer, Answer::from_text(&answer.to_text()));
+        }
+        assert_eq!("#.\\n.#", Answer::art("#.\n.#").to_text());
+        assert!(matches!(Answer::from_text("1/0"), Answer::Text(_)));
+    }
+
+    #[test]
+    fn test_json() {
+        assert_eq!(r#"{"type":"int","value":42}"#, Answer::Int(42).to_json());
+        assert_eq!(
+            r#"{"type":"rational","value":"-3/4"}"#,
+            Answer::Rational(Rational64::new(-3, 4)).to_json()
+        );
+        assert_eq!(
+            r##"{"type":"art","value":["#.",".#"]}"##,
+            Answer::art("#.\n.#").to_json()
+        );
+        for answer in [
+            Answer::from(u128::MAX),
+            Answer::from("CMZ"),
+            Answer::Rational(Rational64::new(1, 3)),
+        ] {
+            assert_eq!(answer, Answer::from_json(&answer.to_json()).unwrap());
+        }
+        assert!(Answer::from_json(r#"{"type":"bigint","value":"x"}"#).is_err());
+    }
+}
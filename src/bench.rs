@@ -0,0 +1,301 @@
+//! Timing of the generator and both parts of each day, with baseline comparison.
+
+use std::collections::BTreeMap;
+use std::fmt::{Display, Formatter};
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+
+use crate::common::ParseError;
+use crate::solution::Solution;
+
+/// The separately timed stages of a day.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Stage {
+    Generator,
+    Part1,
+    Part2,
+}
+
+impl Display for Stage {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Stage::Generator => "generator",
+            Stage::Part1 => "part1",
+            Stage::Part2 => "part2",
+        })
+    }
+}
+
+/// Timing statistics of one stage, all durations in nanoseconds.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Measurement {
+    pub day: u32,
+    pub stage: Stage,
+    pub iterations: usize,
+    pub min_ns: u64,
+    pub median_ns: u64,
+    pub p95_ns: u64,
+}
+
+impl Measurement {
+    fn new(day: u32, stage: Stage, mut samples: Vec<Duration>) -> Self {
+        assert!(!samples.is_empty(), "no samples");
+        samples.sort_unstable();
+        let percentile = |p: usize| samples[(samples.len() * p).div_ceil(100).max(1) - 1];
+        let nanos = |d: Duration| u64::try_from(d.as_nanos()).unwrap_or(u64::MAX);
+        Self {
+            day,
+            stage,
+            iterations: samples.len(),
+            min_ns: nanos(samples[0]),
+            median_ns: nanos(percentile(50)),
+            p95_ns: nanos(percentile(95)),
+        }
+    }
+
+    pub fn min(&self) -> Duration {
+        Duration::from_nanos(self.min_ns)
+    }
+
+    pub fn median(&self) -> Duration {
+        Duration::from_nanos(self.median_ns)
+    }
+
+    pub fn p95(&self) -> Duration {
+        Duration::from_nanos(self.p95_ns)
+    }
+}
+
+fn time<T>(f: impl FnOnce() -> T) -> (T, Duration) {
+    let start = Instant::now();
+    let result = black_box(f());
+    (result, start.elapsed())
+}
+
+/// Times the generator and each part of `solution` over `iterations` runs with default params.
+pub fn bench_day(
+    solution: &dyn Solution,
+    input: &str,
+    iterations: usize,
+) -> Result<Vec<Measurement>, ParseError> {
+    assert!(iterations > 0, "at least one iteration is required");
+    let day = solution.day();
+    let params = solution.params();
+
+    let mut samples = Vec::with_capacity(iterations);
+    let mut parsed = None;
+    for _ in 0..iterations {
+        let (result, elapsed) = time(|| solution.parse(black_box(input)));
+        samples.push(elapsed);
+        parsed = Some(result?);
+    }
+    let parsed = parsed.unwrap();
+    let mut measurements = vec![Measurement::new(day, Stage::Generator, samples)];
+
+    for (part, stage) in [(1, Stage::Part1), (2, Stage::Part2)] {
+        if part == 2 && !solution.has_part2() {
+            continue;
+        }
+
+        let samples = (0..iterations)
+            .map(|_| time(|| solution.solve(part, &parsed, &*params)).1)
+            .collect();
+        measurements.push(Measurement::new(day, stage, samples));
+    }
+
+    Ok(measurements)
+}
+
+pub fn to_json(measurements: &[Measurement]) -> String {
+    serde_json::to_string_pretty(measurements).expect("measurements are always serializable")
+}
+
+pub fn from_json(s: &str) -> serde_json::Result<Vec<Measurement>> {
+    serde_json::from_str(s)
+}
+
+pub fn to_csv(measurements: &[Measurement]) -> String {
+    let mut csv = "day,stage,iterations,min_ns,median_ns,p95_ns\n".to_string();
+    for m in measurements {
+        csv += &format!(
+            "{},{},{},{},{},{}\n",
+            m.day, m.stage, m.iterations, m.min_ns, m.median_ns, m.p95_ns
+        );
+    }
+
+    csv
+}
+
+/// The median of a measurement compared with the one from a baseline.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Comparison {
+    pub day: u32,
+    pub stage: Stage,
+    pub baseline: Duration,
+    pub current: Duration,
+    /// Relative change of the median, e.g. `0.1` if it got 10% slower.
+    pub change: f64,
+    /// `true` if `change` exceeds the threshold given to [`compare`].
+    pub regression: bool,
+}
+
+/// Compares the medians of `current` with the matching ones in `baseline`.
+///
+/// A stage regressed if its median got slower by more than `threshold`, e.g. `0.1` for 10%.
+/// Stages missing from `baseline` are not compared.
+pub fn compare(
+    current: &[Measurement],
+    baseline: &[Measurement],
+    threshold: f64,
+) -> Vec<Comparison> {
+    let baseline = baseline
+        .iter()
+        .map(|m| ((m.day, m.stage), m))
+        .collect::<BTreeMap<_, _>>();
+    current
+        .iter()
+        .filter_map(|m| {
+            let b = baseline.get(&(m.day, m.stage))?;
+            let change = if b.median_ns == 0 {
+                0.0
+            } else {
+                m.median_ns as f64 / b.median_ns as f64 - 1.0
+            };
+            Some(Comparison {
+                day: m.day,
+                stage: m.stage,
+                baseline: b.median(),
+                current: m.median(),
+                change,
+                regression: change > threshold,
+            })
+        })
+        .collect()
+}
+
+/// Renders the measurements as a table, with the comparison against a baseline if given.
+pub fn format_table(measurements: &[Measurement], comparisons: &[Comparison]) -> String {
+    let comparisons = comparisons
+        .iter()
+        .map(|c| ((c.day, c.stage), c))
+        .collect::<BTreeMap<_, _>>();
+    let mut table = format!(
+        "{:>3}  {:9}  {:>10}  {:>10}  {:>10}  {:>10}",
+        "day", "stage", "iterations", "min", "median", "p95"
+    );
+    if !comparisons.is_empty() {
+        table += &format!("  {:>10}  {:>8}", "baseline", "change");
+    }
+    table += "\n";
+
+    for m in measurements {
+        table += &format!(
+            "{:>3}  {:9}  {:>10}  {:>10}  {:>10}  {:>10}",
+            m.day,
+            m.stage.to_string(),
+            m.iterations,
+            format!("{:.1?}", m.min()),
+            format!("{:.1?}", m.median()),
+            format!("{:.1?}", m.p95())
+        );
+        if let Some(c) = comparisons.get(&(m.day, m.stage)) {
+            table += &format!(
+                "  {:>10}  {:>+7.1}%",
+                format!("{:.1?}", c.baseline),
+                c.change * 100.0
+            );
+            if c.regression {
+                table += "  REGRESSION";
+            }
+        }
+        table += "\n";
+    }
+
+    table
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::registry;
+
+    fn measurement(day: u32, stage: Stage, median_ns: u64) -> Measurement {
+        Measurement {
+            day,
+            stage,
+            iterations: 1,
+            min_ns: median_ns,
+            median_ns,
+            p95_ns: median_ns,
+        }
+    }
+
+    #[test]
+    fn test_statistics() {
+        let samples = (1..=20).rev().map(Duration::from_nanos).collect();
+        let m = Measurement::new(1, Stage::Part1, samples);
+        assert_eq!(
+            (20, 1, 10, 19),
+            (m.iterations, m.min_ns, m.median_ns, m.p95_ns)
+        );
+
+        let m = Measurement::new(1, Stage::Part1, vec![Duration::from_nanos(5)]);
+        assert_eq!((5, 5, 5), (m.min_ns, m.median_ns, m.p95_ns));
+    }
+
+    #[test]
+    fn test_bench_day() {
+        let measurements = bench_day(registry().day(1).unwrap(), "1\n2\n\n3\n", 3).unwrap();
+        assert_eq!(
+            vec![(Stage::Generator, 3), (Stage::Part1, 3), (Stage::Part2, 3)],
+            measurements
+                .iter()
+                .map(|m| (m.stage, m.iterations))
+                .collect::<Vec<_>>()
+        );
+
+        let measurements = bench_day(registry().day(25).unwrap(), "1=\n", 1).unwrap();
+        assert_eq!(2, measurements.len());
+        assert!(bench_day(registry().day(1).unwrap(), "x\n", 1).is_err());
+    }
+
+    #[test]
+    fn test_serialization() {
+        let measurements = vec![
+            measurement(16, Stage::Generator, 100),
+            measurement(16, Stage::Part2, 2000),
+        ];
+        assert_eq!(measurements, from_json(&to_json(&measurements)).unwrap());
+        assert_eq!(
+            "day,stage,iterations,min_ns,median_ns,p95_ns\n16,generator,1,100,100,100\n16,part2,1,2000,2000,2000\n",
+            to_csv(&measurements)
+        );
+    }
+
+    #[test]
+    fn test_compare() {
+        let baseline = [
+            measurement(17, Stage::Part1, 100),
+            measurement(17, Stage::Part2, 100),
+        ];
+        let current = [
+            measurement(17, Stage::Part1, 105),
+            measurement(17, Stage::Part2, 150),
+            measurement(19, Stage::Part1, 100),
+        ];
+        let comparisons = compare(&current, &baseline, 0.1);
+        assert_eq!(
+            vec![(Stage::Part1, false), (Stage::Part2, true)],
+            comparisons
+                .iter()
+                .map(|c| (c.stage, c.regression))
+                .collect::<Vec<_>>()
+        );
+        assert!((comparisons[1].change - 0.5).abs() < 1e-9);
+    }
+}
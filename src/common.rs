@@ -1,13 +1,32 @@
-use nalgebra::{Dim, Matrix, Matrix2, Scalar, Storage, Vector2};
+use nalgebra::{Matrix2, Scalar, Vector2};
 use nalgebra::{SVector, Vector3};
-use num::rational::Ratio;
-use num::{Rational64, Signed};
-use std::fmt::Debug;
+use num::rational::{ParseRatioError, Ratio};
+use num::Rational64;
+use std::fmt::{Debug, Display, Formatter};
 use std::hash::Hash;
+use std::num::ParseIntError;
 use std::ops::{Index, IndexMut};
 use std::str::FromStr;
 use thiserror::Error;
 
+pub mod cycle;
+mod direction;
+pub mod geometry;
+mod grid3;
+pub mod input;
+mod interval;
+pub mod metric;
+pub mod parser;
+pub mod search;
+mod sparse;
+mod view;
+
+pub use direction::{Direction, Direction3, Direction8};
+pub use grid3::{Grid3, Neighborhood3, SparseGrid3};
+pub use interval::{Interval, IntervalSet};
+pub use sparse::SparseGrid;
+pub use view::GridView;
+
 pub type Rational128 = Ratio<i128>;
 pub type Vec2i = Vector2<i64>;
 pub type Vec2r = Vector2<Rational64>;
@@ -20,89 +39,6 @@ pub type Mat2i = Matrix2<i64>;
 pub type Mat2r = Matrix2<Rational64>;
 pub type Mat2r128 = Matrix2<Rational128>;
 
-pub fn lp1_norm<T: Scalar + Signed, R: Dim, C: Dim, S: Storage<T, R, C>>(
-    v: &Matrix<T, R, C, S>,
-) -> T {
-    match v.shape() {
-        (1, _) | (_, 1) => v.fold(T::zero(), |a, e| a + e.abs()),
-        _ => {
-            panic!("lp1 norm is only implemented for vectors")
-        }
-    }
-}
-
-#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
-pub enum Direction {
-    North,
-    South,
-    East,
-    West,
-}
-
-impl TryFrom<char> for Direction {
-    type Error = ();
-
-    fn try_from(value: char) -> Result<Self, Self::Error> {
-        Ok(match value {
-            'N' | 'U' | '^' => Self::North,
-            'S' | 'D' | 'v' => Self::South,
-            'E' | 'R' | '>' => Self::East,
-            'W' | 'L' | '<' => Self::West,
-            _ => {
-                return Err(());
-            }
-        })
-    }
-}
-
-impl Direction {
-    pub const VALUES: [Self; 4] = [Self::North, Self::East, Self::South, Self::West];
-
-    pub fn opposite(&self) -> Self {
-        match self {
-            Self::North => Self::South,
-            Self::South => Self::North,
-            Self::East => Self::West,
-            Self::West => Self::East,
-        }
-    }
-
-    pub fn rotate_ccw(&self) -> Direction {
-        match self {
-            Self::North => Self::West,
-            Self::South => Self::East,
-            Self::East => Self::North,
-            Self::West => Self::South,
-        }
-    }
-
-    pub fn rotate_cw(&self) -> Direction {
-        match self {
-            Self::North => Self::East,
-            Self::South => Self::West,
-            Self::East => Self::South,
-            Self::West => Self::North,
-        }
-    }
-
-    pub fn vec(&self) -> Vec2i {
-        match self {
-            Self::North => Vec2i::new(0, -1),
-            Self::South => Vec2i::new(0, 1),
-            Self::East => Vec2i::new(1, 0),
-            Self::West => Vec2i::new(-1, 0),
-        }
-    }
-
-    pub fn offset(&self, pos: &Vec2i) -> Vec2i {
-        self.offset_with_amount(pos, 1)
-    }
-
-    pub fn offset_with_amount(&self, pos: &Vec2i, amount: i64) -> Vec2i {
-        pos + amount * self.vec()
-    }
-}
-
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct Grid<T> {
     pub size_x: usize,
@@ -114,35 +50,101 @@ impl<T> FromStr for Grid<T>
 where
     char: TryInto<T>,
 {
-    type Err = <char as TryInto<T>>::Error;
+    type Err = ParseError;
 
+    /// Parses a grid with one row per non-blank line, ignoring surrounding whitespace. All rows
+    /// must have the same length.
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         let mut size_x = None;
         let mut size_y = 0;
-        let grid = s
-            .lines()
-            .map(str::trim)
-            .filter(|l| !l.is_empty())
-            .flat_map(|l| {
-                size_y += 1;
-                match size_x {
-                    None => size_x = Some(l.len()),
-                    Some(size_x) if size_x == l.len() => {}
-                    _ => {
-                        panic!("non rectangular grid");
-                    }
+        let mut grid = vec![];
+        for l in s.lines().map(str::trim).filter(|l| !l.is_empty()) {
+            let len = l.chars().count();
+            match size_x {
+                None => size_x = Some(len),
+                Some(size_x) if size_x == len => {}
+                Some(size_x) => {
+                    return Err(ParseError::new(format!(
+                        "non rectangular grid, expected {size_x} cells but got {len}"
+                    ))
+                    .located_in(s, l));
                 }
-                l.chars().map(char::try_into)
-            })
-            .collect::<Result<_, _>>()?;
+            }
+            for cell in parse_row(s, l) {
+                grid.push(cell?);
+            }
+            size_y += 1;
+        }
+
+        let size_x = size_x.ok_or_else(|| ParseError::new("empty grid"))?;
+        Ok(Grid {
+            size_x,
+            size_y,
+            grid,
+        })
+    }
+}
+
+fn parse_row<'a, T>(input: &'a str, l: &'a str) -> impl Iterator<Item = Result<T, ParseError>> + 'a
+where
+    char: TryInto<T>,
+{
+    l.chars().enumerate().map(move |(i, c)| {
+        c.try_into().map_err(|_| {
+            ParseError::new(format!("invalid cell '{c}'"))
+                .at_column(i + 1)
+                .located_in(input, l)
+        })
+    })
+}
+
+impl<T: Clone> Grid<T>
+where
+    char: TryInto<T>,
+{
+    /// Parses a grid whose lines may have different lengths, like a drawing. Leading whitespace
+    /// is kept, the cells missing at the end of shorter lines and the rows of blank lines are
+    /// set to `fill`. Trailing blank lines are ignored.
+    pub fn parse_padded(s: &str, fill: T) -> Result<Self, ParseError> {
+        let lines = s.lines().collect::<Vec<_>>();
+        let size_y = lines
+            .iter()
+            .rposition(|l| !l.trim().is_empty())
+            .map_or(0, |y| y + 1);
+        let lines = &lines[..size_y];
+        let size_x = lines.iter().map(|l| l.chars().count()).max().unwrap_or(0);
+        if size_x == 0 {
+            return Err(ParseError::new("empty grid"));
+        }
+
+        let mut grid = Vec::with_capacity(size_x * size_y);
+        for l in lines {
+            let len = grid.len();
+            for cell in parse_row(s, l) {
+                grid.push(cell?);
+            }
+            grid.resize(len + size_x, fill.clone());
+        }
         Ok(Grid {
-            size_x: size_x.unwrap(),
+            size_x,
             size_y,
             grid,
         })
     }
 }
 
+impl<T> Grid<T> {
+    /// A grid from its cells in row-major order.
+    pub fn new(size_x: usize, size_y: usize, grid: Vec<T>) -> Self {
+        assert_eq!(size_x * size_y, grid.len(), "grid size mismatch");
+        Self {
+            size_x,
+            size_y,
+            grid,
+        }
+    }
+}
+
 impl<T: Clone> Grid<T> {
     pub fn new_from_element(size_x: usize, size_y: usize, element: T) -> Self {
         Self {
@@ -174,11 +176,11 @@ impl<T> Grid<T> {
         self.grid.iter()
     }
 
-    pub fn iter_col(&self, x: i64) -> impl Iterator<Item = &T> {
+    pub fn iter_col(&self, x: i64) -> impl DoubleEndedIterator<Item = &T> {
         (0..self.size_y).map(move |y| &self[Vec2i::new(x, y as i64)])
     }
 
-    pub fn iter_row(&self, y: i64) -> impl Iterator<Item = &T> {
+    pub fn iter_row(&self, y: i64) -> impl DoubleEndedIterator<Item = &T> {
         (0..self.size_x).map(move |x| &self[Vec2i::new(x as i64, y)])
     }
 
@@ -191,14 +193,14 @@ impl<T> Grid<T> {
         })
     }
 
-    pub fn pos_iter_col(&self, x: i64) -> impl Iterator<Item = (Vec2i, &T)> {
+    pub fn pos_iter_col(&self, x: i64) -> impl DoubleEndedIterator<Item = (Vec2i, &T)> {
         (0..self.size_y).map(move |y| {
             let pos = Vec2i::new(x, y as i64);
             (pos, &self[pos])
         })
     }
 
-    pub fn pos_iter_row(&self, y: i64) -> impl Iterator<Item = (Vec2i, &T)> {
+    pub fn pos_iter_row(&self, y: i64) -> impl DoubleEndedIterator<Item = (Vec2i, &T)> {
         (0..self.size_x).map(move |x| {
             let pos = Vec2i::new(x as i64, y);
             (pos, &self[pos])
@@ -278,3 +280,178 @@ pub fn parse_vec<T: Scalar + FromStr, const D: usize>(
 
     Ok(SVector::from_iterator(data.into_iter().flatten()))
 }
+
+/// Error returned by the input generators when the puzzle input is malformed.
+///
+/// `line` and `column` are 1-based, `0` means that the position is not known (yet).
+/// Errors created while parsing a fragment of the input are relative to that fragment until
+/// they are moved into the surrounding input with [`ParseError::located_in`].
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    pub day: u32,
+    pub line: usize,
+    pub column: usize,
+    pub reason: String,
+}
+
+impl ParseError {
+    pub fn new(reason: impl Into<String>) -> Self {
+        Self {
+            day: 0,
+            line: 0,
+            column: 0,
+            reason: reason.into(),
+        }
+    }
+
+    pub fn in_day(mut self, day: u32) -> Self {
+        self.day = day;
+        self
+    }
+
+    pub fn at_line(mut self, line: usize) -> Self {
+        self.line = line;
+        self
+    }
+
+    pub fn at_column(mut self, column: usize) -> Self {
+        self.column = column;
+        self
+    }
+
+    /// Translates the position of this error from being relative to `part` to being relative to
+    /// `input`. `part` has to be a subslice of `input`, otherwise the error is returned unchanged.
+    pub fn located_in(mut self, input: &str, part: &str) -> Self {
+        let Some(offset) = (part.as_ptr() as usize).checked_sub(input.as_ptr() as usize) else {
+            return self;
+        };
+        if offset + part.len() > input.len() || !input.is_char_boundary(offset) {
+            return self;
+        }
+
+        let before = &input[..offset];
+        let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+        let part_line = 1 + before.matches('\n').count();
+        let part_column = 1 + before[line_start..].chars().count();
+        if self.line <= 1 {
+            self.column = part_column + self.column.saturating_sub(1);
+        }
+        self.line = part_line + self.line.saturating_sub(1);
+        self
+    }
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let location = [
+            ("day", self.day as usize),
+            ("line", self.line),
+            ("column", self.column),
+        ]
+        .into_iter()
+        .filter(|&(_, n)| n != 0)
+        .map(|(name, n)| format!("{name} {n}"))
+        .collect::<Vec<_>>();
+        if location.is_empty() {
+            write!(f, "{}", self.reason)
+        } else {
+            write!(f, "{}: {}", location.join(", "), self.reason)
+        }
+    }
+}
+
+impl From<ParseIntError> for ParseError {
+    fn from(value: ParseIntError) -> Self {
+        Self::new(format!("invalid number: {value}"))
+    }
+}
+
+impl From<ParseRatioError> for ParseError {
+    fn from(value: ParseRatioError) -> Self {
+        Self::new(format!("invalid number: {value}"))
+    }
+}
+
+/// Like [`parse_lines`], but with a custom parse function whose errors get located in `input`.
+pub fn parse_lines_with<'a, T, B: FromIterator<T>>(
+    input: &'a str,
+    mut f: impl FnMut(&'a str) -> Result<T, ParseError>,
+) -> Result<B, ParseError> {
+    input
+        .lines()
+        .map(str::trim)
+        .filter(|l| !l.is_empty())
+        .map(|l| f(l).map_err(|e| e.located_in(input, l)))
+        .collect()
+}
+
+/// Parses `s` with [`FromStr`] and locates the error at the start of `s`.
+pub fn parse_located<T: FromStr>(input: &str, s: &str) -> Result<T, ParseError>
+where
+    ParseError: From<<T as FromStr>::Err>,
+{
+    s.parse()
+        .map_err(|e| ParseError::from(e).located_in(input, s))
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_parse_error_located_in() {
+        let input = "1,2\n  3,x\n";
+        let line = input.lines().nth(1).unwrap().trim();
+        let e = ParseError::new("invalid number")
+            .at_column(3)
+            .located_in(input, line)
+            .in_day(4);
+        assert_eq!((4, 2, 5), (e.day, e.line, e.column));
+        assert_eq!("day 4, line 2, column 5: invalid number", e.to_string());
+    }
+
+    #[test]
+    fn test_parse_lines_with() {
+        let e =
+            parse_lines_with::<u32, Vec<_>>("1\n\n 2\nthree\n", |l| Ok(l.parse()?)).unwrap_err();
+        assert_eq!((4, 1), (e.line, e.column));
+        let e = parse_lines_with::<u32, Vec<_>>("1 1\n\n  2 x\n", |l| {
+            let (_, n) = l.split_once(' ').unwrap();
+            parse_located(l, n)
+        })
+        .unwrap_err();
+        assert_eq!((3, 5), (e.line, e.column));
+    }
+
+    #[test]
+    fn test_grid_from_str() {
+        let grid: Grid<char> = "\n  ab\n  cd\n\n".parse().unwrap();
+        assert_eq!((2, 2), (grid.size_x, grid.size_y));
+        assert_eq!("abcd", grid.iter().collect::<String>());
+
+        let e = "ab\ncd\n efg\n".parse::<Grid<char>>().unwrap_err();
+        assert_eq!((3, 2), (e.line, e.column));
+        assert_eq!("non rectangular grid, expected 2 cells but got 3", e.reason);
+        let e = "12\n3\u{1f600}\n".parse::<Grid<u8>>().unwrap_err();
+        assert_eq!(
+            (2, 2, "invalid cell '\u{1f600}'"),
+            (e.line, e.column, &*e.reason)
+        );
+        assert_eq!(
+            "empty grid",
+            " \n\n".parse::<Grid<char>>().unwrap_err().reason
+        );
+    }
+
+    #[test]
+    fn test_grid_parse_padded() {
+        let grid = Grid::parse_padded("  ab\n\nc\n   \n\n", '_').unwrap();
+        assert_eq!((4, 3), (grid.size_x, grid.size_y));
+        assert_eq!("  ab____c___", grid.iter().collect::<String>());
+        let e = Grid::<u8>::parse_padded("ab\n \u{1f600}", 0).unwrap_err();
+        assert_eq!((2, 2), (e.line, e.column));
+        assert!(Grid::parse_padded("\n \n", ' ').is_err());
+    }
+}
@@ -0,0 +1,190 @@
+//! Cycle detection for deterministic simulations whose state eventually repeats.
+
+use std::collections::hash_map::Entry;
+use std::hash::Hash;
+
+use num::PrimInt;
+use rustc_hash::FxHashMap;
+
+/// Where the states of a simulation start to repeat, in steps from the initial state.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct Cycle {
+    /// The first step whose state is part of the cycle.
+    pub start: usize,
+    pub length: usize,
+}
+
+impl Cycle {
+    /// The first step with the same state as step `n`.
+    pub fn reduce(&self, n: usize) -> usize {
+        if n < self.start {
+            n
+        } else {
+            self.start + (n - self.start) % self.length
+        }
+    }
+}
+
+/// Finds the cycle by remembering the `key` of every state. Gives up after `limit` steps.
+pub fn find_cycle<S, K: Hash + Eq>(
+    mut state: S,
+    mut step: impl FnMut(&mut S),
+    mut key: impl FnMut(&S) -> K,
+    limit: usize,
+) -> Option<Cycle> {
+    let mut seen = FxHashMap::default();
+    for i in 0..=limit {
+        match seen.entry(key(&state)) {
+            Entry::Occupied(e) => {
+                return Some(Cycle {
+                    start: *e.get(),
+                    length: i - e.get(),
+                })
+            }
+            Entry::Vacant(e) => {
+                e.insert(i);
+            }
+        }
+        step(&mut state);
+    }
+
+    None
+}
+
+/// Finds the cycle with Brent's algorithm, which only keeps two states at a time but runs
+/// about three times as many steps as [`find_cycle`]. Gives up after `limit` steps.
+pub fn brent<S: Clone, K: Eq>(
+    state: S,
+    mut step: impl FnMut(&mut S),
+    mut key: impl FnMut(&S) -> K,
+    limit: usize,
+) -> Option<Cycle> {
+    let mut steps = 0;
+    let mut advance = |s: &mut S| {
+        steps += 1;
+        step(s);
+        steps <= limit
+    };
+
+    // find the length by moving the tortoise to the hare at every power of two
+    let (mut power, mut length) = (1, 1);
+    let mut tortoise = state.clone();
+    let mut hare = state.clone();
+    if !advance(&mut hare) {
+        return None;
+    }
+    while key(&tortoise) != key(&hare) {
+        if power == length {
+            tortoise = hare.clone();
+            power *= 2;
+            length = 0;
+        }
+        if !advance(&mut hare) {
+            return None;
+        }
+        length += 1;
+    }
+
+    // find the start with two states that are `length` steps apart
+    let mut tortoise = state.clone();
+    let mut hare = state;
+    for _ in 0..length {
+        if !advance(&mut hare) {
+            return None;
+        }
+    }
+    let mut start = 0;
+    while key(&tortoise) != key(&hare) {
+        if !advance(&mut tortoise) || !advance(&mut hare) {
+            return None;
+        }
+        start += 1;
+    }
+
+    Some(Cycle { start, length })
+}
+
+/// Runs `n` steps and returns the `value` of the final state. As soon as the `key` of a state
+/// repeats, the remaining whole cycles are skipped, assuming that `value` grows by the same
+/// amount during each of them.
+pub fn extrapolate<S, K: Hash + Eq, V: PrimInt>(
+    mut state: S,
+    n: usize,
+    mut step: impl FnMut(&mut S),
+    mut key: impl FnMut(&S) -> K,
+    mut value: impl FnMut(&S) -> V,
+) -> V {
+    let mut seen = FxHashMap::default();
+    for i in 0..n {
+        let v = value(&state);
+        match seen.entry(key(&state)) {
+            Entry::Occupied(e) => {
+                let (start, start_value) = *e.get();
+                let length = i - start;
+                let periods = V::from((n - i) / length).expect("too many periods");
+                for _ in 0..(n - i) % length {
+                    step(&mut state);
+                }
+                return value(&state) + periods * (v - start_value);
+            }
+            Entry::Vacant(e) => {
+                e.insert((i, v));
+            }
+        }
+        step(&mut state);
+    }
+
+    value(&state)
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    /// x -> x² + 1 mod 255 from 3 enters a cycle of length 6 after 2 steps.
+    fn square(x: &mut u64) {
+        *x = (*x * *x + 1) % 255;
+    }
+
+    #[test]
+    fn test_find_cycle() {
+        let expected = Cycle {
+            start: 2,
+            length: 6,
+        };
+        assert_eq!(Some(expected), find_cycle(3, square, |x| *x, 100));
+        assert_eq!(Some(expected), brent(3, square, |x| *x, 100));
+        assert_eq!(None, find_cycle(3, square, |x| *x, 5));
+        assert_eq!(None, brent(3, square, |x| *x, 5));
+
+        assert_eq!(1, expected.reduce(1));
+        assert_eq!(3, expected.reduce(9));
+        assert_eq!(7, expected.reduce(7 + 6 * 1000));
+    }
+
+    #[test]
+    fn test_extrapolate() {
+        // the value counts how often the state was even, which repeats with the cycle
+        let run = |n: usize| {
+            extrapolate(
+                (3u64, 0u64),
+                n,
+                |(x, evens)| {
+                    square(x);
+                    *evens += u64::from(x.is_multiple_of(2));
+                },
+                |(x, _)| *x,
+                |(_, evens)| *evens,
+            )
+        };
+        let mut naive = (3u64, 0u64);
+        for n in 0..50 {
+            assert_eq!(naive.1, run(n), "after {n} steps");
+            square(&mut naive.0);
+            naive.1 += u64::from(naive.0.is_multiple_of(2));
+        }
+        assert!(run(1_000_000_000_000) > 0);
+    }
+}
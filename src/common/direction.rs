@@ -0,0 +1,373 @@
+use std::str::FromStr;
+
+use nalgebra::{Scalar, Vector2, Vector3};
+use num::Signed;
+
+use super::{ParseError, Vec2i, Vec3i};
+
+/// One of the four directions on a grid whose y axis points down (south).
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum Direction {
+    North,
+    South,
+    East,
+    West,
+}
+
+impl TryFrom<char> for Direction {
+    type Error = ParseError;
+
+    /// Accepts compass letters, up/down/left/right letters and arrows.
+    fn try_from(value: char) -> Result<Self, Self::Error> {
+        Ok(match value {
+            'N' | 'U' | '^' => Self::North,
+            'S' | 'D' | 'v' => Self::South,
+            'E' | 'R' | '>' => Self::East,
+            'W' | 'L' | '<' => Self::West,
+            _ => {
+                return Err(ParseError::new(format!("unknown direction '{value}'")));
+            }
+        })
+    }
+}
+
+impl FromStr for Direction {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut chars = s.chars();
+        match (chars.next(), chars.next()) {
+            (Some(c), None) => c.try_into(),
+            _ => Err(ParseError::new(format!("unknown direction '{s}'"))),
+        }
+    }
+}
+
+impl Direction {
+    /// Clockwise starting with north.
+    pub const VALUES: [Self; 4] = [Self::North, Self::East, Self::South, Self::West];
+
+    pub fn opposite(&self) -> Self {
+        match self {
+            Self::North => Self::South,
+            Self::South => Self::North,
+            Self::East => Self::West,
+            Self::West => Self::East,
+        }
+    }
+
+    pub fn rotate_ccw(&self) -> Direction {
+        match self {
+            Self::North => Self::West,
+            Self::South => Self::East,
+            Self::East => Self::North,
+            Self::West => Self::South,
+        }
+    }
+
+    pub fn rotate_cw(&self) -> Direction {
+        match self {
+            Self::North => Self::East,
+            Self::South => Self::West,
+            Self::East => Self::South,
+            Self::West => Self::North,
+        }
+    }
+
+    pub fn is_horizontal(&self) -> bool {
+        matches!(self, Self::East | Self::West)
+    }
+
+    /// The arrow pointing in this direction, as used in puzzle drawings.
+    pub fn arrow(&self) -> char {
+        match self {
+            Self::North => '^',
+            Self::South => 'v',
+            Self::East => '>',
+            Self::West => '<',
+        }
+    }
+
+    pub fn vec(&self) -> Vec2i {
+        self.vector()
+    }
+
+    /// Like [`Direction::vec`] for any signed coordinate type.
+    pub fn vector<T: Scalar + Signed>(&self) -> Vector2<T> {
+        let (zero, one) = (T::zero(), T::one());
+        match self {
+            Self::North => Vector2::new(zero, -one),
+            Self::South => Vector2::new(zero, one),
+            Self::East => Vector2::new(one, zero),
+            Self::West => Vector2::new(-one, zero),
+        }
+    }
+
+    pub fn offset(&self, pos: &Vec2i) -> Vec2i {
+        self.offset_with_amount(pos, 1)
+    }
+
+    pub fn offset_with_amount(&self, pos: &Vec2i, amount: i64) -> Vec2i {
+        pos + amount * self.vec()
+    }
+}
+
+/// The four directions of [`Direction`] and the four diagonals between them.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum Direction8 {
+    North,
+    NorthEast,
+    East,
+    SouthEast,
+    South,
+    SouthWest,
+    West,
+    NorthWest,
+}
+
+impl From<Direction> for Direction8 {
+    fn from(value: Direction) -> Self {
+        match value {
+            Direction::North => Self::North,
+            Direction::South => Self::South,
+            Direction::East => Self::East,
+            Direction::West => Self::West,
+        }
+    }
+}
+
+impl TryFrom<&str> for Direction8 {
+    type Error = ParseError;
+
+    /// Accepts compass abbreviations like `N` or `SW`.
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        Self::VALUES
+            .into_iter()
+            .find(|d| d.abbreviation() == value)
+            .ok_or_else(|| ParseError::new(format!("unknown direction '{value}'")))
+    }
+}
+
+impl FromStr for Direction8 {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.try_into()
+    }
+}
+
+impl Direction8 {
+    /// Clockwise starting with north.
+    pub const VALUES: [Self; 8] = [
+        Self::North,
+        Self::NorthEast,
+        Self::East,
+        Self::SouthEast,
+        Self::South,
+        Self::SouthWest,
+        Self::West,
+        Self::NorthWest,
+    ];
+
+    fn index(&self) -> usize {
+        Self::VALUES.iter().position(|d| d == self).unwrap()
+    }
+
+    pub fn opposite(&self) -> Self {
+        Self::VALUES[(self.index() + 4) % 8]
+    }
+
+    /// Turns by 45° clockwise.
+    pub fn rotate_cw(&self) -> Self {
+        Self::VALUES[(self.index() + 1) % 8]
+    }
+
+    /// Turns by 45° counterclockwise.
+    pub fn rotate_ccw(&self) -> Self {
+        Self::VALUES[(self.index() + 7) % 8]
+    }
+
+    pub fn is_diagonal(&self) -> bool {
+        self.index() % 2 == 1
+    }
+
+    pub fn abbreviation(&self) -> &'static str {
+        ["N", "NE", "E", "SE", "S", "SW", "W", "NW"][self.index()]
+    }
+
+    pub fn vec(&self) -> Vec2i {
+        self.vector()
+    }
+
+    /// Like [`Direction8::vec`] for any signed coordinate type.
+    pub fn vector<T: Scalar + Signed>(&self) -> Vector2<T> {
+        let (zero, one) = (T::zero(), T::one());
+        let (x, y) = match self {
+            Self::North => (zero, -one),
+            Self::NorthEast => (one.clone(), -one),
+            Self::East => (one, zero),
+            Self::SouthEast => (one.clone(), one),
+            Self::South => (zero, one),
+            Self::SouthWest => (-one.clone(), one),
+            Self::West => (-one, zero),
+            Self::NorthWest => (-one.clone(), -one),
+        };
+        Vector2::new(x, y)
+    }
+
+    pub fn offset(&self, pos: &Vec2i) -> Vec2i {
+        pos + self.vec()
+    }
+}
+
+/// The six directions along the axes in three dimensions.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum Direction3 {
+    PosX,
+    NegX,
+    PosY,
+    NegY,
+    PosZ,
+    NegZ,
+}
+
+impl TryFrom<char> for Direction3 {
+    type Error = ParseError;
+
+    /// Accepts `x`, `y` and `z` for the positive and `X`, `Y` and `Z` for the negative
+    /// directions.
+    fn try_from(value: char) -> Result<Self, Self::Error> {
+        Ok(match value {
+            'x' => Self::PosX,
+            'X' => Self::NegX,
+            'y' => Self::PosY,
+            'Y' => Self::NegY,
+            'z' => Self::PosZ,
+            'Z' => Self::NegZ,
+            _ => {
+                return Err(ParseError::new(format!("unknown direction '{value}'")));
+            }
+        })
+    }
+}
+
+impl Direction3 {
+    pub const VALUES: [Self; 6] = [
+        Self::PosX,
+        Self::NegX,
+        Self::PosY,
+        Self::NegY,
+        Self::PosZ,
+        Self::NegZ,
+    ];
+
+    pub fn opposite(&self) -> Self {
+        match self {
+            Self::PosX => Self::NegX,
+            Self::NegX => Self::PosX,
+            Self::PosY => Self::NegY,
+            Self::NegY => Self::PosY,
+            Self::PosZ => Self::NegZ,
+            Self::NegZ => Self::PosZ,
+        }
+    }
+
+    /// The direction pointing along `v`, `None` if `v` isn't a unit vector along an axis.
+    pub fn from_vec(v: &Vec3i) -> Option<Self> {
+        Self::VALUES.into_iter().find(|d| d.vec() == *v)
+    }
+
+    /// Turns by 90° around `axis` following the right-hand rule. Directions parallel to
+    /// `axis` stay the same.
+    pub fn rotate_around(&self, axis: Direction3) -> Self {
+        Self::from_vec(&axis.vec().cross(&self.vec())).unwrap_or(*self)
+    }
+
+    pub fn vec(&self) -> Vec3i {
+        self.vector()
+    }
+
+    /// Like [`Direction3::vec`] for any signed coordinate type.
+    pub fn vector<T: Scalar + Signed>(&self) -> Vector3<T> {
+        let (zero, one) = (T::zero(), T::one());
+        match self {
+            Self::PosX => Vector3::new(one, zero.clone(), zero),
+            Self::NegX => Vector3::new(-one, zero.clone(), zero),
+            Self::PosY => Vector3::new(zero.clone(), one, zero),
+            Self::NegY => Vector3::new(zero.clone(), -one, zero),
+            Self::PosZ => Vector3::new(zero.clone(), zero, one),
+            Self::NegZ => Vector3::new(zero.clone(), zero, -one),
+        }
+    }
+
+    pub fn offset(&self, pos: &Vec3i) -> Vec3i {
+        pos + self.vec()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_direction() {
+        for d in Direction::VALUES {
+            assert_eq!(d, d.rotate_cw().rotate_ccw());
+            assert_eq!(d.opposite(), d.rotate_cw().rotate_cw());
+            assert_eq!(Ok(d), Direction::try_from(d.arrow()));
+            assert_eq!(d.vec().map(|c| c as i32), d.vector::<i32>());
+            assert_eq!(d.is_horizontal(), d.vec().y == 0);
+        }
+        assert_eq!(Ok(Direction::West), "L".parse());
+        assert_eq!(Ok(Direction::North), "U".parse());
+        assert_eq!(
+            "unknown direction 'x'",
+            "x".parse::<Direction>().unwrap_err().reason
+        );
+        assert!("LR".parse::<Direction>().is_err());
+    }
+
+    #[test]
+    fn test_direction8() {
+        let mut d = Direction8::North;
+        let mut visited = vec![];
+        for _ in 0..8 {
+            visited.push(d.vec());
+            assert_eq!(d, d.rotate_ccw().rotate_cw());
+            assert_eq!(Vec2i::zeros(), d.vec() + d.opposite().vec());
+            assert_eq!(d.is_diagonal(), d.vec().abs().sum() == 2);
+            assert_eq!(Ok(d), d.abbreviation().parse());
+            d = d.rotate_cw();
+        }
+        assert_eq!(Direction8::North, d);
+        visited.sort_by_key(|v| (v.x, v.y));
+        visited.dedup();
+        assert_eq!(8, visited.len());
+        assert_eq!(Vec2i::new(1, -1), Direction8::NorthEast.vec());
+        assert_eq!(Direction8::West, Direction::West.into());
+    }
+
+    #[test]
+    fn test_direction3() {
+        for d in Direction3::VALUES {
+            assert_eq!(Vec3i::zeros(), d.vec() + d.opposite().vec());
+            assert_eq!(Some(d), Direction3::from_vec(&d.vec()));
+            assert_eq!(d, d.rotate_around(d));
+        }
+        assert_eq!(
+            Direction3::PosY,
+            Direction3::PosX.rotate_around(Direction3::PosZ)
+        );
+        assert_eq!(
+            Direction3::NegX,
+            Direction3::PosY.rotate_around(Direction3::PosZ)
+        );
+        assert_eq!(
+            Direction3::PosX,
+            Direction3::PosY.rotate_around(Direction3::NegZ)
+        );
+        assert_eq!(Ok(Direction3::NegZ), Direction3::try_from('Z'));
+    }
+}
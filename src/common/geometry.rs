@@ -0,0 +1,562 @@
+//! Exact geometry in the plane over rationals like [`Rational64`](num::Rational64) and
+//! [`Rational128`](super::Rational128), so that intersections never suffer from rounding.
+
+use std::mem::swap;
+
+use nalgebra::{
+    ClosedAddAssign, ClosedDivAssign, ClosedMulAssign, ClosedSubAssign, Matrix2, Scalar, Vector2,
+};
+use num::Signed;
+
+/// The number types the geometry works with.
+pub trait Exact:
+    Scalar
+    + Copy
+    + Signed
+    + PartialOrd
+    + ClosedAddAssign
+    + ClosedSubAssign
+    + ClosedMulAssign
+    + ClosedDivAssign
+{
+}
+
+impl<T> Exact for T where
+    T: Scalar
+        + Copy
+        + Signed
+        + PartialOrd
+        + ClosedAddAssign
+        + ClosedSubAssign
+        + ClosedMulAssign
+        + ClosedDivAssign
+{
+}
+
+/// The z coordinate of the cross product, positive if `b` points counterclockwise of `a`.
+pub fn cross<T: Exact>(a: &Vector2<T>, b: &Vector2<T>) -> T {
+    a.x * b.y - a.y * b.x
+}
+
+/// How the path from `a` over `b` to `c` turns, with the y axis pointing up. In grids whose
+/// y axis points down, clockwise and counterclockwise swap.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum Orientation {
+    CounterClockwise,
+    Clockwise,
+    Collinear,
+}
+
+pub fn orientation<T: Exact>(a: &Vector2<T>, b: &Vector2<T>, c: &Vector2<T>) -> Orientation {
+    let z = cross(&(b - a), &(c - a));
+    if z.is_positive() {
+        Orientation::CounterClockwise
+    } else if z.is_negative() {
+        Orientation::Clockwise
+    } else {
+        Orientation::Collinear
+    }
+}
+
+/// The larger of two lower bounds, `None` is unbounded.
+fn max_bound<T: Exact>(a: Option<T>, b: Option<T>) -> Option<T> {
+    match (a, b) {
+        (Some(a), Some(b)) => Some(if a > b { a } else { b }),
+        (a, b) => a.or(b),
+    }
+}
+
+/// The smaller of two upper bounds, `None` is unbounded.
+fn min_bound<T: Exact>(a: Option<T>, b: Option<T>) -> Option<T> {
+    match (a, b) {
+        (Some(a), Some(b)) => Some(if a < b { a } else { b }),
+        (a, b) => a.or(b),
+    }
+}
+
+fn in_range<T: Exact>((lo, hi): (Option<T>, Option<T>), t: T) -> bool {
+    lo.is_none_or(|lo| lo <= t) && hi.is_none_or(|hi| t <= hi)
+}
+
+/// The points `origin + t * direction` for all `t` in `range`, shared by segments, rays and
+/// lines.
+pub trait Linear<T: Exact> {
+    fn origin(&self) -> Vector2<T>;
+
+    fn direction(&self) -> Vector2<T>;
+
+    /// The smallest and largest `t`, `None` if unbounded.
+    fn range(&self) -> (Option<T>, Option<T>);
+
+    fn point_at(&self, t: T) -> Vector2<T> {
+        self.origin() + self.direction() * t
+    }
+
+    fn contains(&self, p: &Vector2<T>) -> bool {
+        let (o, d) = (self.origin(), self.direction());
+        if d.iter().all(T::is_zero) {
+            return *p == o;
+        }
+
+        let v = p - o;
+        cross(&d, &v).is_zero() && in_range(self.range(), v.dot(&d) / d.dot(&d))
+    }
+
+    /// The points shared with `other`, `None` if there are none.
+    fn intersection(&self, other: &impl Linear<T>) -> Option<Intersection<T>>
+    where
+        Self: Sized,
+    {
+        let (p1, d1) = (self.origin(), self.direction());
+        let (p2, d2) = (other.origin(), other.direction());
+        if d1.iter().all(T::is_zero) {
+            return other.contains(&p1).then_some(Intersection::Point(p1));
+        }
+        if d2.iter().all(T::is_zero) {
+            return self.contains(&p2).then_some(Intersection::Point(p2));
+        }
+
+        let w = p2 - p1;
+        let denominator = cross(&d1, &d2);
+        if !denominator.is_zero() {
+            let t = cross(&w, &d2) / denominator;
+            let u = cross(&w, &d1) / denominator;
+            return (in_range(self.range(), t) && in_range(other.range(), u))
+                .then(|| Intersection::Point(self.point_at(t)));
+        }
+        if !cross(&w, &d1).is_zero() {
+            // parallel
+            return None;
+        }
+
+        // collinear, so express the range of `other` in terms of `t`
+        let dd = d1.dot(&d1);
+        let offset = w.dot(&d1) / dd;
+        let scale = d2.dot(&d1) / dd;
+        let (lo2, hi2) = other.range();
+        let (mut lo, mut hi) = (
+            lo2.map(|u| offset + scale * u),
+            hi2.map(|u| offset + scale * u),
+        );
+        if scale.is_negative() {
+            swap(&mut lo, &mut hi);
+        }
+        let (lo1, hi1) = self.range();
+        Some(match (max_bound(lo1, lo), min_bound(hi1, hi)) {
+            (Some(lo), Some(hi)) if lo > hi => return None,
+            (Some(lo), Some(hi)) if lo == hi => Intersection::Point(self.point_at(lo)),
+            (Some(lo), Some(hi)) => {
+                Intersection::Segment(Segment::new(self.point_at(lo), self.point_at(hi)))
+            }
+            (Some(lo), None) => Intersection::Ray(Ray::new(self.point_at(lo), d1)),
+            (None, Some(hi)) => Intersection::Ray(Ray::new(self.point_at(hi), -d1)),
+            (None, None) => Intersection::Line(Line::new(p1, d1)),
+        })
+    }
+}
+
+/// The shared points of two [`Linear`] objects, or of one and a [`Rect`].
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum Intersection<T: Scalar> {
+    Point(Vector2<T>),
+    Segment(Segment<T>),
+    Ray(Ray<T>),
+    Line(Line<T>),
+}
+
+/// The points between `a` and `b`, both included.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct Segment<T: Scalar> {
+    pub a: Vector2<T>,
+    pub b: Vector2<T>,
+}
+
+impl<T: Exact> Segment<T> {
+    pub fn new(a: Vector2<T>, b: Vector2<T>) -> Self {
+        Self { a, b }
+    }
+
+    pub fn transform(&self, affine: &Affine<T>) -> Self {
+        Self::new(affine.apply(&self.a), affine.apply(&self.b))
+    }
+}
+
+impl<T: Exact> Linear<T> for Segment<T> {
+    fn origin(&self) -> Vector2<T> {
+        self.a
+    }
+
+    fn direction(&self) -> Vector2<T> {
+        self.b - self.a
+    }
+
+    fn range(&self) -> (Option<T>, Option<T>) {
+        (Some(T::zero()), Some(T::one()))
+    }
+}
+
+/// The points starting at `origin` in `direction`.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct Ray<T: Scalar> {
+    pub origin: Vector2<T>,
+    pub direction: Vector2<T>,
+}
+
+impl<T: Exact> Ray<T> {
+    pub fn new(origin: Vector2<T>, direction: Vector2<T>) -> Self {
+        Self { origin, direction }
+    }
+
+    pub fn transform(&self, affine: &Affine<T>) -> Self {
+        Self::new(
+            affine.apply(&self.origin),
+            affine.apply_vector(&self.direction),
+        )
+    }
+}
+
+impl<T: Exact> Linear<T> for Ray<T> {
+    fn origin(&self) -> Vector2<T> {
+        self.origin
+    }
+
+    fn direction(&self) -> Vector2<T> {
+        self.direction
+    }
+
+    fn range(&self) -> (Option<T>, Option<T>) {
+        (Some(T::zero()), None)
+    }
+}
+
+/// The line through `point` in `direction`.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct Line<T: Scalar> {
+    pub point: Vector2<T>,
+    pub direction: Vector2<T>,
+}
+
+impl<T: Exact> Line<T> {
+    pub fn new(point: Vector2<T>, direction: Vector2<T>) -> Self {
+        Self { point, direction }
+    }
+
+    pub fn through(a: Vector2<T>, b: Vector2<T>) -> Self {
+        Self::new(a, b - a)
+    }
+
+    pub fn transform(&self, affine: &Affine<T>) -> Self {
+        Self::new(
+            affine.apply(&self.point),
+            affine.apply_vector(&self.direction),
+        )
+    }
+}
+
+impl<T: Exact> Linear<T> for Line<T> {
+    fn origin(&self) -> Vector2<T> {
+        self.point
+    }
+
+    fn direction(&self) -> Vector2<T> {
+        self.direction
+    }
+
+    fn range(&self) -> (Option<T>, Option<T>) {
+        (None, None)
+    }
+}
+
+/// The axis-aligned rectangle from `min` to `max`, borders included.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct Rect<T: Scalar> {
+    pub min: Vector2<T>,
+    pub max: Vector2<T>,
+}
+
+impl<T: Exact> Rect<T> {
+    /// The rectangle with the opposite corners `a` and `b`.
+    pub fn new(a: Vector2<T>, b: Vector2<T>) -> Self {
+        Self {
+            min: a.zip_map(&b, |a, b| if a < b { a } else { b }),
+            max: a.zip_map(&b, |a, b| if a > b { a } else { b }),
+        }
+    }
+
+    pub fn contains(&self, p: &Vector2<T>) -> bool {
+        (0..2).all(|i| self.min[i] <= p[i] && p[i] <= self.max[i])
+    }
+
+    pub fn intersection(&self, other: &Self) -> Option<Self> {
+        let min = self
+            .min
+            .zip_map(&other.min, |a, b| if a > b { a } else { b });
+        let max = self
+            .max
+            .zip_map(&other.max, |a, b| if a < b { a } else { b });
+        (0..2)
+            .all(|i| min[i] <= max[i])
+            .then_some(Self { min, max })
+    }
+
+    /// Counterclockwise with the y axis pointing up, starting at `min`.
+    pub fn corners(&self) -> [Vector2<T>; 4] {
+        [
+            self.min,
+            Vector2::new(self.max.x, self.min.y),
+            self.max,
+            Vector2::new(self.min.x, self.max.y),
+        ]
+    }
+
+    /// The borders between consecutive [`Rect::corners`].
+    pub fn edges(&self) -> [Segment<T>; 4] {
+        let c = self.corners();
+        [0, 1, 2, 3].map(|i| Segment::new(c[i], c[(i + 1) % 4]))
+    }
+
+    /// The part of `linear` inside the rectangle, which is bounded, so it is a point or a
+    /// segment.
+    pub fn clip(&self, linear: &impl Linear<T>) -> Option<Intersection<T>> {
+        let (p, d) = (linear.origin(), linear.direction());
+        if d.iter().all(T::is_zero) {
+            return self.contains(&p).then_some(Intersection::Point(p));
+        }
+
+        let (mut lo, mut hi) = linear.range();
+        for i in 0..2 {
+            if d[i].is_zero() {
+                if p[i] < self.min[i] || p[i] > self.max[i] {
+                    return None;
+                }
+                continue;
+            }
+
+            let (mut t1, mut t2) = ((self.min[i] - p[i]) / d[i], (self.max[i] - p[i]) / d[i]);
+            if t1 > t2 {
+                swap(&mut t1, &mut t2);
+            }
+            lo = max_bound(lo, Some(t1));
+            hi = min_bound(hi, Some(t2));
+        }
+
+        // at least one coordinate of `d` isn't zero, so both bounds are set
+        let (lo, hi) = (lo?, hi?);
+        if lo > hi {
+            None
+        } else if lo == hi {
+            Some(Intersection::Point(linear.point_at(lo)))
+        } else {
+            Some(Intersection::Segment(Segment::new(
+                linear.point_at(lo),
+                linear.point_at(hi),
+            )))
+        }
+    }
+}
+
+/// The map `p ↦ linear * p + translation`.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct Affine<T: Scalar> {
+    pub linear: Matrix2<T>,
+    pub translation: Vector2<T>,
+}
+
+impl<T: Exact> Affine<T> {
+    pub fn identity() -> Self {
+        Self::linear(Matrix2::identity())
+    }
+
+    pub fn linear(linear: Matrix2<T>) -> Self {
+        Self {
+            linear,
+            translation: Vector2::zeros(),
+        }
+    }
+
+    pub fn translation(translation: Vector2<T>) -> Self {
+        Self {
+            linear: Matrix2::identity(),
+            translation,
+        }
+    }
+
+    pub fn scaling(factor: T) -> Self {
+        Self::linear(Matrix2::identity() * factor)
+    }
+
+    /// Rotates by `quarter_turns` times 90° counterclockwise around the origin, with the y axis
+    /// pointing up.
+    pub fn rotation(quarter_turns: i32) -> Self {
+        let (zero, one) = (T::zero(), T::one());
+        Self::linear(match quarter_turns.rem_euclid(4) {
+            0 => Matrix2::new(one, zero, zero, one),
+            1 => Matrix2::new(zero, -one, one, zero),
+            2 => Matrix2::new(-one, zero, zero, -one),
+            _ => Matrix2::new(zero, one, -one, zero),
+        })
+    }
+
+    pub fn apply(&self, p: &Vector2<T>) -> Vector2<T> {
+        self.linear * p + self.translation
+    }
+
+    /// Applies only the linear part, as directions aren't translated.
+    pub fn apply_vector(&self, v: &Vector2<T>) -> Vector2<T> {
+        self.linear * v
+    }
+
+    /// The map that applies `self` first and then `other`.
+    pub fn then(&self, other: &Self) -> Self {
+        Self {
+            linear: other.linear * self.linear,
+            translation: other.linear * self.translation + other.translation,
+        }
+    }
+
+    /// `None` if the map collapses the plane onto a line or a point.
+    pub fn inverse(&self) -> Option<Self> {
+        let m = &self.linear;
+        let determinant = m[(0, 0)] * m[(1, 1)] - m[(0, 1)] * m[(1, 0)];
+        if determinant.is_zero() {
+            return None;
+        }
+
+        let linear = Matrix2::new(m[(1, 1)], -m[(0, 1)], -m[(1, 0)], m[(0, 0)]) / determinant;
+        Some(Self {
+            linear,
+            translation: -(linear * self.translation),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use num::Rational64;
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::common::Vec2r;
+
+    fn v(x: i64, y: i64) -> Vec2r {
+        Vec2r::new(x.into(), y.into())
+    }
+
+    fn segment(a: (i64, i64), b: (i64, i64)) -> Segment<Rational64> {
+        Segment::new(v(a.0, a.1), v(b.0, b.1))
+    }
+
+    #[test]
+    fn test_orientation() {
+        assert_eq!(
+            Orientation::CounterClockwise,
+            orientation(&v(0, 0), &v(1, 0), &v(1, 1))
+        );
+        assert_eq!(
+            Orientation::Clockwise,
+            orientation(&v(0, 0), &v(1, 0), &v(1, -1))
+        );
+        assert_eq!(
+            Orientation::Collinear,
+            orientation(&v(0, 0), &v(1, 1), &v(3, 3))
+        );
+    }
+
+    #[test]
+    fn test_intersections() {
+        let a = segment((0, 0), (4, 2));
+        let b = segment((0, 2), (3, -1));
+        let half = Rational64::new(1, 2);
+        assert_eq!(
+            Some(Intersection::Point(Vec2r::new(
+                Rational64::from(1) + Rational64::new(1, 3),
+                Rational64::new(2, 3)
+            ))),
+            a.intersection(&b)
+        );
+        assert_eq!(None, a.intersection(&segment((0, 1), (4, 3))));
+        assert_eq!(None, a.intersection(&segment((5, 0), (6, -5))));
+        assert_eq!(
+            Some(Intersection::Segment(segment((2, 1), (4, 2)))),
+            a.intersection(&segment((6, 3), (2, 1)))
+        );
+        assert_eq!(
+            Some(Intersection::Point(v(4, 2))),
+            a.intersection(&segment((4, 2), (8, 4)))
+        );
+        assert_eq!(
+            Some(Intersection::Point(v(1, 0))),
+            segment((1, 0), (1, 0)).intersection(&Line::through(v(0, 0), v(2, 0)))
+        );
+        assert!(a.contains(&Vec2r::new(1.into(), half)));
+        assert!(!a.contains(&v(6, 3)));
+
+        let ray = Ray::new(v(2, 1), v(-2, -1));
+        assert_eq!(
+            Some(Intersection::Segment(segment((2, 1), (0, 0)))),
+            ray.intersection(&a)
+        );
+        let line = Line::through(v(0, 0), v(4, 2));
+        assert_eq!(
+            Some(Intersection::Ray(Ray::new(v(2, 1), v(-4, -2)))),
+            line.intersection(&ray)
+        );
+        assert_eq!(
+            Some(Intersection::Line(line)),
+            line.intersection(&Line::through(v(8, 4), v(-4, -2)))
+        );
+        assert_eq!(
+            Some(Intersection::Point(v(0, 0))),
+            ray.intersection(&Line::new(v(0, 0), v(0, 1)))
+        );
+        assert_eq!(None, ray.intersection(&Line::new(v(3, 0), v(0, 1))));
+    }
+
+    #[test]
+    fn test_rect() {
+        let r = Rect::new(v(4, 0), v(0, 2));
+        assert_eq!((v(0, 0), v(4, 2)), (r.min, r.max));
+        assert!(r.contains(&v(4, 1)) && !r.contains(&v(5, 1)));
+        assert_eq!(
+            Some(Rect::new(v(2, 1), v(4, 2))),
+            r.intersection(&Rect::new(v(2, 1), v(6, 6)))
+        );
+        assert_eq!(None, r.intersection(&Rect::new(v(5, 0), v(6, 6))));
+        let perimeter: Rational64 = r.edges().iter().map(|e| (e.b - e.a).abs().sum()).sum();
+        assert_eq!(Rational64::from(12), perimeter);
+
+        assert_eq!(
+            Some(Intersection::Segment(segment((0, 0), (4, 2)))),
+            r.clip(&Line::through(v(-2, -1), v(2, 1)))
+        );
+        assert_eq!(
+            Some(Intersection::Segment(segment((1, 1), (4, 1)))),
+            r.clip(&Ray::new(v(1, 1), v(1, 0)))
+        );
+        assert_eq!(
+            Some(Intersection::Point(v(4, 2))),
+            r.clip(&segment((6, 0), (2, 4)))
+        );
+        assert_eq!(None, r.clip(&segment((5, 0), (5, 2))));
+    }
+
+    #[test]
+    fn test_affine() {
+        let rotate = Affine::rotation(1);
+        assert_eq!(v(-2, 1), rotate.apply(&v(1, 2)));
+        let map = rotate
+            .then(&Affine::translation(v(3, -1)))
+            .then(&Affine::scaling(2.into()));
+        assert_eq!(v(2, 0), map.apply(&v(1, 2)));
+        let inverse = map.inverse().unwrap();
+        assert_eq!(v(1, 2), inverse.apply(&v(2, 0)));
+        assert_eq!(Affine::identity(), map.then(&inverse));
+        assert_eq!(None, Affine::scaling(Rational64::from(0)).inverse());
+        assert_eq!(Affine::<Rational64>::rotation(-1), Affine::rotation(3));
+
+        let s = segment((0, 0), (1, 0)).transform(&Affine::rotation(1));
+        assert_eq!(segment((0, 0), (0, 1)), s);
+        let ray = Ray::new(v(1, 0), v(1, 0)).transform(&Affine::translation(v(0, 5)));
+        assert_eq!(Ray::new(v(1, 5), v(1, 0)), ray);
+    }
+}
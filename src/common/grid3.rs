@@ -0,0 +1,433 @@
+use std::ops::{Index, IndexMut};
+
+use rustc_hash::FxHashMap;
+
+use super::search::flood_fill;
+use super::Vec3i;
+
+/// Which of the 26 surrounding cells count as neighbours.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Neighborhood3 {
+    /// The 6 cells sharing a face.
+    Faces,
+    /// The 18 cells sharing a face or an edge.
+    Edges,
+    /// The 26 cells sharing a face, an edge or a corner.
+    Corners,
+}
+
+impl Neighborhood3 {
+    /// The offsets of the neighbours.
+    pub fn offsets(self) -> impl Iterator<Item = Vec3i> {
+        let max_norm = match self {
+            Neighborhood3::Faces => 1,
+            Neighborhood3::Edges => 2,
+            Neighborhood3::Corners => 3,
+        };
+        (-1..=1)
+            .flat_map(|z| (-1..=1).flat_map(move |y| (-1..=1).map(move |x| Vec3i::new(x, y, z))))
+            .filter(move |d| (1..=max_norm).contains(&d.abs().sum()))
+    }
+
+    /// The neighbours of `pos`.
+    pub fn around(self, pos: &Vec3i) -> impl Iterator<Item = Vec3i> {
+        let pos = *pos;
+        self.offsets().map(move |d| pos + d)
+    }
+}
+
+/// A dense box of voxels from `(0, 0, 0)` to `(size_x - 1, size_y - 1, size_z - 1)`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Grid3<T> {
+    pub size_x: usize,
+    pub size_y: usize,
+    pub size_z: usize,
+    grid: Vec<T>,
+}
+
+impl<T: Clone> Grid3<T> {
+    pub fn new_from_element(size_x: usize, size_y: usize, size_z: usize, element: T) -> Self {
+        Self {
+            size_x,
+            size_y,
+            size_z,
+            grid: vec![element; size_x * size_y * size_z],
+        }
+    }
+}
+
+impl<T> Grid3<T> {
+    pub fn in_bounds(&self, pos: &Vec3i) -> bool {
+        (0..self.size_x as i64).contains(&pos.x)
+            && (0..self.size_y as i64).contains(&pos.y)
+            && (0..self.size_z as i64).contains(&pos.z)
+    }
+
+    pub fn get(&self, pos: &Vec3i) -> Option<&T> {
+        self.in_bounds(pos).then(|| &self[*pos])
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &T> {
+        self.grid.iter()
+    }
+
+    pub fn pos_iter(&self) -> impl Iterator<Item = (Vec3i, &T)> {
+        self.grid.iter().enumerate().map(|(i, t)| {
+            let (x, y) = (i % self.size_x, i / self.size_x % self.size_y);
+            let z = i / (self.size_x * self.size_y);
+            (Vec3i::new(x as _, y as _, z as _), t)
+        })
+    }
+
+    /// The neighbours of `pos` that are inside the grid.
+    pub fn neighbors(
+        &self,
+        pos: &Vec3i,
+        neighborhood: Neighborhood3,
+    ) -> impl Iterator<Item = Vec3i> + '_ {
+        neighborhood.around(pos).filter(|n| self.in_bounds(n))
+    }
+
+    fn is_border(&self, pos: &Vec3i) -> bool {
+        [
+            (pos.x, self.size_x),
+            (pos.y, self.size_y),
+            (pos.z, self.size_z),
+        ]
+        .iter()
+        .any(|&(c, size)| c == 0 || c == size as i64 - 1)
+    }
+
+    /// The number of faces between a `solid` cell and a cell that isn't, where everything
+    /// outside the grid is empty.
+    pub fn surface_area(&self, mut solid: impl FnMut(&T) -> bool) -> usize {
+        let solid = self.map(|t| solid(t));
+        solid
+            .pos_iter()
+            .filter(|(_, s)| **s)
+            .map(|(pos, _)| {
+                Neighborhood3::Faces
+                    .around(&pos)
+                    .filter(|n| solid.get(n) != Some(&true))
+                    .count()
+            })
+            .sum()
+    }
+
+    /// The cells that aren't `solid` and are connected to the outside of the grid through
+    /// faces of such cells.
+    pub fn exterior(&self, mut solid: impl FnMut(&T) -> bool) -> Grid3<bool> {
+        let solid = self.map(|t| solid(t));
+        let starts = solid
+            .pos_iter()
+            .filter(|(pos, s)| !**s && solid.is_border(pos))
+            .map(|(pos, _)| pos)
+            .collect::<Vec<_>>();
+        let reached = flood_fill(starts, |pos| {
+            solid
+                .neighbors(pos, Neighborhood3::Faces)
+                .filter(|n| !solid[*n])
+                .collect::<Vec<_>>()
+        });
+
+        let mut exterior = solid.map(|_| false);
+        for pos in reached {
+            exterior[pos] = true;
+        }
+        exterior
+    }
+
+    /// Like [`Grid3::surface_area`], but only counting faces towards the [`Grid3::exterior`].
+    pub fn exterior_surface_area(&self, mut solid: impl FnMut(&T) -> bool) -> usize {
+        let exterior = self.exterior(&mut solid);
+        self.pos_iter()
+            .filter(|(_, t)| solid(t))
+            .map(|(pos, _)| {
+                Neighborhood3::Faces
+                    .around(&pos)
+                    .filter(|n| exterior.get(n) != Some(&false))
+                    .count()
+            })
+            .sum()
+    }
+
+    /// Labels the connected groups of cells for which `include` holds with `0..count` and
+    /// returns the labels together with `count`.
+    pub fn components(
+        &self,
+        neighborhood: Neighborhood3,
+        mut include: impl FnMut(&T) -> bool,
+    ) -> (Grid3<Option<usize>>, usize) {
+        let included = self.map(|t| include(t));
+        let mut labels = self.map(|_| None);
+        let mut count = 0;
+        for (pos, &inc) in included.pos_iter() {
+            if !inc || labels[pos].is_some() {
+                continue;
+            }
+            let component = flood_fill([pos], |p| {
+                included
+                    .neighbors(p, neighborhood)
+                    .filter(|n| included[*n])
+                    .collect::<Vec<_>>()
+            });
+            for p in component {
+                labels[p] = Some(count);
+            }
+            count += 1;
+        }
+
+        (labels, count)
+    }
+
+    pub fn map<U>(&self, f: impl FnMut(&T) -> U) -> Grid3<U> {
+        Grid3 {
+            size_x: self.size_x,
+            size_y: self.size_y,
+            size_z: self.size_z,
+            grid: self.grid.iter().map(f).collect(),
+        }
+    }
+
+    fn index_of(&self, pos: Vec3i) -> usize {
+        debug_assert!(self.in_bounds(&pos));
+        pos.x as usize + self.size_x * (pos.y as usize + self.size_y * pos.z as usize)
+    }
+}
+
+impl<T> Index<Vec3i> for Grid3<T> {
+    type Output = T;
+
+    fn index(&self, index: Vec3i) -> &Self::Output {
+        &self.grid[self.index_of(index)]
+    }
+}
+
+impl<T> IndexMut<Vec3i> for Grid3<T> {
+    fn index_mut(&mut self, index: Vec3i) -> &mut Self::Output {
+        let i = self.index_of(index);
+        &mut self.grid[i]
+    }
+}
+
+/// An unbounded voxel grid that only stores the occupied cells, keeping track of the smallest
+/// box containing all of them.
+#[derive(Debug, Clone)]
+pub struct SparseGrid3<T> {
+    cells: FxHashMap<Vec3i, T>,
+    bounds: Option<(Vec3i, Vec3i)>,
+}
+
+impl<T> Default for SparseGrid3<T> {
+    fn default() -> Self {
+        Self {
+            cells: FxHashMap::default(),
+            bounds: None,
+        }
+    }
+}
+
+impl<T: PartialEq> PartialEq for SparseGrid3<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cells == other.cells
+    }
+}
+
+impl<T: Eq> Eq for SparseGrid3<T> {}
+
+impl<T> SparseGrid3<T> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.cells.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cells.is_empty()
+    }
+
+    pub fn get(&self, pos: &Vec3i) -> Option<&T> {
+        self.cells.get(pos)
+    }
+
+    pub fn contains(&self, pos: &Vec3i) -> bool {
+        self.cells.contains_key(pos)
+    }
+
+    /// Sets the cell at `pos` and returns its previous value.
+    pub fn insert(&mut self, pos: Vec3i, value: T) -> Option<T> {
+        self.bounds = Some(match self.bounds {
+            None => (pos, pos),
+            Some((min, max)) => (min.inf(&pos), max.sup(&pos)),
+        });
+        self.cells.insert(pos, value)
+    }
+
+    /// Clears the cell at `pos` and returns its value. The bounds are only recomputed if the
+    /// cell was on their surface.
+    pub fn remove(&mut self, pos: &Vec3i) -> Option<T> {
+        let value = self.cells.remove(pos)?;
+        if let Some((min, max)) = self.bounds {
+            if (0..3).any(|i| pos[i] == min[i] || pos[i] == max[i]) {
+                let mut keys = self.cells.keys();
+                self.bounds = keys.next().map(|&first| {
+                    keys.fold((first, first), |(min, max), p| (min.inf(p), max.sup(p)))
+                });
+            }
+        }
+        Some(value)
+    }
+
+    /// The inclusive minimum and maximum corner of the occupied cells.
+    pub fn bounds(&self) -> Option<(Vec3i, Vec3i)> {
+        self.bounds
+    }
+
+    /// The occupied cells in arbitrary order.
+    pub fn iter(&self) -> impl Iterator<Item = (&Vec3i, &T)> {
+        self.cells.iter()
+    }
+
+    pub fn positions(&self) -> impl Iterator<Item = &Vec3i> {
+        self.cells.keys()
+    }
+
+    /// A dense grid of the cells within the bounds with the empty ones set to `fill`, together
+    /// with the position of its corner with the smallest coordinates.
+    pub fn to_grid(&self, fill: T) -> (Vec3i, Grid3<T>)
+    where
+        T: Clone,
+    {
+        let Some((min, max)) = self.bounds else {
+            return (Vec3i::zeros(), Grid3::new_from_element(0, 0, 0, fill));
+        };
+        let size = max - min;
+        let (x, y, z) = (size.x + 1, size.y + 1, size.z + 1);
+        let mut grid = Grid3::new_from_element(x as usize, y as usize, z as usize, fill);
+        for (pos, t) in &self.cells {
+            grid[pos - min] = t.clone();
+        }
+        (min, grid)
+    }
+
+    /// The number of faces of occupied cells that don't touch another occupied cell.
+    pub fn surface_area(&self) -> usize {
+        self.cells
+            .keys()
+            .map(|pos| {
+                Neighborhood3::Faces
+                    .around(pos)
+                    .filter(|n| !self.contains(n))
+                    .count()
+            })
+            .sum()
+    }
+
+    /// Like [`SparseGrid3::surface_area`], but without the faces towards enclosed pockets.
+    pub fn exterior_surface_area(&self) -> usize {
+        let (_, solid) = self.to_grid_with(false, |_| true);
+        solid.exterior_surface_area(|s| *s)
+    }
+
+    fn to_grid_with<U: Clone>(&self, fill: U, mut f: impl FnMut(&T) -> U) -> (Vec3i, Grid3<U>) {
+        let mut mapped = SparseGrid3::new();
+        mapped.extend(self.cells.iter().map(|(pos, t)| (*pos, f(t))));
+        mapped.to_grid(fill)
+    }
+}
+
+impl<T> FromIterator<(Vec3i, T)> for SparseGrid3<T> {
+    fn from_iter<I: IntoIterator<Item = (Vec3i, T)>>(iter: I) -> Self {
+        let mut grid = Self::new();
+        grid.extend(iter);
+        grid
+    }
+}
+
+impl<T> Extend<(Vec3i, T)> for SparseGrid3<T> {
+    fn extend<I: IntoIterator<Item = (Vec3i, T)>>(&mut self, iter: I) {
+        for (pos, t) in iter {
+            self.insert(pos, t);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    /// A 3x3x3 cube with a hollow center and a separate voxel.
+    fn hollow() -> SparseGrid3<()> {
+        let mut grid: SparseGrid3<()> = (0..27)
+            .map(|i| (Vec3i::new(i % 3, i / 3 % 3, i / 9), ()))
+            .collect();
+        grid.remove(&Vec3i::new(1, 1, 1));
+        grid.insert(Vec3i::new(5, 1, 1), ());
+        grid
+    }
+
+    #[test]
+    fn test_neighborhood() {
+        assert_eq!(6, Neighborhood3::Faces.offsets().count());
+        assert_eq!(18, Neighborhood3::Edges.offsets().count());
+        assert_eq!(26, Neighborhood3::Corners.offsets().count());
+
+        let grid = Grid3::new_from_element(2, 3, 4, ());
+        assert_eq!(
+            3,
+            grid.neighbors(&Vec3i::zeros(), Neighborhood3::Faces)
+                .count()
+        );
+        assert_eq!(
+            7,
+            grid.neighbors(&Vec3i::zeros(), Neighborhood3::Corners)
+                .count()
+        );
+        assert_eq!((Vec3i::new(1, 2, 3), &()), grid.pos_iter().last().unwrap());
+    }
+
+    #[test]
+    fn test_surface() {
+        let grid = hollow();
+        assert_eq!(Some((Vec3i::zeros(), Vec3i::new(5, 2, 2))), grid.bounds());
+        assert_eq!(54 + 6 + 6, grid.surface_area());
+        assert_eq!(54 + 6, grid.exterior_surface_area());
+
+        let (origin, dense) = grid.to_grid(());
+        assert_eq!(Vec3i::zeros(), origin);
+        let dense = dense.map(|_| false);
+        assert_eq!(0, dense.surface_area(|s| *s));
+
+        let (_, dense) = grid.to_grid_with(false, |_| true);
+        assert_eq!(grid.surface_area(), dense.surface_area(|s| *s));
+        assert_eq!(
+            grid.exterior_surface_area(),
+            dense.exterior_surface_area(|s| *s)
+        );
+        let exterior = dense.exterior(|s| *s);
+        assert!(!exterior[Vec3i::new(1, 1, 1)] && exterior[Vec3i::new(4, 0, 0)]);
+    }
+
+    #[test]
+    fn test_components() {
+        let (_, dense) = hollow().to_grid_with(false, |_| true);
+        let (labels, count) = dense.components(Neighborhood3::Faces, |s| *s);
+        assert_eq!(2, count);
+        assert_eq!(Some(0), labels[Vec3i::zeros()]);
+        assert_eq!(Some(1), labels[Vec3i::new(5, 1, 1)]);
+        assert_eq!(None, labels[Vec3i::new(1, 1, 1)]);
+
+        let (_, count) = dense.components(Neighborhood3::Faces, |s| !*s);
+        assert_eq!(2, count);
+
+        let mut diagonal = Grid3::new_from_element(2, 2, 2, false);
+        diagonal[Vec3i::zeros()] = true;
+        diagonal[Vec3i::new(1, 1, 1)] = true;
+        assert_eq!(2, diagonal.components(Neighborhood3::Edges, |s| *s).1);
+        assert_eq!(1, diagonal.components(Neighborhood3::Corners, |s| *s).1);
+    }
+}
@@ -0,0 +1,85 @@
+//! Normalisation of puzzle inputs that were saved on other platforms or by other editors.
+
+use std::borrow::Cow;
+
+use itertools::Itertools;
+
+/// Strips a leading byte order mark and turns `\r\n` and lone `\r` line endings into `\n`.
+/// With `trim_trailing`, whitespace at the end of each line is removed as well. Leading
+/// whitespace is always kept, it is meaningful e.g. in the stack drawing of day 5.
+pub fn normalize(input: &str, trim_trailing: bool) -> Cow<'_, str> {
+    let input = input.strip_prefix('\u{feff}').unwrap_or(input);
+    let has_trailing = |input: &str| {
+        input
+            .split('\n')
+            .any(|l| l.ends_with(|c: char| c.is_whitespace() && c != '\r'))
+    };
+    if !(input.contains('\r') || trim_trailing && has_trailing(input)) {
+        return Cow::Borrowed(input);
+    }
+
+    let input = input.replace("\r\n", "\n").replace('\r', "\n");
+    if trim_trailing {
+        Cow::Owned(input.split('\n').map(str::trim_end).join("\n"))
+    } else {
+        Cow::Owned(input)
+    }
+}
+
+/// Splits `input` around its first blank line, which may contain whitespace or end with `\r\n`.
+pub fn split_at_blank_line(input: &str) -> Option<(&str, &str)> {
+    let mut start = 0;
+    for line in input.split_inclusive('\n') {
+        let end = start + line.len();
+        if start > 0 && line.trim().is_empty() {
+            return Some((&input[..start], &input[end..]));
+        }
+        start = end;
+    }
+
+    None
+}
+
+/// The input as saved by an editor on Windows that adds a BOM and pads the lines.
+#[cfg(test)]
+pub(crate) fn windows_input(input: &str) -> String {
+    format!("\u{feff}{}", input.replace('\n', " \r\n"))
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_normalize() {
+        let input = "\u{feff}    [D]  \r\n[N] [C]\t\r\n\r\nmove 1 from 2 to 1 \r\n";
+        assert_eq!(
+            "    [D]\n[N] [C]\n\nmove 1 from 2 to 1\n",
+            normalize(input, true)
+        );
+        assert_eq!(
+            "    [D]  \n[N] [C]\t\n\nmove 1 from 2 to 1 \n",
+            normalize(input, false)
+        );
+        assert_eq!("a\nb\nc", normalize("a\rb\r\nc", false));
+
+        assert!(matches!(normalize("a\n b\n", true), Cow::Borrowed(_)));
+        assert!(matches!(
+            normalize("\u{feff}a \n", false),
+            Cow::Borrowed("a \n")
+        ));
+    }
+
+    #[test]
+    fn test_split_at_blank_line() {
+        assert_eq!(Some(("a\nb\n", "c\n")), split_at_blank_line("a\nb\n\nc\n"));
+        assert_eq!(
+            Some(("a\r\n", "c\r\n\r\nd")),
+            split_at_blank_line("a\r\n \t\r\nc\r\n\r\nd")
+        );
+        assert_eq!(None, split_at_blank_line("\na\nb"));
+        assert_eq!(None, split_at_blank_line("a\nb\n"));
+    }
+}
@@ -0,0 +1,271 @@
+use num::PrimInt;
+
+/// The integers from `start` to `end`, both included.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash, Ord, PartialOrd)]
+pub struct Interval<T> {
+    pub start: T,
+    pub end: T,
+}
+
+impl<T: PrimInt> Interval<T> {
+    /// Panics if `start > end`, there are no empty intervals.
+    pub fn new(start: T, end: T) -> Self {
+        assert!(start <= end, "empty interval");
+        Self { start, end }
+    }
+
+    /// `None` if `start > end`.
+    pub fn try_new(start: T, end: T) -> Option<Self> {
+        (start <= end).then_some(Self { start, end })
+    }
+
+    pub fn point(x: T) -> Self {
+        Self { start: x, end: x }
+    }
+
+    /// The number of integers in the interval.
+    pub fn len(&self) -> T {
+        self.end - self.start + T::one()
+    }
+
+    pub fn contains(&self, x: &T) -> bool {
+        self.start <= *x && *x <= self.end
+    }
+
+    pub fn contains_interval(&self, other: &Self) -> bool {
+        self.start <= other.start && other.end <= self.end
+    }
+
+    pub fn overlaps(&self, other: &Self) -> bool {
+        self.start <= other.end && other.start <= self.end
+    }
+
+    pub fn intersection(&self, other: &Self) -> Option<Self> {
+        Self::try_new(self.start.max(other.start), self.end.min(other.end))
+    }
+
+    /// Whether the union of both intervals is an interval again.
+    fn touches(&self, other: &Self) -> bool {
+        self.start <= other.end.saturating_add(T::one())
+            && other.start <= self.end.saturating_add(T::one())
+    }
+}
+
+/// A set of integers stored as sorted, disjoint intervals. Intervals that overlap or are
+/// adjacent are merged on insertion.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct IntervalSet<T> {
+    intervals: Vec<Interval<T>>,
+}
+
+impl<T> Default for IntervalSet<T> {
+    fn default() -> Self {
+        Self { intervals: vec![] }
+    }
+}
+
+impl<T: PrimInt> IntervalSet<T> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.intervals.is_empty()
+    }
+
+    /// The disjoint intervals in ascending order.
+    pub fn intervals(&self) -> &[Interval<T>] {
+        &self.intervals
+    }
+
+    /// The number of integers in the set.
+    pub fn len(&self) -> T {
+        self.intervals
+            .iter()
+            .fold(T::zero(), |sum, interval| sum + interval.len())
+    }
+
+    pub fn contains(&self, x: &T) -> bool {
+        let i = self.intervals.partition_point(|interval| interval.end < *x);
+        self.intervals
+            .get(i)
+            .is_some_and(|interval| interval.contains(x))
+    }
+
+    pub fn insert(&mut self, interval: Interval<T>) {
+        let from = self
+            .intervals
+            .partition_point(|i| i.end < interval.start && !i.touches(&interval));
+        let to = from
+            + self.intervals[from..]
+                .iter()
+                .take_while(|i| i.touches(&interval))
+                .count();
+        let merged = self.intervals[from..to].iter().fold(interval, |m, i| {
+            Interval::new(m.start.min(i.start), m.end.max(i.end))
+        });
+        self.intervals.splice(from..to, [merged]);
+    }
+
+    pub fn remove(&mut self, interval: Interval<T>) {
+        let from = self.intervals.partition_point(|i| i.end < interval.start);
+        let to = from
+            + self.intervals[from..]
+                .iter()
+                .take_while(|i| i.overlaps(&interval))
+                .count();
+        let rest = self.intervals[from..to]
+            .iter()
+            .flat_map(|i| {
+                [
+                    (i.start < interval.start)
+                        .then(|| Interval::new(i.start, interval.start - T::one())),
+                    (interval.end < i.end).then(|| Interval::new(interval.end + T::one(), i.end)),
+                ]
+            })
+            .flatten()
+            .collect::<Vec<_>>();
+        self.intervals.splice(from..to, rest);
+    }
+
+    pub fn union(&self, other: &Self) -> Self {
+        let mut union = self.clone();
+        union.extend(other.intervals.iter().copied());
+        union
+    }
+
+    pub fn intersection(&self, other: &Self) -> Self {
+        let (mut i, mut j) = (0, 0);
+        let mut intervals = vec![];
+        while let (Some(a), Some(b)) = (self.intervals.get(i), other.intervals.get(j)) {
+            intervals.extend(a.intersection(b));
+            if a.end < b.end {
+                i += 1;
+            } else {
+                j += 1;
+            }
+        }
+        Self { intervals }
+    }
+
+    pub fn difference(&self, other: &Self) -> Self {
+        let mut difference = self.clone();
+        for interval in &other.intervals {
+            difference.remove(*interval);
+        }
+        difference
+    }
+
+    /// All integers in `bounds` that aren't in the set.
+    pub fn complement(&self, bounds: Interval<T>) -> Self {
+        Self::from_iter([bounds]).difference(self)
+    }
+
+    /// The intervals between consecutive intervals of the set.
+    pub fn gaps(&self) -> impl Iterator<Item = Interval<T>> + '_ {
+        self.intervals
+            .windows(2)
+            .map(|w| Interval::new(w[0].end + T::one(), w[1].start - T::one()))
+    }
+}
+
+impl<T: PrimInt> FromIterator<Interval<T>> for IntervalSet<T> {
+    fn from_iter<I: IntoIterator<Item = Interval<T>>>(iter: I) -> Self {
+        let mut intervals = iter.into_iter().collect::<Vec<_>>();
+        intervals.sort_unstable();
+        let mut set = Self::new();
+        for interval in intervals {
+            match set.intervals.last_mut() {
+                Some(last) if last.touches(&interval) => last.end = last.end.max(interval.end),
+                _ => set.intervals.push(interval),
+            }
+        }
+        set
+    }
+}
+
+impl<T: PrimInt> Extend<Interval<T>> for IntervalSet<T> {
+    fn extend<I: IntoIterator<Item = Interval<T>>>(&mut self, iter: I) {
+        for interval in iter {
+            self.insert(interval);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    fn set(intervals: &[(i32, i32)]) -> IntervalSet<i32> {
+        intervals
+            .iter()
+            .map(|&(a, b)| Interval::new(a, b))
+            .collect()
+    }
+
+    fn pairs(set: &IntervalSet<i32>) -> Vec<(i32, i32)> {
+        set.intervals().iter().map(|i| (i.start, i.end)).collect()
+    }
+
+    #[test]
+    fn test_interval() {
+        let a = Interval::new(2, 8);
+        assert_eq!(7, a.len());
+        assert!(a.contains_interval(&Interval::new(3, 8)));
+        assert!(!a.contains_interval(&Interval::new(1, 3)));
+        assert!(a.overlaps(&Interval::new(8, 9)));
+        assert!(!a.overlaps(&Interval::new(9, 9)));
+        assert_eq!(
+            Some(Interval::new(6, 8)),
+            a.intersection(&Interval::new(6, 10))
+        );
+        assert_eq!(None, a.intersection(&Interval::point(0)));
+        assert_eq!(None, Interval::try_new(1, 0));
+    }
+
+    #[test]
+    fn test_insert_remove() {
+        let mut s = set(&[(10, 12), (0, 2), (5, 6)]);
+        assert_eq!(vec![(0, 2), (5, 6), (10, 12)], pairs(&s));
+        s.insert(Interval::new(3, 4));
+        assert_eq!(vec![(0, 6), (10, 12)], pairs(&s));
+        s.insert(Interval::new(8, 8));
+        s.insert(Interval::new(-5, -3));
+        assert_eq!(vec![(-5, -3), (0, 6), (8, 8), (10, 12)], pairs(&s));
+        s.insert(Interval::new(-2, 20));
+        assert_eq!(vec![(-5, 20)], pairs(&s));
+        assert_eq!(26, s.len());
+
+        s.remove(Interval::new(0, 4));
+        s.remove(Interval::point(20));
+        s.remove(Interval::new(30, 40));
+        assert_eq!(vec![(-5, -1), (5, 19)], pairs(&s));
+        assert!(s.contains(&-1) && !s.contains(&0) && s.contains(&5));
+        s.remove(Interval::new(-10, 30));
+        assert!(s.is_empty());
+        assert_eq!(0, s.len());
+    }
+
+    #[test]
+    fn test_set_algebra() {
+        let a = set(&[(0, 5), (10, 15)]);
+        let b = set(&[(3, 11), (14, 20)]);
+        assert_eq!(vec![(0, 20)], pairs(&a.union(&b)));
+        assert_eq!(vec![(3, 5), (10, 11), (14, 15)], pairs(&a.intersection(&b)));
+        assert_eq!(vec![(0, 2), (12, 13)], pairs(&a.difference(&b)));
+        assert_eq!(
+            vec![(-2, -1), (6, 9), (16, 17)],
+            pairs(&a.complement(Interval::new(-2, 17)))
+        );
+        assert_eq!(vec![Interval::new(6, 9)], a.gaps().collect::<Vec<_>>());
+
+        let full = set(&[(i32::MIN, i32::MAX)]);
+        assert!(full.complement(Interval::new(0, 10)).is_empty());
+        assert_eq!(
+            vec![(i32::MIN, i32::MAX)],
+            pairs(&set(&[(i32::MIN, 0), (1, i32::MAX)]))
+        );
+    }
+}
@@ -0,0 +1,224 @@
+//! Norms and distances for vectors of any dimension, and the integer points of balls and
+//! spheres under the L1 (Manhattan) and L∞ (Chebyshev) metrics.
+
+use itertools::Itertools;
+use nalgebra::{Dim, SVector, Scalar, Storage, Vector};
+use num::Signed;
+
+use super::Vec2i;
+
+/// The sum of the absolute coordinates.
+pub fn l1_norm<T: Scalar + Signed, D: Dim, S: Storage<T, D>>(v: &Vector<T, D, S>) -> T {
+    v.iter().fold(T::zero(), |sum, x| sum + x.abs())
+}
+
+/// The largest absolute coordinate.
+pub fn linf_norm<T: Scalar + Signed + PartialOrd, D: Dim, S: Storage<T, D>>(
+    v: &Vector<T, D, S>,
+) -> T {
+    v.iter()
+        .map(Signed::abs)
+        .fold(T::zero(), |max, x| if x > max { x } else { max })
+}
+
+/// The square of the euclidean norm, which stays exact for integers.
+pub fn l2_norm_squared<T: Scalar + Signed, D: Dim, S: Storage<T, D>>(v: &Vector<T, D, S>) -> T {
+    v.iter()
+        .fold(T::zero(), |sum, x| sum + x.clone() * x.clone())
+}
+
+fn diff<T: Scalar + Signed, const D: usize>(a: &SVector<T, D>, b: &SVector<T, D>) -> SVector<T, D> {
+    SVector::from_fn(|i, _| a[i].clone() - b[i].clone())
+}
+
+/// The L1 distance, the number of orthogonal steps from `a` to `b`.
+pub fn manhattan<T: Scalar + Signed, const D: usize>(a: &SVector<T, D>, b: &SVector<T, D>) -> T {
+    l1_norm(&diff(a, b))
+}
+
+/// The L∞ distance, the number of steps from `a` to `b` when diagonal steps are allowed.
+pub fn chebyshev<T: Scalar + Signed + PartialOrd, const D: usize>(
+    a: &SVector<T, D>,
+    b: &SVector<T, D>,
+) -> T {
+    linf_norm(&diff(a, b))
+}
+
+/// The square of the euclidean distance.
+pub fn euclidean_squared<T: Scalar + Signed, const D: usize>(
+    a: &SVector<T, D>,
+    b: &SVector<T, D>,
+) -> T {
+    l2_norm_squared(&diff(a, b))
+}
+
+/// All points with a L∞ distance of at most `r` to `center`, the cube with side length
+/// `2r + 1` around it.
+pub fn linf_ball<const D: usize>(
+    center: &SVector<i64, D>,
+    r: i64,
+) -> impl Iterator<Item = SVector<i64, D>> {
+    let center = *center;
+    (0..D)
+        .map(|_| -r..=r)
+        .multi_cartesian_product()
+        .map(move |offset| center + SVector::from_iterator(offset))
+}
+
+/// All points with a L1 distance of at most `r` to `center`.
+pub fn l1_ball<const D: usize>(
+    center: &SVector<i64, D>,
+    r: i64,
+) -> impl Iterator<Item = SVector<i64, D>> {
+    let center = *center;
+    linf_ball(&center, r).filter(move |p| manhattan(&center, p) <= r)
+}
+
+/// All points in the plane with a L1 distance of exactly `r` to `center`, starting at
+/// `center + (r, 0)` and going around towards positive y first.
+pub fn l1_sphere(center: &Vec2i, r: i64) -> impl Iterator<Item = Vec2i> {
+    let center = *center;
+    let count = if r == 0 { 1 } else { 4 * r.max(0) };
+    (0..count).map(move |i| {
+        let (side, t) = if r == 0 { (0, 0) } else { (i / r, i % r) };
+        let offset = match side {
+            0 => Vec2i::new(r - t, t),
+            1 => Vec2i::new(-t, r - t),
+            2 => Vec2i::new(-r + t, -t),
+            _ => Vec2i::new(t, -r + t),
+        };
+        center + offset
+    })
+}
+
+/// All points in the plane with a L∞ distance of exactly `r` to `center`, starting at
+/// `center + (-r, -r)` and going along the edge towards positive x first.
+pub fn linf_sphere(center: &Vec2i, r: i64) -> impl Iterator<Item = Vec2i> {
+    let center = *center;
+    let count = if r == 0 { 1 } else { 8 * r.max(0) };
+    (0..count).map(move |i| {
+        let (side, t) = if r == 0 {
+            (0, 0)
+        } else {
+            (i / (2 * r), i % (2 * r))
+        };
+        let offset = match side {
+            0 => Vec2i::new(-r + t, -r),
+            1 => Vec2i::new(r, -r + t),
+            2 => Vec2i::new(r - t, r),
+            _ => Vec2i::new(-r, r - t),
+        };
+        center + offset
+    })
+}
+
+/// Whether the L1 balls with radius `r1` around `c1` and `r2` around `c2` share a point.
+pub fn l1_balls_overlap<const D: usize>(
+    c1: &SVector<i64, D>,
+    r1: i64,
+    c2: &SVector<i64, D>,
+    r2: i64,
+) -> bool {
+    r1 >= 0 && r2 >= 0 && manhattan(c1, c2) <= r1 + r2
+}
+
+/// Whether the L∞ balls with radius `r1` around `c1` and `r2` around `c2` share a point.
+pub fn linf_balls_overlap<const D: usize>(
+    c1: &SVector<i64, D>,
+    r1: i64,
+    c2: &SVector<i64, D>,
+    r2: i64,
+) -> bool {
+    linf_ball_intersection(c1, r1, c2, r2).is_some()
+}
+
+/// The minimum and maximum corner of the box shared by two L∞ balls, `None` if they don't
+/// overlap.
+pub fn linf_ball_intersection<const D: usize>(
+    c1: &SVector<i64, D>,
+    r1: i64,
+    c2: &SVector<i64, D>,
+    r2: i64,
+) -> Option<(SVector<i64, D>, SVector<i64, D>)> {
+    let min: SVector<i64, D> = SVector::from_fn(|i, _| (c1[i] - r1).max(c2[i] - r2));
+    let max: SVector<i64, D> = SVector::from_fn(|i, _| (c1[i] + r1).min(c2[i] + r2));
+    (r1 >= 0 && r2 >= 0 && min.iter().zip(&max).all(|(a, b)| a <= b)).then_some((min, max))
+}
+
+/// Turns the plane by 45° and scales it, so that L1 balls around `v` become L∞ balls around
+/// the result with the same radius, and L1 distances become L∞ distances.
+pub fn l1_to_linf(v: &Vec2i) -> Vec2i {
+    Vec2i::new(v.x + v.y, v.x - v.y)
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::common::Vec3i;
+
+    #[test]
+    fn test_norms() {
+        let v = Vec3i::new(3, -7, 2);
+        assert_eq!(12, l1_norm(&v));
+        assert_eq!(7, linf_norm(&v));
+        assert_eq!(62, l2_norm_squared(&v));
+        assert_eq!(0, linf_norm(&Vec3i::zeros()));
+
+        let a = Vec2i::new(1, 2);
+        let b = Vec2i::new(-3, 5);
+        assert_eq!(7, manhattan(&a, &b));
+        assert_eq!(4, chebyshev(&a, &b));
+        assert_eq!(25, euclidean_squared(&a, &b));
+        assert_eq!(
+            manhattan(&a, &b),
+            chebyshev(&l1_to_linf(&a), &l1_to_linf(&b))
+        );
+    }
+
+    #[test]
+    fn test_balls() {
+        let c = Vec2i::new(5, -2);
+        assert_eq!(25, linf_ball(&c, 2).count());
+        assert_eq!(13, l1_ball(&c, 2).count());
+        assert_eq!(1, l1_ball(&c, 0).count());
+        assert_eq!(0, linf_ball(&c, -1).count());
+        assert_eq!(7 * 7 * 7, linf_ball(&Vec3i::new(1, 2, 3), 3).count());
+        assert_eq!(25, l1_ball(&Vec3i::zeros(), 2).count());
+
+        for r in 0..5 {
+            let mut l1 = l1_sphere(&c, r).collect::<Vec<_>>();
+            let mut expected = l1_ball(&c, r)
+                .filter(|p| manhattan(&c, p) == r)
+                .collect::<Vec<_>>();
+            l1.sort_by_key(|p| (p.x, p.y));
+            expected.sort_by_key(|p| (p.x, p.y));
+            assert_eq!(expected, l1);
+
+            let mut linf = linf_sphere(&c, r).collect::<Vec<_>>();
+            let mut expected = linf_ball(&c, r)
+                .filter(|p| chebyshev(&c, p) == r)
+                .collect::<Vec<_>>();
+            linf.sort_by_key(|p| (p.x, p.y));
+            expected.sort_by_key(|p| (p.x, p.y));
+            assert_eq!(expected, linf);
+        }
+        assert_eq!(Some(Vec2i::new(6, -1)), l1_sphere(&c, 2).nth(1));
+        assert_eq!(0, l1_sphere(&c, -1).count());
+    }
+
+    #[test]
+    fn test_intersections() {
+        let a = Vec2i::new(0, 0);
+        let b = Vec2i::new(4, 3);
+        assert!(l1_balls_overlap(&a, 3, &b, 4));
+        assert!(!l1_balls_overlap(&a, 3, &b, 3));
+        assert!(linf_balls_overlap(&a, 2, &b, 2));
+        assert!(!linf_balls_overlap(&a, 1, &b, 2));
+        assert_eq!(
+            Some((Vec2i::new(2, 1), Vec2i::new(2, 2))),
+            linf_ball_intersection(&a, 2, &b, 2)
+        );
+    }
+}
@@ -0,0 +1,258 @@
+//! A small parser for the puzzle inputs, made of keywords, tokens, integers and separated
+//! lists. All errors are located at the position in the input where parsing failed.
+
+use std::str::FromStr;
+
+use super::ParseError;
+
+/// A position in `input` that parse functions advance as they consume it.
+#[derive(Debug, Copy, Clone)]
+pub struct Parser<'a> {
+    input: &'a str,
+    pos: usize,
+}
+
+impl<'a> Parser<'a> {
+    pub fn new(input: &'a str) -> Self {
+        Self { input, pos: 0 }
+    }
+
+    /// The input that wasn't consumed yet.
+    pub fn rest(&self) -> &'a str {
+        &self.input[self.pos..]
+    }
+
+    /// Whether there is nothing but whitespace left.
+    pub fn at_end(&self) -> bool {
+        self.rest().trim_start().is_empty()
+    }
+
+    /// An error located at the current position.
+    pub fn error(&self, reason: impl Into<String>) -> ParseError {
+        self.error_at(self.rest(), reason)
+    }
+
+    /// An error located at `part`, which has to be a slice returned by this parser.
+    pub fn error_at(&self, part: &str, reason: impl Into<String>) -> ParseError {
+        ParseError::new(reason).located_in(self.input, part)
+    }
+
+    pub fn skip_whitespace(&mut self) {
+        let rest = self.rest();
+        self.pos += rest.len() - rest.trim_start().len();
+    }
+
+    /// Consumes `keyword` after optional whitespace, leaves the input unchanged if it doesn't
+    /// follow. Whitespace in `keyword` matches any amount of whitespace in the input, but two
+    /// words have to be separated by at least some, and the keyword can't end within a word.
+    pub fn try_keyword(&mut self, keyword: &str) -> bool {
+        let start = self.pos;
+        self.skip_whitespace();
+        let mut prev = None;
+        let mut chars = keyword.chars().peekable();
+        while let Some(c) = chars.next() {
+            if c.is_whitespace() {
+                while chars.next_if(|c| c.is_whitespace()).is_some() {}
+                let skipped = self.pos;
+                self.skip_whitespace();
+                let separates_words = prev.is_some_and(char::is_alphanumeric)
+                    && chars.peek().is_some_and(|c| c.is_alphanumeric());
+                if separates_words && skipped == self.pos {
+                    self.pos = start;
+                    return false;
+                }
+            } else if self.rest().starts_with(c) {
+                self.pos += c.len_utf8();
+                prev = Some(c);
+            } else {
+                self.pos = start;
+                return false;
+            }
+        }
+
+        let splits_word = prev.is_some_and(char::is_alphanumeric)
+            && self.rest().starts_with(char::is_alphanumeric);
+        if splits_word {
+            self.pos = start;
+        }
+        !splits_word
+    }
+
+    /// Like [`Parser::try_keyword`], but fails if `keyword` doesn't follow.
+    pub fn keyword(&mut self, keyword: &str) -> Result<(), ParseError> {
+        if self.try_keyword(keyword) {
+            Ok(())
+        } else {
+            let mut at = *self;
+            at.skip_whitespace();
+            Err(at.error(format!("expected '{keyword}'")))
+        }
+    }
+
+    /// Consumes the first of `keywords` that follows and returns its value.
+    pub fn one_of<T: Copy>(&mut self, keywords: &[(&str, T)]) -> Result<T, ParseError> {
+        if let Some((_, value)) = keywords.iter().find(|(k, _)| self.try_keyword(k)) {
+            return Ok(*value);
+        }
+
+        let expected = keywords
+            .iter()
+            .map(|(k, _)| format!("'{k}'"))
+            .collect::<Vec<_>>()
+            .join(" or ");
+        self.skip_whitespace();
+        Err(self.error(format!("expected {expected}")))
+    }
+
+    /// Consumes the longest non-empty run of chars matching `pred` after optional whitespace.
+    /// `what` describes the token for the error message.
+    pub fn token(
+        &mut self,
+        what: &str,
+        pred: impl Fn(char) -> bool,
+    ) -> Result<&'a str, ParseError> {
+        self.skip_whitespace();
+        let rest = self.rest();
+        let len = rest.find(|c| !pred(c)).unwrap_or(rest.len());
+        if len == 0 {
+            return Err(self.error(format!("expected {what}")));
+        }
+
+        self.pos += len;
+        Ok(&rest[..len])
+    }
+
+    /// A token of letters and digits.
+    pub fn word(&mut self) -> Result<&'a str, ParseError> {
+        self.token("a word", char::is_alphanumeric)
+    }
+
+    /// An integer with an optional sign, parsed with [`FromStr`].
+    pub fn integer<T: FromStr>(&mut self) -> Result<T, ParseError>
+    where
+        ParseError: From<T::Err>,
+    {
+        self.skip_whitespace();
+        let start = *self;
+        let rest = self.rest();
+        let sign = usize::from(rest.starts_with(['+', '-']));
+        self.pos += sign;
+        if let Err(e) = self.token("a number", |c| c.is_ascii_digit()) {
+            *self = start;
+            return Err(e);
+        }
+
+        let number = &rest[..self.pos - start.pos];
+        number
+            .parse()
+            .map_err(|e| ParseError::from(e).located_in(self.input, number))
+    }
+
+    /// One or more items, each one parsed by `item`, with `separator` between them.
+    pub fn separated<T>(
+        &mut self,
+        separator: &str,
+        mut item: impl FnMut(&mut Self) -> Result<T, ParseError>,
+    ) -> Result<Vec<T>, ParseError> {
+        let mut items = vec![item(self)?];
+        while self.try_keyword(separator) {
+            items.push(item(self)?);
+        }
+        Ok(items)
+    }
+
+    /// Items parsed by `item` until only whitespace is left.
+    pub fn until_end<T>(
+        &mut self,
+        mut item: impl FnMut(&mut Self) -> Result<T, ParseError>,
+    ) -> Result<Vec<T>, ParseError> {
+        let mut items = vec![];
+        while !self.at_end() {
+            items.push(item(self)?);
+        }
+        Ok(items)
+    }
+
+    /// Fails if anything but whitespace is left.
+    pub fn end(&mut self) -> Result<(), ParseError> {
+        self.skip_whitespace();
+        if self.at_end() {
+            Ok(())
+        } else {
+            Err(self.error("unexpected trailing input"))
+        }
+    }
+}
+
+/// Parses all of `input` with `f`.
+pub fn parse_all<'a, T>(
+    input: &'a str,
+    f: impl FnOnce(&mut Parser<'a>) -> Result<T, ParseError>,
+) -> Result<T, ParseError> {
+    let mut parser = Parser::new(input);
+    let result = f(&mut parser)?;
+    parser.end()?;
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_keywords() {
+        let mut p = Parser::new("  Starting   items:\n 79, -98,\t7  ");
+        assert!(!p.try_keyword("Start"));
+        assert!(!p.try_keyword("Startingitems"));
+        p.keyword("Starting items :").unwrap();
+        assert_eq!(
+            Ok(vec![79, -98, 7]),
+            p.separated(",", |p| p.integer::<i32>())
+        );
+        assert!(p.at_end());
+        assert_eq!(Ok(()), p.end());
+
+        let mut p = Parser::new("old * old");
+        assert_eq!(Ok("old"), p.word());
+        assert_eq!(Ok('*'), p.one_of(&[("+", '+'), ("*", '*')]));
+        assert_eq!(" old", p.rest());
+    }
+
+    #[test]
+    fn test_errors() {
+        let e = parse_all("move 1\nfrom x", |p| {
+            p.keyword("move")?;
+            let n: u32 = p.integer()?;
+            p.keyword("from")?;
+            p.integer::<u32>().map(|m| n + m)
+        })
+        .unwrap_err();
+        assert_eq!((2, 6, "expected a number"), (e.line, e.column, &*e.reason));
+
+        let e = parse_all("a - b", |p| {
+            p.word()?;
+            p.one_of(&[("+", ()), ("*", ())])
+        })
+        .unwrap_err();
+        assert_eq!(
+            (1, 3, "expected '+' or '*'"),
+            (e.line, e.column, &*e.reason)
+        );
+
+        let e = parse_all("12 x", |p| p.integer::<u8>()).unwrap_err();
+        assert_eq!(
+            (1, 4, "unexpected trailing input"),
+            (e.line, e.column, &*e.reason)
+        );
+
+        let e = parse_all("300", |p| p.integer::<u8>()).unwrap_err();
+        assert_eq!((1, 1), (e.line, e.column));
+        assert!(e.reason.starts_with("invalid number"));
+
+        let mut p = Parser::new("-");
+        assert!(p.integer::<i32>().is_err());
+        assert_eq!("-", p.rest());
+    }
+}
@@ -0,0 +1,387 @@
+//! Graph searches over arbitrary nodes, and over the cells of a [`Grid`] with the four
+//! directions as edges.
+
+use std::cmp::Reverse;
+use std::collections::hash_map::Entry;
+use std::collections::{BinaryHeap, VecDeque};
+use std::hash::Hash;
+
+use num::Zero;
+use rustc_hash::{FxHashMap, FxHashSet};
+
+use super::{Direction, Grid, Vec2i};
+
+/// The distances of the nodes reached by a search, together with the predecessors on a
+/// shortest path to them.
+#[derive(Debug, Clone)]
+pub struct Distances<N, C> {
+    nodes: FxHashMap<N, (C, Option<N>)>,
+}
+
+impl<N: Copy + Eq + Hash, C: Copy> Distances<N, C> {
+    /// The distance of `node`, `None` if it was not reached.
+    pub fn get(&self, node: &N) -> Option<C> {
+        self.nodes.get(node).map(|&(cost, _)| cost)
+    }
+
+    pub fn contains(&self, node: &N) -> bool {
+        self.nodes.contains_key(node)
+    }
+
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&N, C)> {
+        self.nodes.iter().map(|(node, &(cost, _))| (node, cost))
+    }
+
+    /// A shortest path from one of the start nodes to `node`, including both.
+    pub fn path_to(&self, node: &N) -> Option<Vec<N>> {
+        let mut path = vec![*node];
+        let mut current = self.nodes.get(node)?;
+        while let Some(prev) = current.1 {
+            path.push(prev);
+            current = &self.nodes[&prev];
+        }
+        path.reverse();
+        Some(path)
+    }
+}
+
+/// Breadth first search from all `starts`, every edge has a cost of 1.
+pub fn bfs<N, I>(
+    starts: impl IntoIterator<Item = N>,
+    mut successors: impl FnMut(&N) -> I,
+) -> Distances<N, usize>
+where
+    N: Copy + Eq + Hash,
+    I: IntoIterator<Item = N>,
+{
+    let mut nodes = FxHashMap::default();
+    let mut q = VecDeque::new();
+    for start in starts {
+        if nodes.insert(start, (0, None)).is_none() {
+            q.push_back(start);
+        }
+    }
+
+    while let Some(node) = q.pop_front() {
+        let dist = nodes[&node].0 + 1;
+        for next in successors(&node) {
+            if let Entry::Vacant(e) = nodes.entry(next) {
+                e.insert((dist, Some(node)));
+                q.push_back(next);
+            }
+        }
+    }
+
+    Distances { nodes }
+}
+
+/// Dijkstra's algorithm from all `starts`, `successors` yields the neighbours of a node with
+/// the non-negative cost to get there.
+pub fn dijkstra<N, C, I>(
+    starts: impl IntoIterator<Item = N>,
+    mut successors: impl FnMut(&N) -> I,
+) -> Distances<N, C>
+where
+    N: Copy + Eq + Hash,
+    C: Copy + Ord + Zero,
+    I: IntoIterator<Item = (N, C)>,
+{
+    let mut nodes = FxHashMap::default();
+    let mut q = Queue::default();
+    for start in starts {
+        if nodes.insert(start, (C::zero(), None)).is_none() {
+            q.push(C::zero(), start);
+        }
+    }
+
+    while let Some((cost, node)) = q.pop() {
+        if cost > nodes[&node].0 {
+            continue;
+        }
+
+        for (next, step) in successors(&node) {
+            let next_cost = cost + step;
+            match nodes.entry(next) {
+                Entry::Occupied(e) if e.get().0 <= next_cost => continue,
+                Entry::Occupied(mut e) => {
+                    e.insert((next_cost, Some(node)));
+                }
+                Entry::Vacant(e) => {
+                    e.insert((next_cost, Some(node)));
+                }
+            }
+            q.push(next_cost, next);
+        }
+    }
+
+    Distances { nodes }
+}
+
+/// A* search from `start` to the first node for which `is_goal` holds. Returns the path,
+/// including `start` and the goal, and its cost.
+///
+/// `heuristic` must never overestimate the remaining cost, otherwise the path might not be
+/// the shortest one.
+pub fn astar<N, C, I>(
+    start: N,
+    mut successors: impl FnMut(&N) -> I,
+    mut heuristic: impl FnMut(&N) -> C,
+    mut is_goal: impl FnMut(&N) -> bool,
+) -> Option<(Vec<N>, C)>
+where
+    N: Copy + Eq + Hash,
+    C: Copy + Ord + Zero,
+    I: IntoIterator<Item = (N, C)>,
+{
+    let mut nodes = FxHashMap::default();
+    nodes.insert(start, (C::zero(), None));
+    let mut q = Queue::default();
+    q.push(heuristic(&start), start);
+
+    while let Some((_, node)) = q.pop() {
+        let cost = nodes[&node].0;
+        if is_goal(&node) {
+            let path = Distances { nodes }.path_to(&node).unwrap();
+            return Some((path, cost));
+        }
+
+        for (next, step) in successors(&node) {
+            let next_cost = cost + step;
+            match nodes.entry(next) {
+                Entry::Occupied(e) if e.get().0 <= next_cost => continue,
+                Entry::Occupied(mut e) => {
+                    e.insert((next_cost, Some(node)));
+                }
+                Entry::Vacant(e) => {
+                    e.insert((next_cost, Some(node)));
+                }
+            }
+            q.push(next_cost + heuristic(&next), next);
+        }
+    }
+
+    None
+}
+
+/// All nodes reachable from `starts`, including them.
+pub fn flood_fill<N, I>(
+    starts: impl IntoIterator<Item = N>,
+    neighbors: impl FnMut(&N) -> I,
+) -> FxHashSet<N>
+where
+    N: Copy + Eq + Hash,
+    I: IntoIterator<Item = N>,
+{
+    bfs(starts, neighbors).nodes.into_keys().collect()
+}
+
+/// A min-priority queue that doesn't need the nodes to be ordered.
+struct Queue<N, C> {
+    heap: BinaryHeap<Reverse<(C, usize)>>,
+    nodes: Vec<N>,
+}
+
+impl<N, C> Default for Queue<N, C> {
+    fn default() -> Self {
+        Self {
+            heap: BinaryHeap::new(),
+            nodes: vec![],
+        }
+    }
+}
+
+impl<N: Copy, C: Ord + Copy> Queue<N, C> {
+    fn push(&mut self, priority: C, node: N) {
+        self.heap.push(Reverse((priority, self.nodes.len())));
+        self.nodes.push(node);
+    }
+
+    fn pop(&mut self) -> Option<(C, N)> {
+        let Reverse((priority, i)) = self.heap.pop()?;
+        Some((priority, self.nodes[i]))
+    }
+}
+
+impl<T> Grid<T> {
+    /// The positions next to `pos` in the four directions that are inside the grid.
+    pub fn neighbors(&self, pos: &Vec2i) -> impl Iterator<Item = Vec2i> + '_ {
+        let pos = *pos;
+        Direction::VALUES
+            .iter()
+            .map(move |d| d.offset(&pos))
+            .filter(|n| self.in_bounds(n))
+    }
+
+    /// [`bfs`] from `starts`, `can_step(from, to)` tells whether one can step between the
+    /// values of two neighbouring cells.
+    pub fn bfs(
+        &self,
+        starts: impl IntoIterator<Item = Vec2i>,
+        mut can_step: impl FnMut(&T, &T) -> bool,
+    ) -> Distances<Vec2i, usize> {
+        bfs(starts, |pos| {
+            self.neighbors(pos)
+                .filter(|n| can_step(&self[*pos], &self[*n]))
+                .collect::<Vec<_>>()
+        })
+    }
+
+    /// [`dijkstra`] from `starts`, `cost(from, to)` is the cost of stepping between the values
+    /// of two neighbouring cells, `None` if it isn't possible.
+    pub fn dijkstra<C: Copy + Ord + Zero>(
+        &self,
+        starts: impl IntoIterator<Item = Vec2i>,
+        mut cost: impl FnMut(&T, &T) -> Option<C>,
+    ) -> Distances<Vec2i, C> {
+        dijkstra(starts, |pos| {
+            self.neighbors(pos)
+                .filter_map(|n| Some((n, cost(&self[*pos], &self[n])?)))
+                .collect::<Vec<_>>()
+        })
+    }
+
+    /// [`astar`] from `start` to `goal` with costs like in [`Grid::dijkstra`].
+    pub fn astar<C: Copy + Ord + Zero>(
+        &self,
+        start: Vec2i,
+        goal: Vec2i,
+        mut cost: impl FnMut(&T, &T) -> Option<C>,
+        heuristic: impl FnMut(&Vec2i) -> C,
+    ) -> Option<(Vec<Vec2i>, C)> {
+        astar(
+            start,
+            |pos| {
+                self.neighbors(pos)
+                    .filter_map(|n| Some((n, cost(&self[*pos], &self[n])?)))
+                    .collect::<Vec<_>>()
+            },
+            heuristic,
+            |pos| *pos == goal,
+        )
+    }
+
+    /// The cells reachable from `start` through cells whose values are `passable`.
+    pub fn flood_fill(
+        &self,
+        start: Vec2i,
+        mut passable: impl FnMut(&T) -> bool,
+    ) -> FxHashSet<Vec2i> {
+        if !self.in_bounds(&start) || !passable(&self[start]) {
+            return FxHashSet::default();
+        }
+
+        flood_fill([start], |pos| {
+            self.neighbors(pos)
+                .filter(|n| passable(&self[*n]))
+                .collect::<Vec<_>>()
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    fn maze() -> Grid<char> {
+        "\
+S..#....
+.#.#.##.
+.#...#..
+.####.#.
+......#E
+"
+        .parse()
+        .unwrap()
+    }
+
+    fn find(grid: &Grid<char>, c: char) -> Vec2i {
+        grid.pos_iter().find(|(_, t)| **t == c).unwrap().0
+    }
+
+    #[test]
+    fn test_bfs() {
+        let grid = maze();
+        let (start, end) = (find(&grid, 'S'), find(&grid, 'E'));
+        let distances = grid.bfs([start], |_, to| *to != '#');
+        assert_eq!(Some(15), distances.get(&end));
+        assert_eq!(None, distances.get(&Vec2i::new(3, 0)));
+
+        let path = distances.path_to(&end).unwrap();
+        assert_eq!((start, end, 16), (path[0], path[15], path.len()));
+        assert!(path
+            .windows(2)
+            .all(|w| lp1_distance(&w[0], &w[1]) == 1 && grid[w[1]] != '#'));
+
+        let from_both = grid.bfs([start, end], |_, to| *to != '#');
+        assert_eq!(Some(0), from_both.get(&end));
+        assert_eq!(Some(1), from_both.get(&Vec2i::new(7, 3)));
+    }
+
+    fn lp1_distance(a: &Vec2i, b: &Vec2i) -> i64 {
+        (a - b).abs().sum()
+    }
+
+    #[test]
+    fn test_dijkstra_astar() {
+        let grid: Grid<char> = "\
+1163751
+1381373
+2136511
+3694931
+"
+        .parse()
+        .unwrap();
+        let cost = |_: &char, to: &char| to.to_digit(10);
+        let (start, end) = (Vec2i::new(0, 0), Vec2i::new(6, 3));
+        let distances = grid.dijkstra([start], cost);
+        assert_eq!(Some(21), distances.get(&end));
+
+        let (path, total) = grid
+            .astar(start, end, cost, |pos| lp1_distance(pos, &end) as u32)
+            .unwrap();
+        assert_eq!(21, total);
+        assert_eq!(
+            total,
+            path[1..]
+                .iter()
+                .map(|p| grid[*p].to_digit(10).unwrap())
+                .sum::<u32>()
+        );
+        assert_eq!(distances.path_to(&end).unwrap().len(), path.len());
+
+        let walls: Grid<char> = "..#\n###\n...\n".parse().unwrap();
+        assert_eq!(
+            None,
+            walls.astar(
+                start,
+                Vec2i::new(2, 2),
+                |_, to| (*to == '.').then_some(1),
+                |_| 0
+            )
+        );
+    }
+
+    #[test]
+    fn test_flood_fill() {
+        let grid = maze();
+        let region = grid.flood_fill(Vec2i::new(7, 0), |t| *t != '#');
+        assert_eq!(27, region.len());
+        assert!(grid.flood_fill(Vec2i::new(3, 0), |t| *t != '#').is_empty());
+
+        // an infinite graph only limited by the neighbour function
+        let line = flood_fill([0], |n: &i32| {
+            [n - 1, n + 1].into_iter().filter(|n| n.abs() <= 3)
+        });
+        assert_eq!(7, line.len());
+    }
+}
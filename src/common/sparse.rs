@@ -0,0 +1,234 @@
+use rustc_hash::FxHashMap;
+
+use super::{Grid, Vec2i};
+
+/// An unbounded grid that only stores the occupied cells, keeping track of the smallest
+/// rectangle containing all of them.
+#[derive(Debug, Clone)]
+pub struct SparseGrid<T> {
+    cells: FxHashMap<Vec2i, T>,
+    bounds: Option<(Vec2i, Vec2i)>,
+}
+
+impl<T> Default for SparseGrid<T> {
+    fn default() -> Self {
+        Self {
+            cells: FxHashMap::default(),
+            bounds: None,
+        }
+    }
+}
+
+impl<T: PartialEq> PartialEq for SparseGrid<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cells == other.cells
+    }
+}
+
+impl<T: Eq> Eq for SparseGrid<T> {}
+
+impl<T> SparseGrid<T> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Converts the cells of a dense grid for which `keep` holds.
+    pub fn from_grid(grid: &Grid<T>, mut keep: impl FnMut(&T) -> bool) -> Self
+    where
+        T: Clone,
+    {
+        grid.pos_iter()
+            .filter(|(_, t)| keep(t))
+            .map(|(pos, t)| (pos, t.clone()))
+            .collect()
+    }
+
+    pub fn len(&self) -> usize {
+        self.cells.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cells.is_empty()
+    }
+
+    pub fn get(&self, pos: &Vec2i) -> Option<&T> {
+        self.cells.get(pos)
+    }
+
+    pub fn get_mut(&mut self, pos: &Vec2i) -> Option<&mut T> {
+        self.cells.get_mut(pos)
+    }
+
+    pub fn contains(&self, pos: &Vec2i) -> bool {
+        self.cells.contains_key(pos)
+    }
+
+    /// Sets the cell at `pos` and returns its previous value.
+    pub fn insert(&mut self, pos: Vec2i, value: T) -> Option<T> {
+        self.bounds = Some(match self.bounds {
+            None => (pos, pos),
+            Some((min, max)) => (min.inf(&pos), max.sup(&pos)),
+        });
+        self.cells.insert(pos, value)
+    }
+
+    /// Clears the cell at `pos` and returns its value. The bounds are only recomputed if the
+    /// cell was on their edge.
+    pub fn remove(&mut self, pos: &Vec2i) -> Option<T> {
+        let value = self.cells.remove(pos)?;
+        if let Some((min, max)) = self.bounds {
+            if pos.x == min.x || pos.y == min.y || pos.x == max.x || pos.y == max.y {
+                self.bounds = self.compute_bounds();
+            }
+        }
+        Some(value)
+    }
+
+    pub fn clear(&mut self) {
+        self.cells.clear();
+        self.bounds = None;
+    }
+
+    fn compute_bounds(&self) -> Option<(Vec2i, Vec2i)> {
+        let mut keys = self.cells.keys();
+        let first = *keys.next()?;
+        Some(keys.fold((first, first), |(min, max), p| (min.inf(p), max.sup(p))))
+    }
+
+    /// The inclusive minimum and maximum corner of the occupied cells.
+    pub fn bounds(&self) -> Option<(Vec2i, Vec2i)> {
+        self.bounds
+    }
+
+    /// The number of cells, occupied or not, within the bounds.
+    pub fn area(&self) -> usize {
+        self.bounds.map_or(0, |(min, max)| {
+            let size = max - min;
+            (size.x + 1) as usize * (size.y + 1) as usize
+        })
+    }
+
+    /// The occupied cells in arbitrary order.
+    pub fn iter(&self) -> impl Iterator<Item = (&Vec2i, &T)> {
+        self.cells.iter()
+    }
+
+    pub fn positions(&self) -> impl Iterator<Item = &Vec2i> {
+        self.cells.keys()
+    }
+
+    /// The rows within the bounds from top to bottom, each with all cells from left to right.
+    pub fn rows(&self) -> impl Iterator<Item = impl Iterator<Item = (Vec2i, Option<&T>)>> {
+        let (min, max) = self
+            .bounds
+            .unwrap_or((Vec2i::new(0, 0), Vec2i::new(-1, -1)));
+        (min.y..=max.y).map(move |y| {
+            (min.x..=max.x).map(move |x| {
+                let pos = Vec2i::new(x, y);
+                (pos, self.cells.get(&pos))
+            })
+        })
+    }
+
+    /// A dense grid of the cells within the bounds with the empty ones set to `fill`, together
+    /// with the position of its top left corner.
+    pub fn to_grid(&self, fill: T) -> (Vec2i, Grid<T>)
+    where
+        T: Clone,
+    {
+        let Some((min, max)) = self.bounds else {
+            return (Vec2i::zeros(), Grid::new(0, 0, vec![]));
+        };
+        let size = max - min;
+        let mut grid = Grid::new_from_element((size.x + 1) as usize, (size.y + 1) as usize, fill);
+        for (pos, t) in &self.cells {
+            grid[pos - min] = t.clone();
+        }
+        (min, grid)
+    }
+
+    /// One line per row within the bounds, with `empty` for the empty cells.
+    pub fn render(&self, empty: char, mut to_char: impl FnMut(&T) -> char) -> String {
+        let mut s = String::new();
+        for row in self.rows() {
+            s.extend(row.map(|(_, t)| t.map_or(empty, &mut to_char)));
+            s.push('\n');
+        }
+        s
+    }
+}
+
+impl<T> FromIterator<(Vec2i, T)> for SparseGrid<T> {
+    fn from_iter<I: IntoIterator<Item = (Vec2i, T)>>(iter: I) -> Self {
+        let mut grid = Self::new();
+        grid.extend(iter);
+        grid
+    }
+}
+
+impl<T> Extend<(Vec2i, T)> for SparseGrid<T> {
+    fn extend<I: IntoIterator<Item = (Vec2i, T)>>(&mut self, iter: I) {
+        for (pos, t) in iter {
+            self.insert(pos, t);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_bounds() {
+        let mut grid = SparseGrid::new();
+        assert_eq!((None, 0), (grid.bounds(), grid.area()));
+
+        grid.insert(Vec2i::new(2, -1), 'a');
+        grid.insert(Vec2i::new(-3, 4), 'b');
+        grid.insert(Vec2i::new(0, 0), 'c');
+        assert_eq!(Some((Vec2i::new(-3, -1), Vec2i::new(2, 4))), grid.bounds());
+        assert_eq!(36, grid.area());
+
+        assert_eq!(Some('c'), grid.remove(&Vec2i::new(0, 0)));
+        assert_eq!(Some((Vec2i::new(-3, -1), Vec2i::new(2, 4))), grid.bounds());
+        assert_eq!(Some('b'), grid.remove(&Vec2i::new(-3, 4)));
+        assert_eq!(Some((Vec2i::new(2, -1), Vec2i::new(2, -1))), grid.bounds());
+        assert_eq!(None, grid.remove(&Vec2i::new(-3, 4)));
+
+        grid.clear();
+        assert_eq!((None, 0), (grid.bounds(), grid.len()));
+    }
+
+    #[test]
+    fn test_render_and_convert() {
+        let dense: Grid<char> = "..#\n#..\n...\n.#.\n".parse().unwrap();
+        let mut grid = SparseGrid::from_grid(&dense, |c| *c == '#');
+        assert_eq!(3, grid.len());
+        assert_eq!("..#\n#..\n...\n.#.\n", grid.render('.', |c| *c));
+
+        grid.insert(Vec2i::new(-1, 1), 'x');
+        assert_eq!("...#\nx#..\n....\n..#.\n", grid.render('.', |c| *c));
+        assert_eq!(
+            vec![1, 2, 0, 1],
+            grid.rows()
+                .map(|row| row.filter(|(_, t)| t.is_some()).count())
+                .collect::<Vec<_>>()
+        );
+
+        let (origin, back) = grid.to_grid(' ');
+        assert_eq!(
+            (Vec2i::new(-1, 0), 4, 4),
+            (origin, back.size_x, back.size_y)
+        );
+        assert_eq!('x', back[Vec2i::new(0, 1)]);
+        assert_eq!(
+            grid,
+            SparseGrid::from_grid(&back, |c| *c != ' ')
+                .iter()
+                .map(|(p, c)| (p + origin, *c))
+                .collect()
+        );
+    }
+}
@@ -0,0 +1,201 @@
+use std::ops::Index;
+
+use super::{Grid, Vec2i};
+
+impl<T> Grid<T> {
+    /// A grid with the value of every cell computed from its position.
+    pub fn from_fn(size_x: usize, size_y: usize, mut f: impl FnMut(Vec2i) -> T) -> Self {
+        let grid = (0..size_y as i64)
+            .flat_map(|y| (0..size_x as i64).map(move |x| Vec2i::new(x, y)))
+            .map(&mut f)
+            .collect();
+        Self::new(size_x, size_y, grid)
+    }
+
+    pub fn map<U>(&self, f: impl FnMut(&T) -> U) -> Grid<U> {
+        Grid::new(self.size_x, self.size_y, self.grid.iter().map(f).collect())
+    }
+
+    /// The rectangle of `size_x` times `size_y` cells with its top left corner at `origin`,
+    /// `None` if it doesn't fit into the grid.
+    pub fn view(&self, origin: Vec2i, size_x: usize, size_y: usize) -> Option<GridView<'_, T>> {
+        let end = origin + Vec2i::new(size_x as i64, size_y as i64);
+        let fits = origin.x >= 0
+            && origin.y >= 0
+            && end.x <= self.size_x as i64
+            && end.y <= self.size_y as i64;
+        fits.then_some(GridView {
+            grid: self,
+            origin,
+            size_x,
+            size_y,
+        })
+    }
+
+    /// Splits the grid into blocks of `size_x` times `size_y` cells, row by row. Yields the
+    /// position of each block in units of blocks, cells that don't fill a whole block at the
+    /// right and bottom edge are left out.
+    pub fn tiles(
+        &self,
+        size_x: usize,
+        size_y: usize,
+    ) -> impl Iterator<Item = (Vec2i, GridView<'_, T>)> + '_ {
+        assert!(size_x > 0 && size_y > 0, "empty tiles");
+        let (count_x, count_y) = (self.size_x / size_x, self.size_y / size_y);
+        (0..count_y as i64)
+            .flat_map(move |y| (0..count_x as i64).map(move |x| Vec2i::new(x, y)))
+            .map(move |tile| {
+                let origin = Vec2i::new(tile.x * size_x as i64, tile.y * size_y as i64);
+                (tile, self.view(origin, size_x, size_y).unwrap())
+            })
+    }
+}
+
+impl<T: Clone> Grid<T> {
+    /// Swaps rows and columns.
+    pub fn transpose(&self) -> Self {
+        Self::from_fn(self.size_y, self.size_x, |p| {
+            self[Vec2i::new(p.y, p.x)].clone()
+        })
+    }
+
+    /// Mirrors the columns, left becomes right.
+    pub fn flip_horizontal(&self) -> Self {
+        let max_x = self.size_x as i64 - 1;
+        Self::from_fn(self.size_x, self.size_y, |p| {
+            self[Vec2i::new(max_x - p.x, p.y)].clone()
+        })
+    }
+
+    /// Mirrors the rows, top becomes bottom.
+    pub fn flip_vertical(&self) -> Self {
+        let max_y = self.size_y as i64 - 1;
+        Self::from_fn(self.size_x, self.size_y, |p| {
+            self[Vec2i::new(p.x, max_y - p.y)].clone()
+        })
+    }
+
+    /// Rotates by 90° clockwise.
+    pub fn rotate_cw(&self) -> Self {
+        self.transpose().flip_horizontal()
+    }
+
+    /// Rotates by 90° counterclockwise.
+    pub fn rotate_ccw(&self) -> Self {
+        self.transpose().flip_vertical()
+    }
+
+    /// Rotates by `quarter_turns` times 90° clockwise, negative values turn counterclockwise.
+    pub fn rotate(&self, quarter_turns: i32) -> Self {
+        match quarter_turns.rem_euclid(4) {
+            0 => self.clone(),
+            1 => self.rotate_cw(),
+            2 => self.flip_horizontal().flip_vertical(),
+            _ => self.rotate_ccw(),
+        }
+    }
+}
+
+/// A borrowed rectangle of a [`Grid`] with its own coordinates starting at `(0, 0)`.
+#[derive(Debug, Clone, Copy)]
+pub struct GridView<'a, T> {
+    grid: &'a Grid<T>,
+    origin: Vec2i,
+    pub size_x: usize,
+    pub size_y: usize,
+}
+
+impl<'a, T> GridView<'a, T> {
+    /// The position of the top left corner in the underlying grid.
+    pub fn origin(&self) -> Vec2i {
+        self.origin
+    }
+
+    pub fn in_bounds(&self, pos: &Vec2i) -> bool {
+        pos.x >= 0 && (pos.x as usize) < self.size_x && pos.y >= 0 && (pos.y as usize) < self.size_y
+    }
+
+    pub fn get(&self, pos: &Vec2i) -> Option<&'a T> {
+        self.in_bounds(pos).then(|| &self.grid[self.origin + pos])
+    }
+
+    pub fn pos_iter(&self) -> impl Iterator<Item = (Vec2i, &'a T)> + '_ {
+        (0..self.size_y as i64)
+            .flat_map(|y| (0..self.size_x as i64).map(move |x| Vec2i::new(x, y)))
+            .map(|p| (p, &self.grid[self.origin + p]))
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &'a T> + '_ {
+        self.pos_iter().map(|(_, t)| t)
+    }
+
+    pub fn to_grid(&self) -> Grid<T>
+    where
+        T: Clone,
+    {
+        Grid::from_fn(self.size_x, self.size_y, |p| self[p].clone())
+    }
+}
+
+impl<T> Index<Vec2i> for GridView<'_, T> {
+    type Output = T;
+
+    fn index(&self, index: Vec2i) -> &Self::Output {
+        assert!(self.in_bounds(&index), "position outside of the view");
+        &self.grid[self.origin + index]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    fn to_string(grid: &Grid<char>) -> String {
+        (0..grid.size_y as i64)
+            .map(|y| grid.iter_row(y).collect::<String>() + "\n")
+            .collect()
+    }
+
+    #[test]
+    fn test_transforms() {
+        let grid: Grid<char> = "abc\ndef\n".parse().unwrap();
+        assert_eq!("ad\nbe\ncf\n", to_string(&grid.transpose()));
+        assert_eq!("cba\nfed\n", to_string(&grid.flip_horizontal()));
+        assert_eq!("def\nabc\n", to_string(&grid.flip_vertical()));
+        assert_eq!("da\neb\nfc\n", to_string(&grid.rotate_cw()));
+        assert_eq!("cf\nbe\nad\n", to_string(&grid.rotate_ccw()));
+        assert_eq!("fed\ncba\n", to_string(&grid.rotate(2)));
+        assert_eq!(grid.rotate_ccw(), grid.rotate(-1));
+        assert_eq!(grid, grid.rotate(4));
+        assert_eq!(grid, grid.rotate_cw().rotate_ccw());
+        assert_eq!(grid.rotate(1), grid.rotate(-3));
+    }
+
+    #[test]
+    fn test_views() {
+        let grid: Grid<char> = "abcd\nefgh\nijkl\n".parse().unwrap();
+        let view = grid.view(Vec2i::new(1, 1), 3, 2).unwrap();
+        assert_eq!('f', view[Vec2i::new(0, 0)]);
+        assert_eq!(Some(&'l'), view.get(&Vec2i::new(2, 1)));
+        assert_eq!(None, view.get(&Vec2i::new(3, 0)));
+        assert_eq!("fghjkl", view.iter().collect::<String>());
+        assert_eq!("fgh\njkl\n", to_string(&view.to_grid()));
+        assert!(grid.view(Vec2i::new(2, 2), 3, 1).is_none());
+        assert!(grid.view(Vec2i::new(-1, 0), 1, 1).is_none());
+
+        let tiles = grid
+            .tiles(2, 2)
+            .map(|(pos, view)| (pos, view.origin(), view.iter().collect::<String>()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            vec![
+                (Vec2i::new(0, 0), Vec2i::new(0, 0), "abef".to_string()),
+                (Vec2i::new(1, 0), Vec2i::new(2, 0), "cdgh".to_string()),
+            ],
+            tiles
+        );
+        assert_eq!(12, grid.tiles(1, 1).count());
+    }
+}
@@ -1,28 +1,36 @@
+//! Day 1: Calorie Counting, the calories carried by a group of elves.
+
 use std::cmp::Reverse;
-use std::str::FromStr;
 
 use aoc_runner_derive::{aoc, aoc_generator};
 use itertools::Itertools;
 
-type Num = u32;
+use crate::common::input::normalize;
+use crate::common::{parse_located, ParseError};
+use crate::solution::Day;
 
-#[aoc_generator(day1)]
-pub fn input_generator(input: &str) -> Vec<Vec<Num>> {
+pub type Num = u32;
+
+/// Parses the calories of every elf, with a blank line between two elves.
+pub fn parse(input: &str) -> Result<Vec<Vec<Num>>, ParseError> {
+    let input = &normalize(input, true);
     let all_calories = input
         .lines()
         .map(|l| {
+            let l = l.trim();
             if l.is_empty() {
-                0
+                Ok(0)
             } else {
-                let result = Num::from_str(l).unwrap();
+                let result: Num = parse_located(input, l)?;
                 if result == 0 {
-                    panic!();
+                    return Err(ParseError::new("calories must be positive").located_in(input, l));
                 }
 
-                result
+                Ok(result)
             }
         })
-        .collect_vec();
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.in_day(1))?;
 
     let mut calories_grouped = vec![];
     let mut current_group = vec![];
@@ -39,24 +47,65 @@ pub fn input_generator(input: &str) -> Vec<Vec<Num>> {
         calories_grouped.push(current_group);
     }
 
-    calories_grouped
+    Ok(calories_grouped)
 }
 
-#[aoc(day1, part1)]
-pub fn part1(input: &[Vec<Num>]) -> Num {
-    input
+/// The total calories of the `n` elves carrying the most.
+pub fn top_calories(elves: &[Vec<Num>], n: usize) -> Num {
+    elves
         .iter()
         .map(|calories| calories.iter().sum())
-        .max()
-        .unwrap()
+        .sorted_unstable_by_key(|c: &Num| Reverse(*c))
+        .take(n)
+        .sum()
+}
+
+pub fn solve_part1(input: &[Vec<Num>]) -> Num {
+    top_calories(input, 1)
+}
+
+pub fn solve_part2(input: &[Vec<Num>]) -> Num {
+    top_calories(input, 3)
+}
+
+#[doc(hidden)]
+#[aoc_generator(day1)]
+pub fn input_generator(input: &str) -> Result<Vec<Vec<Num>>, ParseError> {
+    parse(input)
 }
 
+#[doc(hidden)]
+#[aoc(day1, part1)]
+pub fn part1(input: &[Vec<Num>]) -> Num {
+    solve_part1(input)
+}
+
+#[doc(hidden)]
 #[aoc(day1, part2)]
 pub fn part2(input: &[Vec<Num>]) -> Num {
-    input
-        .iter()
-        .map(|calories| calories.iter().sum())
-        .sorted_unstable_by_key(|c: &Num| Reverse(*c))
-        .take(3)
-        .sum()
+    solve_part2(input)
+}
+
+pub(crate) static SOLUTION: Day<Vec<Vec<Num>>> = Day {
+    day: 1,
+    parse,
+    part1: |input, _| Ok(solve_part1(input).into()),
+    part2: Some(|input, _| Ok(solve_part2(input).into())),
+};
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::common::input::windows_input;
+
+    #[test]
+    fn test_crlf() {
+        let input = parse(&windows_input(include_str!(
+            "../fixtures/day01/example.txt"
+        )))
+        .unwrap();
+        assert_eq!(24000, solve_part1(&input));
+    }
 }
@@ -1,7 +1,12 @@
+//! Day 2: Rock Paper Scissors, scoring a strategy guide of rounds.
+
 use std::str::FromStr;
 
 use aoc_runner_derive::{aoc, aoc_generator};
-use itertools::Itertools;
+
+use crate::common::input::normalize;
+use crate::common::{parse_lines_with, parse_located, ParseError};
+use crate::solution::Day;
 
 #[derive(Debug, Eq, PartialEq, Copy, Clone)]
 pub enum Hand {
@@ -11,6 +16,7 @@ pub enum Hand {
 }
 
 impl Hand {
+    /// The score for choosing this hand.
     pub fn score(&self) -> u32 {
         match self {
             Hand::Rock => 1,
@@ -19,6 +25,7 @@ impl Hand {
         }
     }
 
+    /// The score for playing this hand against `other`: 6 for a win, 3 for a draw.
     pub fn winning_score(&self, other: &Hand) -> u32 {
         match (self, other) {
             (a, b) if a == b => 3,
@@ -33,6 +40,8 @@ impl Hand {
     }
 }
 
+/// The first column of the guide, the hand of the opponent.
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
 pub enum Left {
     A,
     B,
@@ -50,18 +59,22 @@ impl Left {
 }
 
 impl FromStr for Left {
-    type Err = ();
+    type Err = ParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         match s {
             "A" => Ok(Left::A),
             "B" => Ok(Left::B),
             "C" => Ok(Left::C),
-            _ => Err(()),
+            _ => Err(ParseError::new(format!(
+                "expected one of A, B, C but got '{s}'"
+            ))),
         }
     }
 }
 
+/// The second column of the guide, either your hand or the outcome you need.
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
 pub enum Right {
     X,
     Y,
@@ -77,6 +90,7 @@ impl Right {
         }
     }
 
+    /// The hand that loses (X), draws (Y) or wins (Z) against `other_hand`.
     pub fn to_desired_hand(&self, other_hand: &Hand) -> Hand {
         match (self, other_hand) {
             (Self::X, Hand::Rock) => Hand::Scissors,
@@ -91,50 +105,91 @@ impl Right {
 }
 
 impl FromStr for Right {
-    type Err = ();
+    type Err = ParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         match s {
             "X" => Ok(Right::X),
             "Y" => Ok(Right::Y),
             "Z" => Ok(Right::Z),
-            _ => Err(()),
+            _ => Err(ParseError::new(format!(
+                "expected one of X, Y, Z but got '{s}'"
+            ))),
         }
     }
 }
 
-#[aoc_generator(day2)]
-pub fn input_generator(input: &str) -> Vec<(Left, Right)> {
-    input
-        .lines()
-        .map(|l| {
-            let v = l.trim().splitn(2, ' ').collect_vec();
-            (
-                Left::from_str(v[0]).unwrap(),
-                Right::from_str(v[1]).unwrap(),
-            )
-        })
-        .collect()
+pub fn parse(input: &str) -> Result<Vec<(Left, Right)>, ParseError> {
+    let input = &normalize(input, true);
+    parse_lines_with(input, |l| {
+        let (left, right) = l
+            .split_once(' ')
+            .ok_or_else(|| ParseError::new("expected two hands separated by a space"))?;
+        Ok((parse_located(l, left)?, parse_located(l, right.trim())?))
+    })
+    .map_err(|e| e.in_day(2))
 }
 
-#[aoc(day2, part1)]
-pub fn part1(input: &[(Left, Right)]) -> u32 {
-    input.iter().map(|(l, r)| get_score_1(l, r)).sum()
+pub fn solve_part1(input: &[(Left, Right)]) -> u32 {
+    input.iter().map(|(l, r)| score_as_hand(l, r)).sum()
 }
 
-#[aoc(day2, part2)]
-pub fn part2(input: &[(Left, Right)]) -> u32 {
-    input.iter().map(|(l, r)| get_score_2(l, r)).sum()
+pub fn solve_part2(input: &[(Left, Right)]) -> u32 {
+    input.iter().map(|(l, r)| score_as_outcome(l, r)).sum()
 }
 
-fn get_score_1(l: &Left, r: &Right) -> u32 {
+/// The score of a round if the second column is your hand.
+pub fn score_as_hand(l: &Left, r: &Right) -> u32 {
     let other_hand = l.to_hand();
     let my_hand = r.to_hand();
     my_hand.winning_score(&other_hand) + my_hand.score()
 }
 
-fn get_score_2(l: &Left, r: &Right) -> u32 {
+/// The score of a round if the second column is the outcome.
+pub fn score_as_outcome(l: &Left, r: &Right) -> u32 {
     let other_hand = l.to_hand();
     let my_hand = r.to_desired_hand(&other_hand);
     my_hand.winning_score(&other_hand) + my_hand.score()
 }
+
+#[doc(hidden)]
+#[aoc_generator(day2)]
+pub fn input_generator(input: &str) -> Result<Vec<(Left, Right)>, ParseError> {
+    parse(input)
+}
+
+#[doc(hidden)]
+#[aoc(day2, part1)]
+pub fn part1(input: &[(Left, Right)]) -> u32 {
+    solve_part1(input)
+}
+
+#[doc(hidden)]
+#[aoc(day2, part2)]
+pub fn part2(input: &[(Left, Right)]) -> u32 {
+    solve_part2(input)
+}
+
+pub(crate) static SOLUTION: Day<Vec<(Left, Right)>> = Day {
+    day: 2,
+    parse,
+    part1: |input, _| Ok(solve_part1(input).into()),
+    part2: Some(|input, _| Ok(solve_part2(input).into())),
+};
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::common::input::windows_input;
+
+    #[test]
+    fn test_crlf() {
+        let input = parse(&windows_input(include_str!(
+            "../fixtures/day02/example.txt"
+        )))
+        .unwrap();
+        assert_eq!(15, solve_part1(&input));
+    }
+}
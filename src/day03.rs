@@ -1,43 +1,55 @@
+//! Day 3: Rucksack Reorganization, finding the items shared between compartments and elves.
+
 use std::collections::HashSet;
 
 use aoc_runner_derive::{aoc, aoc_generator};
 use itertools::Itertools;
 
-type Rucksack = (HashSet<u8>, HashSet<u8>);
+use crate::common::input::normalize;
+use crate::common::{parse_lines_with, ParseError};
+use crate::solution::Day;
 
-#[aoc_generator(day3)]
-pub fn input_generator(input: &str) -> Vec<Rucksack> {
-    input
-        .lines()
-        .map(|l| l.trim())
-        .filter(|l| !l.is_empty())
-        .map(|l| (&l[0..(l.len() / 2)], &l[(l.len() / 2)..l.len()]))
-        .map(|(l, r)| (letters_to_numbers(l), letters_to_numbers(r)))
-        .collect()
+/// The priorities of the items in both compartments.
+pub type Rucksack = (HashSet<u8>, HashSet<u8>);
+
+pub fn parse(input: &str) -> Result<Vec<Rucksack>, ParseError> {
+    let input = &normalize(input, true);
+    parse_lines_with(input, |l| {
+        if l.len() % 2 != 0 {
+            return Err(ParseError::new(
+                "rucksack compartments must have the same size",
+            ));
+        }
+
+        let (left, right) = l.split_at(l.len() / 2);
+        Ok((letters_to_numbers(l, left)?, letters_to_numbers(l, right)?))
+    })
+    .map_err(|e| e.in_day(3))
 }
 
-fn letters_to_numbers(s: &str) -> HashSet<u8> {
-    s.chars().map(letter_to_number).collect()
+fn letters_to_numbers(line: &str, s: &str) -> Result<HashSet<u8>, ParseError> {
+    s.char_indices()
+        .map(|(i, c)| priority(c).map_err(|e| e.at_column(i + 1).located_in(line, s)))
+        .collect()
 }
 
-fn letter_to_number(c: char) -> u8 {
+/// The priority of an item, 1 to 26 for `a` to `z` and 27 to 52 for `A` to `Z`.
+pub fn priority(c: char) -> Result<u8, ParseError> {
     match c {
-        'a'..='z' => (c as u8 - b'a') + 1,
-        'A'..='Z' => (c as u8 - b'A') + 27,
-        _ => panic!(),
+        'a'..='z' => Ok((c as u8 - b'a') + 1),
+        'A'..='Z' => Ok((c as u8 - b'A') + 27),
+        _ => Err(ParseError::new(format!("invalid item '{c}'"))),
     }
 }
 
-#[aoc(day3, part1)]
-pub fn part1(input: &[Rucksack]) -> u32 {
+pub fn solve_part1(input: &[Rucksack]) -> u32 {
     input
         .iter()
         .map(|(l, r)| *l.intersection(r).at_most_one().unwrap().unwrap() as u32)
         .sum()
 }
 
-#[aoc(day3, part2)]
-pub fn part2(input: &[Rucksack]) -> u32 {
+pub fn solve_part2(input: &[Rucksack]) -> u32 {
     input
         .iter()
         .chunks(3)
@@ -56,3 +68,45 @@ pub fn part2(input: &[Rucksack]) -> u32 {
         })
         .sum()
 }
+
+#[doc(hidden)]
+#[aoc_generator(day3)]
+pub fn input_generator(input: &str) -> Result<Vec<Rucksack>, ParseError> {
+    parse(input)
+}
+
+#[doc(hidden)]
+#[aoc(day3, part1)]
+pub fn part1(input: &[Rucksack]) -> u32 {
+    solve_part1(input)
+}
+
+#[doc(hidden)]
+#[aoc(day3, part2)]
+pub fn part2(input: &[Rucksack]) -> u32 {
+    solve_part2(input)
+}
+
+pub(crate) static SOLUTION: Day<Vec<Rucksack>> = Day {
+    day: 3,
+    parse,
+    part1: |input, _| Ok(solve_part1(input).into()),
+    part2: Some(|input, _| Ok(solve_part2(input).into())),
+};
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::common::input::windows_input;
+
+    #[test]
+    fn test_crlf() {
+        let input = parse(&windows_input(include_str!(
+            "../fixtures/day03/example.txt"
+        )))
+        .unwrap();
+        assert_eq!(157, solve_part1(&input));
+    }
+}
@@ -1,49 +1,85 @@
+//! Day 4: Camp Cleanup, pairs of section assignments that contain or overlap each other.
+
 use aoc_runner_derive::{aoc, aoc_generator};
 use itertools::Itertools;
 
-type Interval = (u32, u32);
-type Pair = (Interval, Interval);
+use crate::common::input::normalize;
+use crate::common::{parse_lines_with, parse_located, Interval, ParseError};
+use crate::solution::Day;
 
-#[aoc_generator(day4)]
-pub fn input_generator(input: &str) -> Vec<Pair> {
+/// The sections assigned to two elves.
+pub type Pair = (Interval<u32>, Interval<u32>);
+
+pub fn parse(input: &str) -> Result<Vec<Pair>, ParseError> {
+    let input = &normalize(input, true);
+    parse_lines_with(input, |l| {
+        l.splitn(2, ',')
+            .map(|range| {
+                let (lower, upper) = range
+                    .split_once('-')
+                    .ok_or_else(|| ParseError::new("expected range 'a-b'").located_in(l, range))?;
+                let lower: u32 = parse_located(l, lower)?;
+                let upper: u32 = parse_located(l, upper)?;
+                Interval::try_new(lower, upper)
+                    .ok_or_else(|| ParseError::new("range is empty").located_in(l, range))
+            })
+            .collect::<Result<Vec<_>, _>>()?
+            .into_iter()
+            .collect_tuple()
+            .ok_or_else(|| ParseError::new("expected two ranges separated by ','"))
+    })
+    .map_err(|e| e.in_day(4))
+}
+
+pub fn solve_part1(input: &[Pair]) -> usize {
     input
-        .lines()
-        .map(|l| {
-            l.splitn(2, ',')
-                .map(|range| {
-                    let (lower, upper) = range
-                        .splitn(2, '-')
-                        .map(|n| n.parse::<u32>().unwrap())
-                        .collect_tuple()
-                        .unwrap();
-                    if lower > upper {
-                        panic!()
-                    }
-                    (lower, upper)
-                })
-                .collect_tuple()
-                .unwrap()
-        })
-        .collect()
+        .iter()
+        .filter(|(l, r)| l.contains_interval(r) || r.contains_interval(l))
+        .count()
 }
 
-fn fully_contains(a: &Interval, b: &Interval) -> bool {
-    b.0 >= a.0 && b.1 <= a.1
+pub fn solve_part2(input: &[Pair]) -> usize {
+    input.iter().filter(|(l, r)| l.overlaps(r)).count()
 }
 
-fn overlaps(a: &Interval, b: &Interval) -> bool {
-    a.0 <= b.1 && a.1 >= b.0
+#[doc(hidden)]
+#[aoc_generator(day4)]
+pub fn input_generator(input: &str) -> Result<Vec<Pair>, ParseError> {
+    parse(input)
 }
 
+#[doc(hidden)]
 #[aoc(day4, part1)]
 pub fn part1(input: &[Pair]) -> usize {
-    input
-        .iter()
-        .filter(|(l, r)| fully_contains(l, r) || fully_contains(r, l))
-        .count()
+    solve_part1(input)
 }
 
+#[doc(hidden)]
 #[aoc(day4, part2)]
 pub fn part2(input: &[Pair]) -> usize {
-    input.iter().filter(|(l, r)| overlaps(l, r)).count()
+    solve_part2(input)
+}
+
+pub(crate) static SOLUTION: Day<Vec<Pair>> = Day {
+    day: 4,
+    parse,
+    part1: |input, _| Ok(solve_part1(input).into()),
+    part2: Some(|input, _| Ok(solve_part2(input).into())),
+};
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::common::input::windows_input;
+
+    #[test]
+    fn test_crlf() {
+        let input = parse(&windows_input(include_str!(
+            "../fixtures/day04/example.txt"
+        )))
+        .unwrap();
+        assert_eq!(2, solve_part1(&input));
+    }
 }
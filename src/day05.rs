@@ -1,15 +1,32 @@
+//! Day 5: Supply Stacks, moving crates between stacks with two kinds of crane.
+
 use aoc_runner_derive::{aoc, aoc_generator};
 use itertools::Itertools;
-use regex::Regex;
 
-type Move = (usize, usize, usize);
+use crate::common::input::{normalize, split_at_blank_line};
+use crate::common::parser::parse_all;
+use crate::common::{parse_lines_with, Grid, ParseError};
+use crate::solution::Day;
+
+/// Moves `count` crates `from` one stack `to` another, both zero based.
+pub type Move = (usize, usize, usize);
 
+/// The stacks of crates, bottom first.
 #[derive(Debug, Clone)]
 pub struct Stacks {
     stacks: Vec<Vec<char>>,
 }
 
 impl Stacks {
+    pub fn new(stacks: Vec<Vec<char>>) -> Self {
+        Self { stacks }
+    }
+
+    pub fn stacks(&self) -> &[Vec<char>] {
+        &self.stacks
+    }
+
+    /// Moves the crates all at once, keeping their order.
     pub fn do_multi_move(&mut self, (count, from, to): &Move) {
         let from_stack = &mut self.stacks[*from];
         let len = from_stack.len();
@@ -17,6 +34,7 @@ impl Stacks {
         self.stacks[*to].extend_from_slice(&cs);
     }
 
+    /// Moves the crates one at a time, reversing their order.
     pub fn do_move(&mut self, (count, from, to): &Move) {
         for _ in 0..*count {
             let c = self.stacks[*from].pop().unwrap();
@@ -24,63 +42,77 @@ impl Stacks {
         }
     }
 
+    /// The crate on top of each non-empty stack.
     pub fn top_str(&self) -> String {
         self.stacks.iter().filter_map(|s| s.last()).collect()
     }
 }
 
-#[aoc_generator(day5)]
-pub fn input_generator(input: &str) -> (Stacks, Vec<Move>) {
-    let (a, b): (&str, &str) = input.split_once("\n\n").unwrap();
+pub fn parse(input: &str) -> Result<(Stacks, Vec<Move>), ParseError> {
+    let input = &normalize(input, true);
+    parse_stacks_and_moves(input).map_err(|e| e.in_day(5))
+}
+
+fn parse_stacks_and_moves(input: &str) -> Result<(Stacks, Vec<Move>), ParseError> {
+    let (a, b): (&str, &str) = split_at_blank_line(input)
+        .ok_or_else(|| ParseError::new("expected a blank line between stacks and moves"))?;
 
     let stacks: Vec<Vec<char>> = {
-        let lines = a.lines().collect_vec();
-        // let's hope all chars and numbers will always be ascii and only one char long
-        let cols = (lines.last().unwrap().len() + 1) / 4;
+        let drawing: Grid<char> =
+            Grid::parse_padded(a, ' ').map_err(|_| ParseError::new("missing stack drawing"))?;
+        // let's hope all chars and numbers will always be ascii and only one char long, the
+        // last stack may have lost the space after it if trailing whitespace was trimmed
+        let cols = (drawing.size_x + 2) / 4;
 
-        fn to_col_idx(i: usize) -> usize {
-            i * 4 + 1
+        fn to_col_idx(i: usize) -> i64 {
+            (i * 4 + 1) as i64
         }
 
         (0..cols)
-            .into_iter()
             .map(to_col_idx)
-            .map(|i| {
-                lines
-                    .iter()
+            .map(|x| {
+                drawing
+                    .iter_col(x)
                     .rev()
                     .skip(1)
-                    .map(|s| s.as_bytes()[i] as char)
-                    .filter(|c| *c != ' ')
+                    .filter(|c| **c != ' ')
+                    .copied()
                     .collect()
             })
             .collect()
     };
 
     let moves: Vec<(usize, usize, usize)> = {
-        let r = Regex::new(r"^move (\d+) from (\d+) to (\d+)$").unwrap();
-        b.trim()
-            .lines()
-            .map(|l| l.trim())
-            .filter(|l| !l.is_empty())
-            .map(|l| {
-                let c = r.captures(l).unwrap();
-                let count: usize = c[1].parse().unwrap();
-                let from: usize = c[2].parse().unwrap();
-                let to: usize = c[3].parse().unwrap();
-                if count < 1 || from < 1 || from > stacks.len() || to < 1 || to > stacks.len() {
-                    panic!();
-                }
-                (count, from - 1, to - 1)
-            })
-            .collect()
+        let mut heights = stacks.iter().map(Vec::len).collect_vec();
+        parse_lines_with(b, |l| {
+            let (count, from, to): (usize, usize, usize) = parse_all(l, |p| {
+                p.keyword("move")?;
+                let count = p.integer()?;
+                p.keyword("from")?;
+                let from = p.integer()?;
+                p.keyword("to")?;
+                Ok((count, from, p.integer()?))
+            })?;
+            if count < 1 || from < 1 || from > stacks.len() || to < 1 || to > stacks.len() {
+                return Err(ParseError::new("move refers to a non-existing stack"));
+            }
+            if count > heights[from - 1] {
+                return Err(ParseError::new(format!(
+                    "move of {count} crates from a stack of {}",
+                    heights[from - 1]
+                )));
+            }
+            heights[from - 1] -= count;
+            heights[to - 1] += count;
+            Ok((count, from - 1, to - 1))
+        })
+        .map_err(|e| e.located_in(input, b))?
     };
 
-    (Stacks { stacks }, moves)
+    Ok((Stacks::new(stacks), moves))
 }
 
-#[aoc(day5, part1)]
-pub fn part1((stacks, moves): &(Stacks, Vec<Move>)) -> String {
+pub fn solve_part1((stacks, moves): &(Stacks, Vec<Move>)) -> String {
     let mut stacks = stacks.clone();
     for op in moves {
         stacks.do_move(op);
@@ -89,8 +121,7 @@ pub fn part1((stacks, moves): &(Stacks, Vec<Move>)) -> String {
     stacks.top_str()
 }
 
-#[aoc(day5, part2)]
-pub fn part2((stacks, moves): &(Stacks, Vec<Move>)) -> String {
+pub fn solve_part2((stacks, moves): &(Stacks, Vec<Move>)) -> String {
     let mut stacks = stacks.clone();
     for op in moves {
         stacks.do_multi_move(op);
@@ -98,3 +129,80 @@ pub fn part2((stacks, moves): &(Stacks, Vec<Move>)) -> String {
 
     stacks.top_str()
 }
+
+#[doc(hidden)]
+#[aoc_generator(day5)]
+pub fn input_generator(input: &str) -> Result<(Stacks, Vec<Move>), ParseError> {
+    parse(input)
+}
+
+#[doc(hidden)]
+#[aoc(day5, part1)]
+pub fn part1(input: &(Stacks, Vec<Move>)) -> String {
+    solve_part1(input)
+}
+
+#[doc(hidden)]
+#[aoc(day5, part2)]
+pub fn part2(input: &(Stacks, Vec<Move>)) -> String {
+    solve_part2(input)
+}
+
+pub(crate) static SOLUTION: Day<(Stacks, Vec<Move>)> = Day {
+    day: 5,
+    parse,
+    part1: |input, _| Ok(solve_part1(input).into()),
+    part2: Some(|input, _| Ok(solve_part2(input).into())),
+};
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_invalid_move() {
+        let e = input_generator(
+            r"    [D]
+[N] [C]
+[Z] [M] [P]
+ 1   2   3
+
+move 1 from 2 to 1
+move 3 from 1 to 4
+",
+        )
+        .unwrap_err();
+        assert_eq!((5, 7, 1), (e.day, e.line, e.column));
+    }
+
+    #[test]
+    fn test_too_many_crates() {
+        let e = input_generator(
+            r"    [D]
+[N] [C]
+[Z] [M] [P]
+ 1   2   3
+
+move 1 from 2 to 1
+move 3 from 1 to 3
+move 2 from 1 to 2
+",
+        )
+        .unwrap_err();
+        assert_eq!((5, 8, 1), (e.day, e.line, e.column));
+        assert_eq!("move of 2 crates from a stack of 0", e.reason);
+    }
+
+    #[test]
+    fn test_crlf() {
+        let input = input_generator(
+            "    [D]    \r\n[N] [C]    \r\n[Z] [M] [P]\r\n 1   2   3 \r\n  \r\n\
+             move 1 from 2 to 1\r\nmove 3 from 1 to 3\r\nmove 2 from 2 to 1\r\nmove 1 from 1 to 2\r\n",
+        )
+        .unwrap();
+        assert_eq!("CMZ", part1(&input));
+        assert_eq!("MCD", part2(&input));
+    }
+}
@@ -1,23 +1,76 @@
+//! Day 6: Tuning Trouble, finding markers of distinct characters in a datastream.
+
 use aoc_runner_derive::aoc;
 use itertools::Itertools;
 
-fn find_first_distinct(input: &str, n: usize) -> usize {
-    // once again we assume that the input is only ascii
-    input
-        .trim()
-        .as_bytes()
+use crate::common::input::normalize;
+use crate::common::ParseError;
+use crate::solution::Day;
+
+/// The number of characters up to and including the first `n` distinct ones in a row.
+pub fn find_first_distinct(input: &str, n: usize) -> usize {
+    let chars = input.trim().chars().collect_vec();
+    chars
         .windows(n)
         .position(|window| window.iter().all_unique())
         .map(|i| i + n)
         .unwrap()
 }
 
+/// The normalised datastream, there is nothing else to parse.
+pub fn parse(input: &str) -> Result<String, ParseError> {
+    let input = &normalize(input, true);
+    Ok(input.to_string())
+}
+
+pub fn solve_part1(input: &str) -> usize {
+    find_first_distinct(input, 4)
+}
+
+pub fn solve_part2(input: &str) -> usize {
+    find_first_distinct(input, 14)
+}
+
+#[doc(hidden)]
 #[aoc(day6, part1)]
 pub fn part1(input: &str) -> usize {
-    find_first_distinct(input, 4)
+    solve_part1(&normalize(input, true))
 }
 
+#[doc(hidden)]
 #[aoc(day6, part2)]
 pub fn part2(input: &str) -> usize {
-    find_first_distinct(input, 14)
+    solve_part2(&normalize(input, true))
+}
+
+pub(crate) static SOLUTION: Day<String> = Day {
+    day: 6,
+    parse,
+    part1: |input, _| Ok(solve_part1(input).into()),
+    part2: Some(|input, _| Ok(solve_part2(input).into())),
+};
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::common::input::windows_input;
+
+    #[test]
+    fn test_find_first_distinct() {
+        assert_eq!(5, part1("bvwbjplbgvbhsrlpgdmjqwftvncz\r\n"));
+        assert_eq!(23, part2("bvwbjplbgvbhsrlpgdmjqwftvncz\r\n"));
+        // counts characters, not bytes
+        assert_eq!(5, find_first_distinct("aäaäbc", 3));
+    }
+
+    #[test]
+    fn test_crlf() {
+        let input = parse(&windows_input(include_str!(
+            "../fixtures/day06/example1.txt"
+        )))
+        .unwrap();
+        assert_eq!(5, solve_part1(&input));
+    }
 }
@@ -1,7 +1,12 @@
-use std::str::FromStr;
+//! Day 7: No Space Left On Device, the file system tree recovered from a terminal session.
 
 use aoc_runner_derive::{aoc, aoc_generator};
 use itertools::Itertools;
+use thiserror::Error;
+
+use crate::common::input::normalize;
+use crate::common::{parse_located, ParseError};
+use crate::solution::Day;
 
 #[derive(Debug, Clone)]
 enum FileType {
@@ -9,6 +14,17 @@ enum FileType {
     Dir,
 }
 
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum AddChildError {
+    #[error("not a directory")]
+    NotADirectory,
+    #[error("no such directory '{0}'")]
+    NoSuchDirectory(String),
+    #[error("'{0}' already exists")]
+    AlreadyExists(String),
+}
+
+/// A file or a directory with its children.
 #[derive(Debug, Clone)]
 pub struct Node {
     name: String,
@@ -33,14 +49,19 @@ impl Node {
         }
     }
 
-    pub fn add_child<S: AsRef<str>>(&mut self, path: &[S], node: Node) -> Result<(), ()> {
+    /// Adds `node` to the directory at `path` below this one.
+    pub fn add_child<S: AsRef<str>>(
+        &mut self,
+        path: &[S],
+        node: Node,
+    ) -> Result<(), AddChildError> {
         if !self.is_dir() {
-            return Err(());
+            return Err(AddChildError::NotADirectory);
         }
 
         if path.is_empty() {
             if self.children.iter().any(|c| c.name == node.name) {
-                return Err(());
+                return Err(AddChildError::AlreadyExists(node.name));
             }
 
             self.children.push(node);
@@ -54,11 +75,20 @@ impl Node {
                 .exactly_one()
             {
                 Ok(child) => child.add_child(&path[1..], node),
-                Err(_) => Err(()),
+                Err(_) => Err(AddChildError::NoSuchDirectory(child_path.to_string())),
             }
         }
     }
 
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn children(&self) -> &[Node] {
+        &self.children
+    }
+
+    /// The size of a file, or the total size of all files below a directory.
     pub fn size(&self) -> usize {
         match self.file_type {
             FileType::File { size } => size,
@@ -69,114 +99,138 @@ impl Node {
     pub fn is_dir(&self) -> bool {
         matches!(self.file_type, FileType::Dir)
     }
+
+    /// This node and all directories below it, if it is a directory.
+    pub fn dirs(&self) -> impl Iterator<Item = &Node> {
+        let mut q = vec![self];
+        std::iter::from_fn(move || {
+            let n = q.pop()?;
+            q.extend(n.children.iter().filter(|c| c.is_dir()));
+            Some(n)
+        })
+        .filter(|n| n.is_dir())
+    }
 }
 
 #[derive(Debug, Clone)]
-struct CommandInfo {
+struct CommandInfo<'a> {
     args: Vec<String>,
-    output: Vec<String>,
+    output: Vec<&'a str>,
 }
 
 #[derive(Debug, Clone)]
-enum Command {
-    Cd(CommandInfo),
-    Ls(CommandInfo),
+enum Command<'a> {
+    Cd(CommandInfo<'a>),
+    Ls(CommandInfo<'a>),
 }
 
-#[aoc_generator(day7)]
-pub fn input_generator(input: &str) -> Node {
+/// Replays the `cd` and `ls` commands of the session into a tree below the root `/`.
+pub fn parse(input: &str) -> Result<Node, ParseError> {
+    let input = &normalize(input, true);
+    parse_terminal_output(input).map_err(|e| e.in_day(7))
+}
+
+fn parse_terminal_output(input: &str) -> Result<Node, ParseError> {
     let commands = input
         .lines()
         .map(|l| l.trim())
+        .filter(|l| !l.is_empty())
         .peekable()
         .batching(|it| match it.next() {
             Some(l1) => {
-                if !l1.starts_with('$') {
-                    panic!("expected command starting with '$' but got '{l1}'");
-                }
+                let Some(cmd_line) = l1.strip_prefix("$ ") else {
+                    return Some(Err(ParseError::new(format!(
+                        "expected command starting with '$' but got '{l1}'"
+                    ))
+                    .located_in(input, l1)));
+                };
 
-                let mut iter = l1[2..].split_whitespace();
-                let cmd = iter.next().unwrap();
+                let mut iter = cmd_line.split_whitespace();
+                let cmd = iter.next().unwrap_or_default();
                 let args = iter.map(|s| s.to_string()).collect_vec();
-                let output = it
-                    .peeking_take_while(|l| !l.starts_with('$'))
-                    .map(|l| l.to_string())
-                    .collect_vec();
+                let output = it.peeking_take_while(|l| !l.starts_with('$')).collect_vec();
 
                 Some(match cmd {
-                    "cd" => Command::Cd(CommandInfo { args, output }),
-                    "ls" => Command::Ls(CommandInfo { args, output }),
-                    _ => panic!("unknown command {cmd}"),
+                    "cd" => Ok((l1, Command::Cd(CommandInfo { args, output }))),
+                    "ls" => Ok((l1, Command::Ls(CommandInfo { args, output }))),
+                    _ => Err(ParseError::new(format!("unknown command '{cmd}'"))
+                        .located_in(input, cmd_line)),
                 })
             }
             None => None,
         })
-        .collect_vec();
+        .collect::<Result<Vec<_>, _>>()?;
 
-    let first_cmd = &commands[0];
+    let Some((first_line, first_cmd)) = commands.first() else {
+        return Err(ParseError::new("expected at least one command"));
+    };
     if !matches!(first_cmd, Command::Cd(CommandInfo {args, output:_}) if args.len() == 1 && args[0] == "/")
     {
-        panic!("expected 'cd /' in first command but got {first_cmd:?}")
+        return Err(ParseError::new(format!(
+            "expected 'cd /' in first command but got '{first_line}'"
+        ))
+        .located_in(input, first_line));
     }
 
     let mut root = Node::new_dir("");
     let mut current_path: Vec<&str> = vec![];
-    for cmd in &commands[1..] {
+    for (line, cmd) in &commands[1..] {
         match cmd {
             Command::Cd(CommandInfo { args, output: _ }) => {
                 if args.len() != 1 {
-                    panic!("expected ! arg for cd, got {args:?}");
+                    return Err(
+                        ParseError::new(format!("expected 1 arg for cd, got {args:?}"))
+                            .located_in(input, line),
+                    );
                 }
 
                 let arg = args[0].as_str();
                 match arg {
                     "/" => current_path.clear(),
                     ".." => {
-                        current_path.pop().unwrap();
+                        if current_path.pop().is_none() {
+                            return Err(ParseError::new("cannot cd above the root directory")
+                                .located_in(input, line));
+                        }
                     }
                     name if !name.starts_with('/') => current_path.push(name),
-                    _ => panic!("unknown arg for cd: '{arg}'"),
+                    _ => {
+                        return Err(ParseError::new(format!("unknown arg for cd: '{arg}'"))
+                            .located_in(input, line))
+                    }
                 };
             }
             Command::Ls(CommandInfo { args: _, output }) => {
-                for entry in output {
-                    let (prefix, name) = entry.split_once(' ').unwrap();
-                    match prefix {
-                        "dir" => root.add_child(&current_path, Node::new_dir(name)).unwrap(),
-                        size_str => {
-                            let size = usize::from_str(size_str).unwrap();
-                            root.add_child(&current_path, Node::new_file(name, size))
-                                .unwrap()
-                        }
-                    }
+                for &entry in output {
+                    let (prefix, name) = entry.split_once(' ').ok_or_else(|| {
+                        ParseError::new("expected '<size|dir> <name>'").located_in(input, entry)
+                    })?;
+                    let node = match prefix {
+                        "dir" => Node::new_dir(name),
+                        size_str => Node::new_file(name, parse_located(input, size_str)?),
+                    };
+                    root.add_child(&current_path, node).map_err(|e| {
+                        ParseError::new(format!(
+                            "cannot add '{name}' to the current directory: {e}"
+                        ))
+                        .located_in(input, entry)
+                    })?;
                 }
             }
         }
     }
 
-    root
+    Ok(root)
 }
 
-#[aoc(day7, part1)]
-pub fn part1(root: &Node) -> usize {
-    let mut sum = 0usize;
-    let mut q = vec![root];
-    while let Some(n) = q.pop() {
-        if n.is_dir() {
-            let size = n.size();
-            if size <= 100_000 {
-                sum += size;
-            }
-
-            n.children.iter().for_each(|c| q.push(c));
-        }
-    }
-
-    sum
+pub fn solve_part1(root: &Node) -> usize {
+    root.dirs()
+        .map(Node::size)
+        .filter(|&size| size <= 100_000)
+        .sum()
 }
 
-#[aoc(day7, part2)]
-pub fn part2(root: &Node) -> usize {
+pub fn solve_part2(root: &Node) -> usize {
     let capacity = 70000000usize;
     let required = 30000000usize;
     let used = root.size();
@@ -203,3 +257,45 @@ pub fn part2(root: &Node) -> usize {
 
     node_to_free
 }
+
+#[doc(hidden)]
+#[aoc_generator(day7)]
+pub fn input_generator(input: &str) -> Result<Node, ParseError> {
+    parse(input)
+}
+
+#[doc(hidden)]
+#[aoc(day7, part1)]
+pub fn part1(root: &Node) -> usize {
+    solve_part1(root)
+}
+
+#[doc(hidden)]
+#[aoc(day7, part2)]
+pub fn part2(root: &Node) -> usize {
+    solve_part2(root)
+}
+
+pub(crate) static SOLUTION: Day<Node> = Day {
+    day: 7,
+    parse,
+    part1: |input, _| Ok(solve_part1(input).into()),
+    part2: Some(|input, _| Ok(solve_part2(input).into())),
+};
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::common::input::windows_input;
+
+    #[test]
+    fn test_crlf() {
+        let input = parse(&windows_input(include_str!(
+            "../fixtures/day07/example.txt"
+        )))
+        .unwrap();
+        assert_eq!(95437, solve_part1(&input));
+    }
+}
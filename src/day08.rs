@@ -1,5 +1,12 @@
+//! Day 8: Treetop Tree House, the visibility and scenic score of trees in a grid.
+
 use aoc_runner_derive::{aoc, aoc_generator};
 
+use crate::common::input::normalize;
+use crate::common::{parse_lines_with, ParseError};
+use crate::solution::Day;
+
+/// The heights of the trees, row by row.
 #[derive(Debug, Clone)]
 pub struct GridForest {
     trees: Vec<Vec<u8>>,
@@ -22,6 +29,14 @@ impl GridForest {
         }
     }
 
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
     fn get_raw(&self, x: usize, y: usize) -> u8 {
         self.trees[y][x]
     }
@@ -34,6 +49,7 @@ impl GridForest {
         self.get_raw(x, y)
     }
 
+    /// Whether the tree can be seen from outside the grid.
     pub fn is_visible(&self, x: usize, y: usize) -> bool {
         let tree = self.get(x, y);
 
@@ -62,6 +78,7 @@ impl GridForest {
         false
     }
 
+    /// The product of the viewing distances in all four directions.
     pub fn scenic_score(&self, x: usize, y: usize) -> usize {
         let tree = self.get(x, y);
         let mut score = 1;
@@ -101,32 +118,41 @@ impl GridForest {
     }
 }
 
-#[aoc_generator(day8)]
-pub fn input_generator(input: &str) -> GridForest {
-    let trees: Vec<Vec<u8>> = input
-        .lines()
-        .map(|l| l.trim())
-        .filter(|l| !l.is_empty())
-        .map(|l| l.bytes().map(|b| b - b'0').collect())
-        .collect();
-    let len = trees[0].len();
-    if trees.iter().any(|l| l.len() != len) {
-        panic!("length mismatch");
+pub fn parse(input: &str) -> Result<GridForest, ParseError> {
+    let input = &normalize(input, true);
+    let mut len = None;
+    let trees: Vec<Vec<u8>> = parse_lines_with(input, |l| {
+        if *len.get_or_insert(l.len()) != l.len() {
+            return Err(ParseError::new("length mismatch"));
+        }
+
+        l.bytes()
+            .enumerate()
+            .map(|(i, b)| match b {
+                b'0'..=b'9' => Ok(b - b'0'),
+                _ => Err(
+                    ParseError::new(format!("invalid tree height '{}'", b as char))
+                        .at_column(i + 1),
+                ),
+            })
+            .collect()
+    })
+    .map_err(|e| e.in_day(8))?;
+    if trees.is_empty() {
+        return Err(ParseError::new("empty forest").in_day(8));
     }
 
-    GridForest::new(trees)
+    Ok(GridForest::new(trees))
 }
 
-#[aoc(day8, part1)]
-pub fn part1(forest: &GridForest) -> usize {
+pub fn solve_part1(forest: &GridForest) -> usize {
     (0..forest.width)
         .flat_map(|x| (0..forest.height).map(move |y| (x, y)))
         .filter(|(x, y)| forest.is_visible(*x, *y))
         .count()
 }
 
-#[aoc(day8, part2)]
-pub fn part2(forest: &GridForest) -> usize {
+pub fn solve_part2(forest: &GridForest) -> usize {
     (0..forest.width)
         .flat_map(|x| (0..forest.height).map(move |y| (x, y)))
         .map(|(x, y)| forest.scenic_score(x, y))
@@ -134,11 +160,37 @@ pub fn part2(forest: &GridForest) -> usize {
         .unwrap()
 }
 
+#[doc(hidden)]
+#[aoc_generator(day8)]
+pub fn input_generator(input: &str) -> Result<GridForest, ParseError> {
+    parse(input)
+}
+
+#[doc(hidden)]
+#[aoc(day8, part1)]
+pub fn part1(forest: &GridForest) -> usize {
+    solve_part1(forest)
+}
+
+#[doc(hidden)]
+#[aoc(day8, part2)]
+pub fn part2(forest: &GridForest) -> usize {
+    solve_part2(forest)
+}
+
+pub(crate) static SOLUTION: Day<GridForest> = Day {
+    day: 8,
+    parse,
+    part1: |input, _| Ok(solve_part1(input).into()),
+    part2: Some(|input, _| Ok(solve_part2(input).into())),
+};
+
 #[cfg(test)]
 mod tests {
     use pretty_assertions::assert_eq;
 
     use super::*;
+    use crate::common::input::windows_input;
 
     #[test]
     fn test_part2_1() {
@@ -150,7 +202,8 @@ mod tests {
 33549
 35390
 ",
-        );
+        )
+        .unwrap();
         assert_eq!(forest.scenic_score(2, 1), 4);
     }
 
@@ -164,7 +217,17 @@ mod tests {
 33549
 35390
 ",
-        );
+        )
+        .unwrap();
         assert_eq!(forest.scenic_score(2, 3), 8);
     }
+
+    #[test]
+    fn test_crlf() {
+        let input = parse(&windows_input(include_str!(
+            "../fixtures/day08/example.txt"
+        )))
+        .unwrap();
+        assert_eq!(21, solve_part1(&input));
+    }
 }
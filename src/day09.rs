@@ -1,95 +1,201 @@
-use std::str::FromStr;
+//! Day 9: Rope Bridge, the positions visited by the tail of a rope with any number of knots.
+
+use std::any::Any;
 
 use aoc_runner_derive::{aoc, aoc_generator};
-use rustc_hash::FxHashSet;
 
-type Num = i32;
+use crate::common::input::normalize;
+use crate::common::metric::linf_norm;
+use crate::common::{parse_lines_with, parse_located, Direction, ParseError, SparseGrid, Vec2i};
+use crate::solution::{parse_param_at_least, Day, ParamError, PuzzleParams};
+
+/// Parses the moves of the head, one direction and a number of steps per line.
+pub fn parse(input: &str) -> Result<Vec<(Direction, usize)>, ParseError> {
+    let input = &normalize(input, true);
+    parse_lines_with(input, |l| {
+        let (dir, amount) = l
+            .split_once(' ')
+            .ok_or_else(|| ParseError::new("expected '<dir> <amount>'"))?;
+        Ok((parse_located(l, dir)?, parse_located(l, amount)?))
+    })
+    .map_err(|e| e.in_day(9))
+}
 
-#[derive(Debug, Copy, Clone)]
-pub enum Dir {
-    Down,
-    Up,
-    Left,
-    Right,
+/// The knots of the rope, head first.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Rope {
+    knots: Vec<Vec2i>,
 }
 
-impl Dir {
-    fn get_vector(&self) -> (Num, Num) {
-        match self {
-            Dir::Down => (0, -1),
-            Dir::Up => (0, 1),
-            Dir::Left => (-1, 0),
-            Dir::Right => (1, 0),
+impl Rope {
+    /// A rope with all `knot_count` knots at the origin.
+    pub fn new(knot_count: usize) -> Self {
+        assert!(knot_count >= 1);
+        Self {
+            knots: vec![Vec2i::zeros(); knot_count],
         }
     }
 
-    fn move_vec(&self, (x, y): &(Num, Num)) -> (Num, Num) {
-        let (dx, dy) = self.get_vector();
-        (x + dx, y + dy)
+    pub fn knots(&self) -> &[Vec2i] {
+        &self.knots
     }
-}
 
-impl FromStr for Dir {
-    type Err = ();
+    pub fn tail(&self) -> Vec2i {
+        self.knots[self.knots.len() - 1]
+    }
 
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        match s {
-            "D" => Ok(Dir::Down),
-            "U" => Ok(Dir::Up),
-            "L" => Ok(Dir::Left),
-            "R" => Ok(Dir::Right),
-            _ => Err(()),
+    /// Moves the head one step in `dir` and lets the other knots follow.
+    pub fn step(&mut self, dir: Direction) {
+        let knots = &mut self.knots;
+        knots[0] = dir.offset(&knots[0]);
+        for i in 1..knots.len() {
+            let diff = knots[i - 1] - knots[i];
+            if linf_norm(&diff) > 1 {
+                knots[i] += diff.map(i64::signum);
+            } else {
+                break;
+            }
         }
     }
 }
 
-#[aoc_generator(day9)]
-pub fn input_generator(input: &str) -> Vec<(Dir, usize)> {
-    input
-        .lines()
-        .map(|l| l.trim())
-        .filter(|l| !l.is_empty())
-        .map(|l| {
-            let (dir, amount) = l.split_once(' ').unwrap();
-            (dir.parse().unwrap(), amount.parse().unwrap())
+/// The rope after each single step of the head.
+pub fn simulate(
+    moves: &[(Direction, usize)],
+    knot_count: usize,
+) -> impl Iterator<Item = Rope> + '_ {
+    let mut rope = Rope::new(knot_count);
+    moves
+        .iter()
+        .flat_map(|&(dir, amount)| std::iter::repeat_n(dir, amount))
+        .map(move |dir| {
+            rope.step(dir);
+            rope.clone()
         })
-        .collect()
 }
 
-fn follow_head(knots: &mut [(Num, Num)]) {
-    for i in 1..knots.len() {
-        let (hx, hy) = &knots[i - 1];
-        let (x, y) = &knots[i];
-        if hx.abs_diff(*x) > 1 || hy.abs_diff(*y) > 1 {
-            knots[i] = (x + (hx - x).signum(), y + (hy - y).signum());
-        } else {
-            break;
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Params {
+    pub part1_knots: usize,
+    pub part2_knots: usize,
+}
+
+impl Default for Params {
+    fn default() -> Self {
+        Self {
+            part1_knots: 2,
+            part2_knots: 10,
         }
     }
 }
 
-fn simulate_rope<const KNOTS: usize>(moves: &[(Dir, usize)]) -> usize {
-    assert!(KNOTS >= 1);
-    let mut knots = [(0, 0); KNOTS];
-    let mut all_tail_pos = FxHashSet::default();
-    all_tail_pos.insert(knots[KNOTS - 1]);
-    for (dir, amount) in moves {
-        for _ in 0..*amount {
-            knots[0] = dir.move_vec(&knots[0]);
-            follow_head(&mut knots);
-            all_tail_pos.insert(knots[KNOTS - 1]);
+impl PuzzleParams for Params {
+    fn set(&mut self, key: &str, value: &str) -> Result<(), ParamError> {
+        match key {
+            "part1_knots" => self.part1_knots = parse_param_at_least(key, value, 1)?,
+            "part2_knots" => self.part2_knots = parse_param_at_least(key, value, 1)?,
+            _ => return Err(ParamError::Unknown(key.to_string())),
         }
+        Ok(())
     }
 
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+/// The number of positions the last of `knot_count` knots visits while the head moves.
+pub fn simulate_rope(moves: &[(Direction, usize)], knot_count: usize) -> usize {
+    let all_tail_pos: SparseGrid<()> = std::iter::once(Vec2i::zeros())
+        .chain(simulate(moves, knot_count).map(|rope| rope.tail()))
+        .map(|pos| (pos, ()))
+        .collect();
     all_tail_pos.len()
 }
 
+pub fn part1_with(input: &[(Direction, usize)], params: &Params) -> usize {
+    simulate_rope(input, params.part1_knots)
+}
+
+pub fn part2_with(input: &[(Direction, usize)], params: &Params) -> usize {
+    simulate_rope(input, params.part2_knots)
+}
+
+pub fn solve_part1(input: &[(Direction, usize)]) -> usize {
+    part1_with(input, &Params::default())
+}
+
+pub fn solve_part2(input: &[(Direction, usize)]) -> usize {
+    part2_with(input, &Params::default())
+}
+
+#[doc(hidden)]
+#[aoc_generator(day9)]
+pub fn input_generator(input: &str) -> Result<Vec<(Direction, usize)>, ParseError> {
+    parse(input)
+}
+
+#[doc(hidden)]
 #[aoc(day9, part1)]
-pub fn part1(input: &[(Dir, usize)]) -> usize {
-    simulate_rope::<2>(input)
+pub fn part1(input: &[(Direction, usize)]) -> usize {
+    solve_part1(input)
 }
 
+#[doc(hidden)]
 #[aoc(day9, part2)]
-pub fn part2(input: &[(Dir, usize)]) -> usize {
-    simulate_rope::<10>(input)
+pub fn part2(input: &[(Direction, usize)]) -> usize {
+    solve_part2(input)
+}
+
+pub(crate) static SOLUTION: Day<Vec<(Direction, usize)>, Params> = Day {
+    day: 9,
+    parse,
+    part1: |input, params| Ok(part1_with(input, params).into()),
+    part2: Some(|input, params| Ok(part2_with(input, params).into())),
+};
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::common::input::windows_input;
+
+    #[test]
+    fn test_simulate() {
+        let moves = input_generator("R 4\nU 2\n").unwrap();
+        let ropes = simulate(&moves, 3).collect::<Vec<_>>();
+        assert_eq!(6, ropes.len());
+        assert_eq!(
+            [Vec2i::new(4, 0), Vec2i::new(3, 0), Vec2i::new(2, 0)],
+            ropes[3].knots()
+        );
+        assert_eq!(
+            [Vec2i::new(4, -2), Vec2i::new(4, -1), Vec2i::new(3, -1)],
+            ropes[5].knots()
+        );
+        assert_eq!(4, simulate_rope(&moves, 3));
+    }
+
+    #[test]
+    fn test_params() {
+        let mut params = Params::default();
+        params.set("part1_knots", "1").unwrap();
+        assert_eq!(1, params.part1_knots);
+        assert!(matches!(
+            params.set("part1_knots", "0"),
+            Err(ParamError::InvalidValue { .. })
+        ));
+        assert!(params.set("part2_knots", "0").is_err());
+        assert_eq!(10, params.part2_knots);
+    }
+
+    #[test]
+    fn test_crlf() {
+        let input = parse(&windows_input(include_str!(
+            "../fixtures/day09/example.txt"
+        )))
+        .unwrap();
+        assert_eq!(13, solve_part1(&input));
+    }
 }
@@ -1,14 +1,23 @@
+//! Day 10: Cathode-Ray Tube, a CPU with a single register that drives a CRT.
+
 use std::str::FromStr;
 
 use aoc_runner_derive::{aoc, aoc_generator};
 
-#[derive(Debug, Clone)]
+use crate::answer::Answer;
+use crate::common::input::normalize;
+use crate::common::{parse_lines_with, parse_located, ParseError};
+use crate::solution::Day;
+use crate::visualize::{notify, Frame, Observer, Palette, Visualize};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum Instruction {
     Noop,
     Addx(i32),
 }
 
 impl Instruction {
+    /// The number of cycles the instruction takes to complete.
     pub fn cycles(&self) -> u32 {
         match self {
             Instruction::Noop => 1,
@@ -18,21 +27,36 @@ impl Instruction {
 }
 
 impl FromStr for Instruction {
-    type Err = ();
+    type Err = ParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         if s == "noop" {
             return Ok(Instruction::Noop);
         }
 
-        if let Some(s) = s.strip_prefix("addx ") {
-            return Ok(Instruction::Addx(s.parse().map_err(|_| ())?));
+        if let Some(n) = s.strip_prefix("addx ") {
+            return Ok(Instruction::Addx(parse_located(s, n)?));
         }
 
-        Err(())
+        Err(ParseError::new(format!("unknown instruction '{s}'")))
     }
 }
 
+/// The value of the register during a cycle, which starts at 1.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct Tick {
+    pub cycle: u32,
+    pub register_x: i32,
+}
+
+impl Tick {
+    /// The cycle multiplied by `register_x`.
+    pub fn signal_strength(&self) -> i32 {
+        self.cycle as i32 * self.register_x
+    }
+}
+
+/// Runs a program one cycle at a time, see [`Cpu::tick`].
 #[derive(Debug, Clone)]
 pub struct Cpu {
     pub register_x: i32,
@@ -53,15 +77,18 @@ impl Cpu {
         }
     }
 
+    /// Whether the program hasn't finished yet.
     pub fn has_instruction(&self) -> bool {
         (self.pc as usize) < self.instructions.len()
     }
 
+    /// Starts the next cycle, `register_x` keeps its value during the cycle.
     pub fn pre_cycle(&mut self) {
         self.cycle += 1;
         self.cycles_with_current_instruction += 1;
     }
 
+    /// Ends the cycle, which completes the current instruction after its last cycle.
     pub fn post_cycle(&mut self) {
         if self.cycles_with_current_instruction >= self.instructions[self.pc as usize].cycles() {
             self.execute_current_instruction();
@@ -77,56 +104,165 @@ impl Cpu {
         }
     }
 
+    /// The current cycle multiplied by `register_x`.
     pub fn signal_strength(&self) -> i32 {
         self.cycle as i32 * self.register_x
     }
+
+    /// Runs the next cycle and returns the register during it, `None` once the program has
+    /// finished.
+    pub fn tick(&mut self) -> Option<Tick> {
+        if !self.has_instruction() {
+            return None;
+        }
+
+        self.pre_cycle();
+        let tick = Tick {
+            cycle: self.cycle,
+            register_x: self.register_x,
+        };
+        self.post_cycle();
+        Some(tick)
+    }
+
+    /// The remaining cycles of the program.
+    pub fn ticks(mut self) -> impl Iterator<Item = Tick> {
+        std::iter::from_fn(move || self.tick())
+    }
 }
 
-#[aoc_generator(day10)]
-pub fn input_generator(input: &str) -> Cpu {
-    Cpu::new(
-        input
-            .lines()
-            .map(|l| l.trim())
-            .filter(|l| !l.is_empty())
-            .map(|l| l.parse().unwrap())
-            .collect(),
-    )
+pub fn parse(input: &str) -> Result<Cpu, ParseError> {
+    let input = &normalize(input, true);
+    Ok(Cpu::new(
+        parse_lines_with(input, str::parse).map_err(|e| e.in_day(10))?,
+    ))
 }
 
-#[aoc(day10, part1)]
-pub fn part1(cpu: &Cpu) -> i32 {
-    let mut cpu = cpu.clone();
-    let mut sum: i32 = 0;
-    while cpu.has_instruction() {
-        cpu.pre_cycle();
-        if (20..=220).contains(&cpu.cycle) && (cpu.cycle - 20) % 40 == 0 {
-            sum += cpu.signal_strength();
+pub fn solve_part1(cpu: &Cpu) -> i32 {
+    cpu.clone()
+        .ticks()
+        .filter(|tick| (20..=220).contains(&tick.cycle) && (tick.cycle - 20).is_multiple_of(40))
+        .map(|tick| tick.signal_strength())
+        .sum()
+}
+
+/// The CRT while it is being drawn, with the sprite in the row below the screen.
+struct Crt<'a> {
+    pixels: &'a [bool],
+    register_x: i32,
+}
+
+const PALETTE: Palette = Palette(&[
+    ('#', [255, 204, 0]),
+    ('.', [32, 32, 32]),
+    ('=', [0, 160, 255]),
+]);
+
+impl Visualize for Crt<'_> {
+    fn frame(&self) -> Frame {
+        let mut frame = Frame::new(40, 7, '.', PALETTE);
+        for (i, _) in self.pixels.iter().enumerate().filter(|(_, lit)| **lit) {
+            frame.set(i % 40, i / 40, '#');
+        }
+        for x in (self.register_x - 1..=self.register_x + 1).filter(|x| *x >= 0) {
+            frame.set(x as usize, 6, '=');
         }
 
-        cpu.post_cycle();
+        frame
     }
-
-    sum
 }
 
-#[aoc(day10, part2)]
-pub fn part2(cpu: &Cpu) -> String {
+/// Runs the program and returns which pixels of the CRT are lit.
+pub fn draw(cpu: &Cpu, mut observer: Option<&mut dyn Observer>) -> Vec<bool> {
     let mut cpu = cpu.clone();
+    let mut pixels = vec![];
+    while let Some(tick) = cpu.tick() {
+        let pixel = (tick.cycle as i32 - 1) % 40;
+        pixels.push(tick.register_x.abs_diff(pixel) <= 1);
+        notify(
+            &mut observer,
+            &Crt {
+                pixels: &pixels,
+                register_x: cpu.register_x,
+            },
+        );
+    }
+
+    pixels
+}
+
+pub fn solve_part2(cpu: &Cpu) -> String {
     let mut display = '\n'.to_string();
-    while cpu.has_instruction() {
-        cpu.pre_cycle();
-        let pixel = (cpu.cycle as i32 - 1) % 40;
-        display.push(if cpu.register_x.abs_diff(pixel) <= 1 {
-            '\u{2588}'
-        } else {
-            ' '
-        });
-        if pixel == 39 {
+    for (i, lit) in draw(cpu, None).into_iter().enumerate() {
+        display.push(if lit { '\u{2588}' } else { ' ' });
+        if i % 40 == 39 {
             display.push('\n');
         }
-        cpu.post_cycle();
     }
 
     display
 }
+
+/// Draws the CRT cycle by cycle, both parts run the same program.
+pub fn visualize(cpu: &Cpu, _part: u32, observer: &mut dyn Observer) {
+    draw(cpu, Some(observer));
+}
+
+#[doc(hidden)]
+#[aoc_generator(day10)]
+pub fn input_generator(input: &str) -> Result<Cpu, ParseError> {
+    parse(input)
+}
+
+#[doc(hidden)]
+#[aoc(day10, part1)]
+pub fn part1(cpu: &Cpu) -> i32 {
+    solve_part1(cpu)
+}
+
+#[doc(hidden)]
+#[aoc(day10, part2)]
+pub fn part2(cpu: &Cpu) -> String {
+    solve_part2(cpu)
+}
+
+pub(crate) static SOLUTION: Day<Cpu> = Day {
+    day: 10,
+    parse,
+    part1: |input, _| Ok(solve_part1(input).into()),
+    part2: Some(|input, _| Ok(Answer::art(&solve_part2(input)))),
+};
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::common::input::windows_input;
+
+    #[test]
+    fn test_ticks() {
+        let mut cpu = input_generator("noop\naddx 3\naddx -5\n").unwrap();
+        assert_eq!(
+            vec![(1, 1), (2, 1), (3, 1), (4, 4), (5, 4)],
+            cpu.clone()
+                .ticks()
+                .map(|tick| (tick.cycle, tick.register_x))
+                .collect::<Vec<_>>()
+        );
+
+        // stop after the first addx
+        while cpu.tick().is_some_and(|tick| tick.cycle < 3) {}
+        assert_eq!(4, cpu.register_x);
+        assert_eq!(Some(16), cpu.tick().map(|tick| tick.signal_strength()));
+    }
+
+    #[test]
+    fn test_crlf() {
+        let input = parse(&windows_input(include_str!(
+            "../fixtures/day10/example.txt"
+        )))
+        .unwrap();
+        assert_eq!(13140, solve_part1(&input));
+    }
+}
@@ -1,13 +1,22 @@
+//! Day 11: Monkey in the Middle, monkeys throwing items around based on their worry level.
+
+use std::any::Any;
 use std::cmp::Reverse;
 use std::collections::VecDeque;
-use std::str::FromStr;
 
 use aoc_runner_derive::{aoc, aoc_generator};
 use itertools::Itertools;
-use regex::Regex;
 
-#[derive(Debug, Clone)]
-enum Operation {
+use crate::common::input::normalize;
+use crate::common::parser::{parse_all, Parser};
+use crate::common::ParseError;
+use crate::solution::{
+    parse_param, parse_param_at_least, Day, ParamError, PuzzleParams, SolveError,
+};
+
+/// How a monkey changes the worry level of an item it inspects.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Operation {
     Add(u64),
     Sub(u64),
     Mul(u64),
@@ -17,7 +26,7 @@ enum Operation {
 }
 
 impl Operation {
-    fn apply(&self, n: &u64) -> u64 {
+    pub fn apply(&self, n: &u64) -> u64 {
         match self {
             Operation::Add(m) => n + m,
             Operation::Sub(m) => n - m,
@@ -27,32 +36,50 @@ impl Operation {
             Operation::Square => n * n,
         }
     }
-}
 
-impl FromStr for Operation {
-    type Err = ();
+    /// Like [`Operation::apply`], but `None` on overflow, underflow or division by zero.
+    pub fn checked_apply(&self, n: &u64) -> Option<u64> {
+        match self {
+            Operation::Add(m) => n.checked_add(*m),
+            Operation::Sub(m) => n.checked_sub(*m),
+            Operation::Mul(m) => n.checked_mul(*m),
+            Operation::Div(m) => n.checked_div(*m),
+            Operation::Mod(m) => n.checked_rem(*m),
+            Operation::Square => n.checked_mul(*n),
+        }
+    }
 
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        if s == "old * old" {
-            Ok(Self::Square)
-        } else if let Some(n) = s.strip_prefix("old + ") {
-            Ok(Self::Add(n.parse().map_err(|_| ())?))
-        } else if let Some(n) = s.strip_prefix("old - ") {
-            Ok(Self::Sub(n.parse().map_err(|_| ())?))
-        } else if let Some(n) = s.strip_prefix("old * ") {
-            Ok(Self::Mul(n.parse().map_err(|_| ())?))
-        } else if let Some(n) = s.strip_prefix("old / ") {
-            Ok(Self::Div(n.parse().map_err(|_| ())?))
-        } else if let Some(n) = s.strip_prefix("old % ") {
-            Ok(Self::Mod(n.parse().map_err(|_| ())?))
-        } else {
-            Err(())
+    /// Whether the result modulo any number only depends on `n` modulo that number.
+    fn is_modular(&self) -> bool {
+        matches!(
+            self,
+            Operation::Add(_) | Operation::Mul(_) | Operation::Square
+        )
+    }
+}
+
+impl Operation {
+    /// Parses the right hand side of `new = old <op> <n>` or `new = old * old`.
+    pub fn parse(p: &mut Parser) -> Result<Self, ParseError> {
+        p.keyword("old")?;
+        let op = p.one_of(&[("+", '+'), ("-", '-'), ("*", '*'), ("/", '/'), ("%", '%')])?;
+        if op == '*' && p.try_keyword("old") {
+            return Ok(Self::Square);
         }
+
+        let n = p.integer()?;
+        Ok(match op {
+            '+' => Self::Add(n),
+            '-' => Self::Sub(n),
+            '*' => Self::Mul(n),
+            '/' => Self::Div(n),
+            _ => Self::Mod(n),
+        })
     }
 }
 
 #[derive(Debug, Clone)]
-struct Monkey {
+pub struct Monkey {
     items: VecDeque<u64>,
     operation: Operation,
     divisibility_test: u64,
@@ -61,21 +88,60 @@ struct Monkey {
     inspections: u64,
 }
 
+impl Monkey {
+    /// The worry levels of the items the monkey holds, in the order it inspects them.
+    pub fn items(&self) -> &VecDeque<u64> {
+        &self.items
+    }
+
+    pub fn operation(&self) -> &Operation {
+        &self.operation
+    }
+
+    /// The number of items the monkey inspected so far.
+    pub fn inspections(&self) -> u64 {
+        self.inspections
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct Monkeys {
     monkeys: Vec<Monkey>,
 }
 
 impl Monkeys {
-    pub fn do_rounds<const ROUNDS: usize, const DIVISOR: u64>(&mut self) -> u64 {
+    pub fn monkeys(&self) -> &[Monkey] {
+        &self.monkeys
+    }
+
+    /// Plays `rounds` rounds, dividing the worry level by `divisor` after each inspection, and
+    /// returns the monkey business, the product of the two highest inspection counts.
+    ///
+    /// Fails if the worry levels get too large for a `u64`.
+    pub fn do_rounds(&mut self, rounds: usize, divisor: u64) -> Result<u64, SolveError> {
+        // Without division, only the worry levels modulo the divisibility tests matter if the
+        // operations allow it, otherwise they're kept exact.
         // could use lcm here, but all the divisibility_test numbers are prime
-        let modulus: u64 = self.monkeys.iter().map(|m| m.divisibility_test).product();
-        for _ in 0..ROUNDS {
+        let modulus = if divisor == 1 && self.monkeys.iter().all(|m| m.operation.is_modular()) {
+            self.monkeys
+                .iter()
+                .try_fold(1u64, |acc, m| acc.checked_mul(m.divisibility_test))
+        } else {
+            None
+        };
+        for round in 0..rounds {
             for i in 0..self.monkeys.len() {
                 while let Some(item) = self.monkeys[i].items.pop_front() {
                     self.monkeys[i].inspections += 1;
-                    let item = (self.monkeys[i].operation.apply(&item) / DIVISOR) % modulus;
-                    let target = if item % self.monkeys[i].divisibility_test == 0 {
+                    let item = self.monkeys[i]
+                        .operation
+                        .checked_apply(&item)
+                        .ok_or_else(|| {
+                            SolveError::new(format!("worry levels overflow in round {}", round + 1))
+                        })?
+                        / divisor;
+                    let item = modulus.map_or(item, |modulus| item % modulus);
+                    let target = if item.is_multiple_of(self.monkeys[i].divisibility_test) {
                         self.monkeys[i].true_target
                     } else {
                         self.monkeys[i].false_target
@@ -85,65 +151,240 @@ impl Monkeys {
             }
         }
 
-        self.monkeys
+        let business = self
+            .monkeys
             .iter()
             .map(|m| m.inspections)
             .sorted_by_key(|&n| Reverse(n))
             .take(2)
-            .product()
+            .product();
+        Ok(business)
     }
 }
 
-#[aoc_generator(day11)]
-pub fn input_generator(input: &str) -> Monkeys {
-    let re = Regex::new(
-        r"^Monkey (\d+):
-  Starting items: (\d+(?:, \d+)*)
-  Operation: new = ([old +*0-9]+)
-  Test: divisible by (\d+)
-    If true: throw to monkey (\d+)
-    If false: throw to monkey (\d+)$",
-    )
-    .unwrap();
-    Monkeys {
-        monkeys: input
-            .trim()
-            .split("\n\n")
-            .map(|s| {
-                let caps = re.captures(s).unwrap();
-                // let index: usize = caps.get(1).unwrap().as_str().parse().unwrap();
-                let items: VecDeque<u64> = caps
-                    .get(2)
-                    .unwrap()
-                    .as_str()
-                    .split(", ")
-                    .map(|n| n.parse().unwrap())
-                    .collect();
-                let operation: Operation = caps.get(3).unwrap().as_str().parse().unwrap();
-                let divisibility_test: u64 = caps.get(4).unwrap().as_str().parse().unwrap();
-                let true_target: usize = caps.get(5).unwrap().as_str().parse().unwrap();
-                let false_target: usize = caps.get(6).unwrap().as_str().parse().unwrap();
-                Monkey {
-                    items,
-                    operation,
-                    divisibility_test,
-                    true_target,
-                    false_target,
-                    inspections: 0,
-                }
+pub fn parse(input: &str) -> Result<Monkeys, ParseError> {
+    let input = &normalize(input, true);
+    let monkeys = parse_all(input, |p| {
+        p.until_end(|p| {
+            p.keyword("Monkey")?;
+            let _index: usize = p.integer()?;
+            p.keyword(":")?;
+            p.keyword("Starting items:")?;
+            let items = p.separated(",", |p| p.integer())?.into();
+            p.keyword("Operation: new =")?;
+            let operation = Operation::parse(p)?;
+            p.keyword("Test: divisible by")?;
+            p.skip_whitespace();
+            let start = *p;
+            let divisibility_test = p.integer()?;
+            if divisibility_test == 0 {
+                return Err(start.error("expected a non-zero divisor"));
+            }
+            p.keyword("If true: throw to monkey")?;
+            let true_target = p.integer()?;
+            p.keyword("If false: throw to monkey")?;
+            let false_target = p.integer()?;
+            Ok(Monkey {
+                items,
+                operation,
+                divisibility_test,
+                true_target,
+                false_target,
+                inspections: 0,
             })
-            .collect(),
+        })
+    })
+    .map_err(|e| e.in_day(11))?;
+
+    if monkeys.is_empty() {
+        return Err(ParseError::new("expected at least one monkey").in_day(11));
+    }
+    if let Some(i) = monkeys
+        .iter()
+        .flat_map(|m| [m.true_target, m.false_target])
+        .find(|&i| i >= monkeys.len())
+    {
+        return Err(ParseError::new(format!("throw to non-existing monkey {i}")).in_day(11));
     }
+
+    Ok(Monkeys { monkeys })
 }
 
-#[aoc(day11, part1)]
-pub fn part1(monkeys: &Monkeys) -> u64 {
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Params {
+    pub part1_rounds: usize,
+    pub part1_divisor: u64,
+    pub part2_rounds: usize,
+    pub part2_divisor: u64,
+}
+
+impl Default for Params {
+    fn default() -> Self {
+        Self {
+            part1_rounds: 20,
+            part1_divisor: 3,
+            part2_rounds: 10000,
+            part2_divisor: 1,
+        }
+    }
+}
+
+impl PuzzleParams for Params {
+    fn set(&mut self, key: &str, value: &str) -> Result<(), ParamError> {
+        match key {
+            "part1_rounds" => self.part1_rounds = parse_param(key, value)?,
+            "part1_divisor" => self.part1_divisor = parse_param_at_least(key, value, 1)?,
+            "part2_rounds" => self.part2_rounds = parse_param(key, value)?,
+            "part2_divisor" => self.part2_divisor = parse_param_at_least(key, value, 1)?,
+            _ => return Err(ParamError::Unknown(key.to_string())),
+        }
+        Ok(())
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+pub fn part1_with(monkeys: &Monkeys, params: &Params) -> Result<u64, SolveError> {
     let mut monkeys = monkeys.clone();
-    monkeys.do_rounds::<20, 3>()
+    monkeys.do_rounds(params.part1_rounds, params.part1_divisor)
 }
 
-#[aoc(day11, part2)]
-pub fn part2(monkeys: &Monkeys) -> u64 {
+pub fn part2_with(monkeys: &Monkeys, params: &Params) -> Result<u64, SolveError> {
     let mut monkeys = monkeys.clone();
-    monkeys.do_rounds::<10000, 1>()
+    monkeys.do_rounds(params.part2_rounds, params.part2_divisor)
+}
+
+pub fn solve_part1(monkeys: &Monkeys) -> Result<u64, SolveError> {
+    part1_with(monkeys, &Params::default())
+}
+
+pub fn solve_part2(monkeys: &Monkeys) -> Result<u64, SolveError> {
+    part2_with(monkeys, &Params::default())
+}
+
+#[doc(hidden)]
+#[aoc_generator(day11)]
+pub fn input_generator(input: &str) -> Result<Monkeys, ParseError> {
+    parse(input)
+}
+
+#[doc(hidden)]
+#[aoc(day11, part1)]
+pub fn part1(monkeys: &Monkeys) -> Result<u64, SolveError> {
+    solve_part1(monkeys)
+}
+
+#[doc(hidden)]
+#[aoc(day11, part2)]
+pub fn part2(monkeys: &Monkeys) -> Result<u64, SolveError> {
+    solve_part2(monkeys)
+}
+
+pub(crate) static SOLUTION: Day<Monkeys, Params> = Day {
+    day: 11,
+    parse,
+    part1: |input, params| Ok(part1_with(input, params)?.into()),
+    part2: Some(|input, params| Ok(part2_with(input, params)?.into())),
+};
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::common::input::windows_input;
+
+    const INPUT: &str = r"Monkey 0:
+  Starting items: 79, 98
+  Operation: new = old * 19
+  Test: divisible by 23
+    If true: throw to monkey 2
+    If false: throw to monkey 3
+
+Monkey 1:
+  Starting items: 54, 65, 75, 74
+  Operation: new = old + 6
+  Test: divisible by 19
+    If true: throw to monkey 2
+    If false: throw to monkey 0
+
+Monkey 2:
+  Starting items: 79, 60, 97
+  Operation: new = old * old
+  Test: divisible by 13
+    If true: throw to monkey 1
+    If false: throw to monkey 3
+
+Monkey 3:
+  Starting items: 74
+  Operation: new = old + 3
+  Test: divisible by 17
+    If true: throw to monkey 0
+    If false: throw to monkey 1
+";
+
+    #[test]
+    fn test_1() {
+        assert_eq!(Ok(10605), part1(&input_generator(INPUT).unwrap()));
+    }
+
+    #[test]
+    fn test_2() {
+        assert_eq!(Ok(2713310158), part2(&input_generator(INPUT).unwrap()));
+    }
+
+    #[test]
+    fn test_whitespace() {
+        let squashed = INPUT
+            .replace("\n\n", "\n")
+            .replace("    ", "\t")
+            .replace(", ", ",");
+        assert_eq!(Ok(10605), part1(&input_generator(&squashed).unwrap()));
+
+        let e = input_generator(&INPUT.replace("old + 6", "old ^ 6")).unwrap_err();
+        assert_eq!((11, 10, 24), (e.day, e.line, e.column));
+    }
+
+    #[test]
+    fn test_params() {
+        let mut params = Params::default();
+        params.set("part2_divisor", "2").unwrap();
+        assert_eq!(2, params.part2_divisor);
+        assert!(matches!(
+            params.set("part1_divisor", "0"),
+            Err(ParamError::InvalidValue { .. })
+        ));
+        assert!(params.set("part2_divisor", "0").is_err());
+        assert_eq!(2, params.part2_divisor);
+    }
+
+    #[test]
+    fn test_exact_worry_levels() {
+        let monkeys = parse(INPUT).unwrap();
+        let mut params = Params::default();
+        params.set("part1_rounds", "40").unwrap();
+        assert_eq!(Ok(43870), part1_with(&monkeys, &params));
+        params.set("part1_rounds", "80").unwrap();
+        assert_eq!(Ok(184450), part1_with(&monkeys, &params));
+
+        params.set("part2_divisor", "2").unwrap();
+        assert!(part2_with(&monkeys, &params).is_err());
+    }
+
+    #[test]
+    fn test_zero_divisibility_test() {
+        let e = parse(&INPUT.replace("divisible by 19", "divisible by 0")).unwrap_err();
+        assert_eq!((11, 11, 22), (e.day, e.line, e.column));
+    }
+
+    #[test]
+    fn test_crlf() {
+        let input = parse(&windows_input(include_str!(
+            "../fixtures/day11/example.txt"
+        )))
+        .unwrap();
+        assert_eq!(Ok(10605), solve_part1(&input));
+    }
 }
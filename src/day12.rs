@@ -1,138 +1,147 @@
-use std::collections::VecDeque;
+//! Day 12: Hill Climbing Algorithm, the shortest paths up a heightmap.
 
 use aoc_runner_derive::{aoc, aoc_generator};
 use itertools::Itertools;
-use tinyvec::array_vec;
 
+use crate::common::input::normalize;
+use crate::common::{Grid, ParseError, Vec2i};
+use crate::solution::Day;
+
+/// The heights `a` to `z` of the area, with the start and end position of the climb.
 #[derive(Debug, Clone)]
-pub struct Grid {
-    grid: Vec<u8>,
-    width: usize,
-    height: usize,
-    start: usize,
-    end: usize,
+pub struct Heightmap {
+    grid: Grid<u8>,
+    start: Vec2i,
+    end: Vec2i,
 }
 
-impl Grid {
-    pub fn new(mut trees: Vec<u8>, width: usize) -> Grid {
-        let height = trees.len() / width;
-        if trees.len() != width * height {
-            panic!()
+impl Heightmap {
+    /// Parses the rows of heights in `trees`, where `S` is the start at height `a` and `E` the
+    /// end at height `z`.
+    pub fn new(mut trees: Vec<u8>, width: usize) -> Result<Heightmap, ParseError> {
+        if width == 0 || !trees.len().is_multiple_of(width) {
+            return Err(ParseError::new("non rectangular grid"));
         }
+        let height = trees.len() / width;
+        let to_pos = |i: usize| Vec2i::new((i % width) as i64, (i / width) as i64);
 
-        let (start, _) = trees.iter().find_position(|&&n| n == b'S').unwrap();
+        let (start, _) = trees
+            .iter()
+            .find_position(|&&n| n == b'S')
+            .ok_or_else(|| ParseError::new("missing start 'S'"))?;
         trees[start] = b'a';
 
-        let (end, _) = trees.iter().find_position(|&&n| n == b'E').unwrap();
+        let (end, _) = trees
+            .iter()
+            .find_position(|&&n| n == b'E')
+            .ok_or_else(|| ParseError::new("missing end 'E'"))?;
         trees[end] = b'z';
 
-        Grid {
-            grid: trees,
-            width,
-            height,
-            start,
-            end,
+        if let Some(i) = trees.iter().position(|n| !n.is_ascii_lowercase()) {
+            return Err(
+                ParseError::new(format!("invalid height '{}'", trees[i] as char))
+                    .at_column(i % width + 1)
+                    .at_line(i / width + 1),
+            );
         }
+
+        Ok(Heightmap {
+            grid: Grid::new(width, height, trees),
+            start: to_pos(start),
+            end: to_pos(end),
+        })
     }
 
-    fn to_idx(&self, x: usize, y: usize) -> usize {
-        x + y * self.width
+    pub fn grid(&self) -> &Grid<u8> {
+        &self.grid
     }
 
-    fn to_coords(&self, idx: usize) -> (usize, usize) {
-        (idx % self.width, idx / self.width)
+    pub fn start(&self) -> Vec2i {
+        self.start
     }
 
-    fn bfs_impl(&self, source: usize, can_step_to: impl Fn(u8, u8) -> bool) -> Vec<usize> {
-        let mut dist = vec![usize::MAX; self.grid.len()];
-        dist[source] = 0;
-
-        let mut q = VecDeque::new();
-        q.push_back(source);
-
-        while let Some(n) = q.pop_front() {
-            let value = self.grid[n];
-
-            let mut neighbors = array_vec!([usize; 4]);
-            let (x, y) = self.to_coords(n);
-            if x > 0 {
-                let idx = self.to_idx(x - 1, y);
-                if can_step_to(value, self.grid[idx]) {
-                    neighbors.push(idx);
-                }
-            }
-            if x + 1 < self.width {
-                let idx = self.to_idx(x + 1, y);
-                if can_step_to(value, self.grid[idx]) {
-                    neighbors.push(idx);
-                }
-            }
-            if y > 0 {
-                let idx = self.to_idx(x, y - 1);
-                if can_step_to(value, self.grid[idx]) {
-                    neighbors.push(idx);
-                }
-            }
-            if y + 1 < self.height {
-                let idx = self.to_idx(x, y + 1);
-                if can_step_to(value, self.grid[idx]) {
-                    neighbors.push(idx);
-                }
-            }
-
-            let from_dist = dist[n];
-            neighbors.into_iter().for_each(|to| {
-                let new_dist = from_dist + 1;
-                if new_dist < dist[to] {
-                    dist[to] = new_dist;
-                    q.push_back(to);
-                }
-            });
-        }
+    pub fn end(&self) -> Vec2i {
+        self.end
+    }
 
-        dist
+    /// The fewest steps from the start to the end, climbing at most one height per step.
+    pub fn fewest_steps(&self) -> Option<usize> {
+        self.grid
+            .bfs([self.start], |&from, &to| to <= from + 1)
+            .get(&self.end)
     }
 
-    pub fn bfs(&self) -> Vec<usize> {
-        fn can_step_to(from: u8, to: u8) -> bool {
-            to <= from + 1
-        }
+    /// The fewest steps from any square at height `a` to the end.
+    pub fn fewest_steps_from_lowest(&self) -> Option<usize> {
+        self.grid
+            .bfs([self.end], |&from, &to| from <= to + 1)
+            .iter()
+            .filter(|&(pos, _)| self.grid[*pos] == b'a')
+            .map(|(_, dist)| dist)
+            .min()
+    }
+}
 
-        self.bfs_impl(self.start, can_step_to)
+pub fn parse(input: &str) -> Result<Heightmap, ParseError> {
+    let input = &normalize(input, true);
+    let trimmed = input.trim();
+    let width = trimmed.lines().next().map_or(0, str::len);
+    if let Some(l) = trimmed.lines().find(|l| l.len() != width) {
+        return Err(ParseError::new("non rectangular grid")
+            .located_in(input, l)
+            .in_day(12));
     }
+    let grid: Vec<u8> = trimmed.lines().flat_map(str::bytes).collect();
 
-    pub fn bfs_reverse(&self) -> Vec<usize> {
-        fn can_step_to(from: u8, to: u8) -> bool {
-            from <= to + 1
-        }
+    Heightmap::new(grid, width).map_err(|e| e.located_in(input, trimmed).in_day(12))
+}
 
-        self.bfs_impl(self.end, can_step_to)
-    }
+pub fn solve_part1(map: &Heightmap) -> usize {
+    map.fewest_steps().unwrap_or(usize::MAX)
 }
 
-#[aoc_generator(day12)]
-pub fn input_generator(input: &str) -> Grid {
-    let input = input.trim();
-    let (width, _) = input.bytes().find_position(|&c| c == b'\n').unwrap();
-    let grid: Vec<u8> = input.lines().flat_map(str::bytes).collect();
+pub fn solve_part2(map: &Heightmap) -> usize {
+    map.fewest_steps_from_lowest().unwrap_or(usize::MAX)
+}
 
-    Grid::new(grid, width)
+#[doc(hidden)]
+#[aoc_generator(day12)]
+pub fn input_generator(input: &str) -> Result<Heightmap, ParseError> {
+    parse(input)
 }
 
+#[doc(hidden)]
 #[aoc(day12 part1)]
-pub fn part1(grid: &Grid) -> usize {
-    let result = grid.bfs();
-    result[grid.end]
+pub fn part1(map: &Heightmap) -> usize {
+    solve_part1(map)
 }
 
+#[doc(hidden)]
 #[aoc(day12, part2)]
-pub fn part2(grid: &Grid) -> usize {
-    let result = grid.bfs_reverse();
-    let (_, dist) = result
-        .into_iter()
-        .enumerate()
-        .filter(|&(i, _)| grid.grid[i] == b'a')
-        .min_by_key(|&(_, dist)| dist)
+pub fn part2(map: &Heightmap) -> usize {
+    solve_part2(map)
+}
+
+pub(crate) static SOLUTION: Day<Heightmap> = Day {
+    day: 12,
+    parse,
+    part1: |input, _| Ok(solve_part1(input).into()),
+    part2: Some(|input, _| Ok(solve_part2(input).into())),
+};
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::common::input::windows_input;
+
+    #[test]
+    fn test_crlf() {
+        let input = parse(&windows_input(include_str!(
+            "../fixtures/day12/example.txt"
+        )))
         .unwrap();
-    dist
+        assert_eq!(31, solve_part1(&input));
+    }
 }
@@ -1,9 +1,16 @@
+//! Day 13: Distress Signal, comparing and sorting nested list packets.
+
 use std::cmp::Ordering;
 use std::str::FromStr;
 
 use aoc_runner_derive::{aoc, aoc_generator};
 use itertools::Itertools;
 
+use crate::common::input::normalize;
+use crate::common::{parse_lines_with, ParseError};
+use crate::solution::Day;
+
+/// A packet like `[1,[2,3]]`, ordered the way the distress signal expects them.
 #[derive(Debug, Clone, Eq, PartialEq)]
 pub enum Packet {
     Int(u32),
@@ -11,39 +18,49 @@ pub enum Packet {
 }
 
 impl FromStr for Packet {
-    type Err = ();
+    type Err = ParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let error = |i: usize, reason: &str| ParseError::new(reason).at_column(i + 1);
         let mut stack: Vec<Vec<Packet>> = vec![];
-        let mut it = s.chars().peekable();
-        while let Some(c) = it.peek() {
+        let mut it = s.char_indices().peekable();
+        while let Some(&(i, c)) = it.peek() {
             match c {
                 '[' => {
                     stack.push(vec![]);
                     it.next();
                 }
                 ']' => {
-                    let tos = Packet::List(stack.pop().ok_or(())?);
+                    let tos = Packet::List(stack.pop().ok_or_else(|| error(i, "unmatched ']'"))?);
                     it.next();
                     match stack.last_mut() {
                         Some(new_tos) => new_tos.push(tos),
-                        None => return it.next().map_or_else(|| Ok(tos), |_| Err(())),
+                        None => {
+                            return match it.next() {
+                                None => Ok(tos),
+                                Some((i, _)) => Err(error(i, "trailing characters after packet")),
+                            }
+                        }
                     }
                 }
-                '0'..='9' => stack.last_mut().ok_or(())?.push(Packet::Int(
-                    it.peeking_take_while(|c| ('0'..='9').contains(c))
-                        .collect::<String>()
-                        .parse()
-                        .map_err(|_| ())?,
-                )),
+                '0'..='9' => stack
+                    .last_mut()
+                    .ok_or_else(|| error(i, "integer outside of list"))?
+                    .push(Packet::Int(
+                        it.peeking_take_while(|(_, c)| c.is_ascii_digit())
+                            .map(|(_, c)| c)
+                            .collect::<String>()
+                            .parse()
+                            .map_err(|e| ParseError::from(e).at_column(i + 1))?,
+                    )),
                 ',' | ' ' => {
                     it.next();
                 }
-                _ => break,
+                _ => return Err(error(i, "unexpected character")),
             }
         }
 
-        Err(())
+        Err(error(s.len(), "unexpected end of packet"))
     }
 }
 
@@ -76,18 +93,12 @@ impl Ord for Packet {
     }
 }
 
-#[aoc_generator(day13)]
-pub fn input_generator(input: &str) -> Vec<Packet> {
-    input
-        .lines()
-        .map(str::trim)
-        .filter(|l| !l.is_empty())
-        .map(|l| l.parse().unwrap())
-        .collect()
+pub fn parse(input: &str) -> Result<Vec<Packet>, ParseError> {
+    let input = &normalize(input, true);
+    parse_lines_with(input, str::parse).map_err(|e| e.in_day(13))
 }
 
-#[aoc(day13 part1)]
-pub fn part1(packets: &[Packet]) -> usize {
+pub fn solve_part1(packets: &[Packet]) -> usize {
     packets
         .chunks_exact(2)
         .enumerate()
@@ -96,8 +107,7 @@ pub fn part1(packets: &[Packet]) -> usize {
         .sum()
 }
 
-#[aoc(day13, part2)]
-pub fn part2(packets: &[Packet]) -> usize {
+pub fn solve_part2(packets: &[Packet]) -> usize {
     let mut packets = packets.to_vec();
     let div1: Packet = "[[2]]".parse().unwrap();
     let div2: Packet = "[[6]]".parse().unwrap();
@@ -108,3 +118,45 @@ pub fn part2(packets: &[Packet]) -> usize {
     (packets.iter().position(|p| p == &div1).unwrap() + 1)
         * (packets.iter().position(|p| p == &div2).unwrap() + 1)
 }
+
+#[doc(hidden)]
+#[aoc_generator(day13)]
+pub fn input_generator(input: &str) -> Result<Vec<Packet>, ParseError> {
+    parse(input)
+}
+
+#[doc(hidden)]
+#[aoc(day13 part1)]
+pub fn part1(packets: &[Packet]) -> usize {
+    solve_part1(packets)
+}
+
+#[doc(hidden)]
+#[aoc(day13, part2)]
+pub fn part2(packets: &[Packet]) -> usize {
+    solve_part2(packets)
+}
+
+pub(crate) static SOLUTION: Day<Vec<Packet>> = Day {
+    day: 13,
+    parse,
+    part1: |input, _| Ok(solve_part1(input).into()),
+    part2: Some(|input, _| Ok(solve_part2(input).into())),
+};
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::common::input::windows_input;
+
+    #[test]
+    fn test_crlf() {
+        let input = parse(&windows_input(include_str!(
+            "../fixtures/day13/example.txt"
+        )))
+        .unwrap();
+        assert_eq!(13, solve_part1(&input));
+    }
+}
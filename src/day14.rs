@@ -1,71 +1,57 @@
+//! Day 14: Regolith Reservoir, sand pouring into a cave of rock paths.
+
 use std::str::FromStr;
 
 use aoc_runner_derive::{aoc, aoc_generator};
-use rustc_hash::FxHashSet;
 
-#[derive(Debug, Clone, Copy)]
+use crate::common::input::normalize;
+use crate::common::{parse_lines_with, parse_located, ParseError, SparseGrid, Vec2i};
+use crate::solution::Day;
+use crate::visualize::{notify, Frame, Observer, Palette, Visualize};
+
+/// A horizontal or vertical line of rock, both ends included.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Obstacle {
     LineX { y: u32, x_start: u32, x_end: u32 },
     LineY { x: u32, y_start: u32, y_end: u32 },
 }
 
-impl Obstacle {
-    pub fn contains(&self, pos: &Pos) -> bool {
-        match self {
-            Obstacle::LineX { y, x_start, x_end } => {
-                y == &pos.y && (x_start..=x_end).contains(&&pos.x)
-            }
-            Obstacle::LineY { x, y_start, y_end } => {
-                x == &pos.x && (y_start..=y_end).contains(&&pos.y)
-            }
-        }
-    }
-
-    pub fn max_y(&self) -> u32 {
-        match self {
-            Obstacle::LineX { y, .. } => *y,
-            Obstacle::LineY { y_end, .. } => *y_end,
-        }
-    }
-}
-
 #[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
 pub struct Pos {
-    x: u32,
-    y: u32,
+    pub x: u32,
+    pub y: u32,
 }
 
 impl FromStr for Pos {
-    type Err = ();
+    type Err = ParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let (x, y) = s.split_once(',').ok_or(())?;
+        let (x, y) = s
+            .split_once(',')
+            .ok_or_else(|| ParseError::new("expected position 'x,y'"))?;
         Ok(Pos {
-            x: x.parse().map_err(|_| ())?,
-            y: y.parse().map_err(|_| ())?,
+            x: parse_located(s, x)?,
+            y: parse_located(s, y)?,
         })
     }
 }
 
-#[aoc_generator(day14)]
-pub fn input_generator(input: &str) -> Vec<Obstacle> {
-    let paths: Vec<Vec<Pos>> = input
-        .lines()
-        .map(str::trim)
-        .filter(|l| !l.is_empty())
-        .map(|l| {
-            l.split("->")
-                .map(str::trim)
-                .filter(|l| !l.is_empty())
-                .map(|p| p.parse().unwrap())
-                .collect()
-        })
-        .collect();
+/// Parses the rock paths into their straight lines.
+pub fn parse(input: &str) -> Result<Vec<Obstacle>, ParseError> {
+    let input = &normalize(input, true);
+    let paths: Vec<Vec<(&str, Pos)>> = parse_lines_with(input, |l| {
+        l.split("->")
+            .map(str::trim)
+            .filter(|l| !l.is_empty())
+            .map(|p| Ok((p, parse_located(l, p)?)))
+            .collect()
+    })
+    .map_err(|e| e.in_day(14))?;
 
     let mut obstacles = Vec::new();
     for path in paths {
         for window in path.windows(2) {
-            if let [a, b] = window {
+            if let [(_, a), (b_str, b)] = window {
                 if a.y == b.y {
                     obstacles.push(Obstacle::LineX {
                         y: a.y,
@@ -79,108 +65,220 @@ pub fn input_generator(input: &str) -> Vec<Obstacle> {
                         y_end: a.y.max(b.y),
                     });
                 } else {
-                    panic!("diagonal lines not allowed");
+                    return Err(ParseError::new("diagonal lines not allowed")
+                        .located_in(input, b_str)
+                        .in_day(14));
                 }
             }
         }
     }
 
-    obstacles
+    if obstacles.is_empty() {
+        return Err(ParseError::new("expected at least one rock path").in_day(14));
+    }
+
+    Ok(obstacles)
 }
 
-const SPAWN_POINT: Pos = Pos { x: 500, y: 0 };
+const SPAWN_POINT: Vec2i = Vec2i::new(500, 0);
 
-#[aoc(day14 part1)]
-pub fn part1(obstacles: &[Obstacle]) -> usize {
-    let max_y = obstacles.iter().map(|o| o.max_y()).max().unwrap();
-    let mut all_sand = FxHashSet::default();
-
-    let mut count = 0;
-    let mut path = vec![];
-    'outer: loop {
-        let mut sand = path.pop().unwrap_or(SPAWN_POINT);
-        'inner: loop {
-            if sand.y > max_y {
-                break 'outer;
-            }
+/// The cave with the rocks and the sand that came to rest so far.
+#[derive(Debug, Clone)]
+pub struct Cave {
+    cave: SparseGrid<char>,
+    max_y: i64,
+    floor: Option<i64>,
+    /// the positions the last unit of sand fell through, where the next one will follow it
+    path: Vec<Vec2i>,
+    sand: usize,
+}
 
-            for next_pos in &[
-                Pos {
-                    x: sand.x,
-                    y: sand.y + 1,
-                },
-                Pos {
-                    x: sand.x - 1,
-                    y: sand.y + 1,
-                },
-                Pos {
-                    x: sand.x + 1,
-                    y: sand.y + 1,
-                },
-            ] {
-                if all_sand.contains(next_pos) || obstacles.iter().any(|o| o.contains(next_pos)) {
-                    continue;
+impl Cave {
+    /// The cave with just the rocks. If `has_floor` is set, there is an infinite floor two
+    /// tiles below the lowest rock.
+    pub fn new(obstacles: &[Obstacle], has_floor: bool) -> Self {
+        let mut cave = SparseGrid::new();
+        for o in obstacles {
+            match *o {
+                Obstacle::LineX { y, x_start, x_end } => {
+                    cave.extend((x_start..=x_end).map(|x| (Vec2i::new(x as i64, y as i64), '#')))
+                }
+                Obstacle::LineY { x, y_start, y_end } => {
+                    cave.extend((y_start..=y_end).map(|y| (Vec2i::new(x as i64, y as i64), '#')))
                 }
-
-                path.push(sand);
-                sand = *next_pos;
-                continue 'inner;
             }
+        }
+        let max_y = cave.bounds().map_or(0, |(_, max)| max.y);
 
-            count += 1;
-            all_sand.insert(sand);
-            continue 'outer;
+        Self {
+            cave,
+            max_y,
+            floor: has_floor.then_some(max_y + 2),
+            path: vec![],
+            sand: 0,
         }
     }
 
-    count
-}
+    /// The rocks `#` and the sand `o` that came to rest.
+    pub fn tiles(&self) -> &SparseGrid<char> {
+        &self.cave
+    }
 
-#[aoc(day14, part2)]
-pub fn part2(obstacles: &[Obstacle]) -> usize {
-    let floor = 2 + obstacles.iter().map(|o| o.max_y()).max().unwrap();
-    let mut all_sand = FxHashSet::default();
-
-    let mut count = 0;
-    let mut path = vec![];
-    'outer: loop {
-        let mut sand = path.pop().unwrap_or(SPAWN_POINT);
-        if all_sand.contains(&sand) || obstacles.iter().any(|o| o.contains(&sand)) {
-            break 'outer;
+    /// The units of sand that came to rest so far.
+    pub fn sand(&self) -> usize {
+        self.sand
+    }
+
+    /// Pours one unit of sand and returns where it came to rest, `None` if it fell into the
+    /// abyss or the source is blocked.
+    pub fn drop_sand(&mut self) -> Option<Vec2i> {
+        let mut sand = self.path.pop().unwrap_or(SPAWN_POINT);
+        if self.cave.contains(&sand) {
+            return None;
         }
 
-        'inner: loop {
-            if sand.y + 1 < floor {
-                for next_pos in &[
-                    Pos {
-                        x: sand.x,
-                        y: sand.y + 1,
-                    },
-                    Pos {
-                        x: sand.x - 1,
-                        y: sand.y + 1,
-                    },
-                    Pos {
-                        x: sand.x + 1,
-                        y: sand.y + 1,
-                    },
-                ] {
-                    if all_sand.contains(next_pos) || obstacles.iter().any(|o| o.contains(next_pos))
-                    {
-                        continue;
-                    }
+        'fall: loop {
+            if self.floor.is_none() && sand.y > self.max_y {
+                return None;
+            }
 
-                    path.push(sand);
-                    sand = *next_pos;
-                    continue 'inner;
+            if self.floor.is_none_or(|floor| sand.y + 1 < floor) {
+                for dx in [0, -1, 1] {
+                    let next_pos = sand + Vec2i::new(dx, 1);
+                    if !self.cave.contains(&next_pos) {
+                        self.path.push(sand);
+                        sand = next_pos;
+                        continue 'fall;
+                    }
                 }
             }
 
-            count += 1;
-            all_sand.insert(sand);
-            continue 'outer;
+            self.sand += 1;
+            self.cave.insert(sand, 'o');
+            return Some(sand);
+        }
+    }
+}
+
+const PALETTE: Palette = Palette(&[
+    ('#', [128, 128, 128]),
+    ('o', [230, 190, 90]),
+    ('+', [255, 60, 60]),
+    ('.', [16, 16, 32]),
+]);
+
+impl Visualize for Cave {
+    fn frame(&self) -> Frame {
+        let (min, max) = self.cave.bounds().unwrap();
+        let (min_x, max_x) = (min.x.min(SPAWN_POINT.x), max.x.max(SPAWN_POINT.x));
+        let max_y = self.floor.unwrap_or(max.y);
+        // one column of air on both sides
+        let min_x = min_x - 1;
+
+        let mut frame = Frame::new(
+            (max_x - min_x + 2) as usize,
+            (max_y + 1) as usize,
+            '.',
+            PALETTE,
+        );
+        let mut set = |x: i64, y: i64, c| frame.set((x - min_x) as usize, y as usize, c);
+        for (p, &c) in self.cave.iter() {
+            set(p.x, p.y, c);
         }
+        if let Some(floor) = self.floor {
+            (min_x..=max_x + 1).for_each(|x| set(x, floor, '#'));
+        }
+        set(SPAWN_POINT.x, SPAWN_POINT.y, '+');
+
+        frame
+    }
+}
+
+/// Pours sand until it either falls into the abyss or, if there is a floor below the lowest
+/// rock, until it blocks the source. Returns the units of sand that came to rest.
+pub fn pour_sand(
+    obstacles: &[Obstacle],
+    has_floor: bool,
+    mut observer: Option<&mut dyn Observer>,
+) -> usize {
+    let mut cave = Cave::new(obstacles, has_floor);
+    while cave.drop_sand().is_some() {
+        notify(&mut observer, &cave);
+    }
+
+    cave.sand()
+}
+
+pub fn solve_part1(obstacles: &[Obstacle]) -> usize {
+    pour_sand(obstacles, false, None)
+}
+
+pub fn solve_part2(obstacles: &[Obstacle]) -> usize {
+    pour_sand(obstacles, true, None)
+}
+
+/// Shows the cave after each unit of sand came to rest.
+pub fn visualize(obstacles: &[Obstacle], part: u32, observer: &mut dyn Observer) {
+    pour_sand(obstacles, part == 2, Some(observer));
+}
+
+#[doc(hidden)]
+#[aoc_generator(day14)]
+pub fn input_generator(input: &str) -> Result<Vec<Obstacle>, ParseError> {
+    parse(input)
+}
+
+#[doc(hidden)]
+#[aoc(day14 part1)]
+pub fn part1(obstacles: &[Obstacle]) -> usize {
+    solve_part1(obstacles)
+}
+
+#[doc(hidden)]
+#[aoc(day14, part2)]
+pub fn part2(obstacles: &[Obstacle]) -> usize {
+    solve_part2(obstacles)
+}
+
+pub(crate) static SOLUTION: Day<Vec<Obstacle>> = Day {
+    day: 14,
+    parse,
+    part1: |input, _| Ok(solve_part1(input).into()),
+    part2: Some(|input, _| Ok(solve_part2(input).into())),
+};
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::common::input::windows_input;
+
+    #[test]
+    fn test_drop_sand() {
+        let obstacles = input_generator(
+            r"498,4 -> 498,6 -> 496,6
+503,4 -> 502,4 -> 502,9 -> 494,9
+",
+        )
+        .unwrap();
+        let mut cave = Cave::new(&obstacles, false);
+        assert_eq!(Some(Vec2i::new(500, 8)), cave.drop_sand());
+        assert_eq!(Some(Vec2i::new(499, 8)), cave.drop_sand());
+        assert_eq!(Some(Vec2i::new(501, 8)), cave.drop_sand());
+        while cave.drop_sand().is_some() {}
+        assert_eq!(24, cave.sand());
+        assert_eq!(Some(&'o'), cave.tiles().get(&Vec2i::new(495, 8)));
+
+        assert_eq!(93, pour_sand(&obstacles, true, None));
     }
 
-    count
+    #[test]
+    fn test_crlf() {
+        let input = parse(&windows_input(include_str!(
+            "../fixtures/day14/example.txt"
+        )))
+        .unwrap();
+        assert_eq!(24, solve_part1(&input));
+    }
 }
@@ -1,202 +1,261 @@
+//! Day 15: Beacon Exclusion Zone, the positions sensors rule out for the distress beacon.
+
+use std::any::Any;
+
 use aoc_runner_derive::{aoc, aoc_generator};
-use itertools::Itertools;
-use regex::Regex;
+use nalgebra::Matrix2;
+use num::{One, Rational64, Signed, Zero};
+use rayon::prelude::*;
 
-#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
-pub struct Pos {
-    x: i32,
-    y: i32,
-}
+use crate::common::geometry::{Affine, Intersection, Linear, Rect, Segment};
+use crate::common::input::normalize;
+use crate::common::metric::manhattan;
+use crate::common::parser::parse_all;
+use crate::common::{parse_lines_with, Interval, IntervalSet, ParseError, Vec2i, Vec2r};
+use crate::solution::{
+    parse_param, parse_param_at_least, Day, ParamError, PuzzleParams, SolveError,
+};
 
-impl Pos {
-    fn distance(&self, other: &Pos) -> u32 {
-        self.x.abs_diff(other.x) + self.y.abs_diff(other.y)
-    }
+pub type Pos = Vec2i;
 
-    fn tuning_frequency(&self) -> i64 {
-        (self.x as i64) * 4000000 + (self.y as i64)
-    }
+/// The answer the distress beacon at `pos` expects.
+pub fn tuning_frequency(pos: &Pos) -> i64 {
+    pos.x * 4000000 + pos.y
 }
 
-#[aoc_generator(day15)]
-pub fn input_generator(input: &str) -> Vec<(Pos, Pos)> {
-    let r = Regex::new(r"^Sensor at x=([+\-]?\d+), y=([+\-]?\d+): closest beacon is at x=([+\-]?\d+), y=([+\-]?\d+)$").unwrap();
-    input
-        .lines()
-        .map(str::trim)
-        .filter(|l| !l.is_empty())
-        .map(|l| {
-            let c = r.captures(l).unwrap();
-            (
-                Pos {
-                    x: c[1].parse().unwrap(),
-                    y: c[2].parse().unwrap(),
-                },
-                Pos {
-                    x: c[3].parse().unwrap(),
-                    y: c[4].parse().unwrap(),
-                },
-            )
+/// Parses every sensor with the closest beacon it detected.
+pub fn parse(input: &str) -> Result<Vec<(Pos, Pos)>, ParseError> {
+    let input = &normalize(input, true);
+    parse_lines_with(input, |l| {
+        parse_all(l, |p| {
+            p.keyword("Sensor at x=")?;
+            let sensor_x = p.integer()?;
+            p.keyword(", y=")?;
+            let sensor_y = p.integer()?;
+            p.keyword(": closest beacon is at x=")?;
+            let beacon_x = p.integer()?;
+            p.keyword(", y=")?;
+            let beacon_y = p.integer()?;
+            Ok((Pos::new(sensor_x, sensor_y), Pos::new(beacon_x, beacon_y)))
         })
-        .collect()
+    })
+    .map_err(|e| e.in_day(15))
 }
 
-fn no_beacon_pos<const Y: i32>(input: &[(Pos, Pos)]) -> usize {
-    input
+/// The number of positions in row `y` where there can't be a beacon.
+pub fn no_beacon_pos(input: &[(Pos, Pos)], y: i64) -> usize {
+    let mut covered: IntervalSet<i64> = input
         .iter()
-        .flat_map(|(sensor, beacon)| {
-            let d = sensor.distance(beacon);
-            let dy = Y.abs_diff(sensor.y);
-            if d < dy {
-                vec![]
-            } else {
-                let dx = (d - dy) as i32;
-                ((sensor.x - dx)..=(sensor.x + dx))
-                    .filter(|&x| Y != beacon.y || x != beacon.x)
-                    .collect()
-            }
+        .filter_map(|(sensor, beacon)| {
+            let dx = manhattan(sensor, beacon) - (y - sensor.y).abs();
+            (dx >= 0).then(|| Interval::new(sensor.x - dx, sensor.x + dx))
         })
-        .unique()
-        .count()
-}
-
-fn corner_points(sensor: &Pos, d: u32) -> (Pos, Pos, Pos, Pos) {
-    let d = d as i32;
-    (
-        Pos {
-            x: sensor.x + d,
-            y: sensor.y,
-        },
-        Pos {
-            x: sensor.x - d,
-            y: sensor.y,
-        },
-        Pos {
-            x: sensor.x,
-            y: sensor.y + d,
-        },
-        Pos {
-            x: sensor.x,
-            y: sensor.y - d,
-        },
-    )
-}
-
-fn diag_iter(a: &Pos, b: &Pos) -> impl Iterator<Item = Pos> {
-    struct Iter {
-        pos: Pos,
-        end: Pos,
-        done: bool,
-        dx: i32,
-        dy: i32,
+        .collect();
+    // a beacon can lie within the range of another sensor, so skip all of them
+    for (_, beacon) in input.iter().filter(|(_, beacon)| beacon.y == y) {
+        covered.remove(Interval::point(beacon.x));
     }
+    covered.len() as usize
+}
 
-    impl Iterator for Iter {
-        type Item = Pos;
-
-        fn next(&mut self) -> Option<Self::Item> {
-            if self.done {
-                None
-            } else {
-                let pos = self.pos;
-                if pos == self.end {
-                    self.done = true;
-                } else {
-                    self.pos.x += self.dx;
-                    self.pos.y += self.dy;
-                }
+/// The four edges of the diamond of all positions at distance `r` from `center`.
+fn diamond_edges(center: &Pos, r: i64) -> [Segment<Rational64>; 4] {
+    let corners = [(r, 0), (0, r), (-r, 0), (0, -r)]
+        .map(|(dx, dy)| (center + Pos::new(dx, dy)).map(Rational64::from_integer));
+    [0, 1, 2, 3].map(|i| Segment::new(corners[i], corners[(i + 1) % 4]))
+}
 
-                Some(pos)
-            }
-        }
-    }
+/// Turns the diamonds of the sensors into axis-aligned squares, as `(x, y) ↦ (x + y, x - y)`
+/// keeps the Manhattan distance as the Chebyshev distance.
+fn rotation() -> Affine<Rational64> {
+    let one = Rational64::one();
+    Affine::linear(Matrix2::new(one, one, one, -one))
+}
 
-    Iter {
-        pos: *a,
-        end: *b,
-        done: false,
-        dx: (b.x - a.x).signum(),
-        dy: (b.y - a.y).signum(),
+/// The first position on the horizontal, vertical or diagonal `segment` that none of the
+/// `covered` squares contains, which are given in the coordinates of [`rotation`].
+fn first_uncovered(segment: &Segment<Rational64>, covered: &[Rect<Rational64>]) -> Option<Vec2r> {
+    let d = segment.direction();
+    let steps = d.x.abs().max(d.y.abs());
+    if steps.is_zero() {
+        return Some(segment.a);
     }
+
+    // the positions on the segment are `a + k * d / steps` for k from 0 to `steps`
+    let rotated = segment.transform(&rotation());
+    let rd = rotated.direction();
+    let to_k = |p: Vec2r| (p - rotated.a).dot(&rd) / rd.dot(&rd) * steps;
+    let covered_k: IntervalSet<i64> = covered
+        .iter()
+        .filter_map(|square| {
+            let (lo, hi) = match square.clip(&rotated)? {
+                Intersection::Point(p) => (to_k(p), to_k(p)),
+                Intersection::Segment(s) => (to_k(s.a), to_k(s.b)),
+                _ => unreachable!("clipped to a bounded rectangle"),
+            };
+            let (lo, hi) = if lo <= hi { (lo, hi) } else { (hi, lo) };
+            Interval::try_new(lo.ceil().to_integer(), hi.floor().to_integer())
+        })
+        .collect();
+    let k = covered_k
+        .complement(Interval::new(0, steps.floor().to_integer()))
+        .intervals()
+        .first()?
+        .start;
+    Some(segment.a + d * Rational64::from_integer(k) / steps)
 }
 
-fn find_beacon_pos<const MAX: i32>(input: &[(Pos, Pos)]) -> Option<i64> {
-    assert!(MAX >= 0);
-    for (sensor, beacon) in input {
-        let d = sensor.distance(beacon) + 1;
-        let (e, w, s, n) = corner_points(sensor, d);
-
-        for pos in diag_iter(&n, &w) {
-            if pos.x >= 0
-                && pos.x <= MAX
-                && pos.y >= 0
-                && pos.y <= MAX
-                && input
-                    .iter()
-                    .all(|(sensor2, beacon2)| sensor2.distance(&pos) > sensor2.distance(beacon2))
-            {
-                return Some(pos.tuning_frequency());
-            }
-        }
+/// The candidates for the free position that `a` and the edges after it contribute. Where
+/// edges overlap, the free position can be anywhere on the overlap, so it is searched for
+/// among the positions the `covered` squares leave out.
+fn candidates<'a>(
+    edges: &'a [Segment<Rational64>],
+    i: usize,
+    area: &'a Rect<Rational64>,
+    covered: &'a [Rect<Rational64>],
+) -> impl Iterator<Item = Vec2r> + 'a {
+    edges[i..]
+        .iter()
+        .filter_map(move |b| edges[i].intersection(b))
+        .filter_map(move |intersection| match intersection {
+            Intersection::Point(p) => Some(p),
+            Intersection::Segment(s) => match area.clip(&s)? {
+                Intersection::Point(p) => Some(p),
+                Intersection::Segment(s) => first_uncovered(&s, covered),
+                _ => None,
+            },
+            _ => None,
+        })
+}
 
-        for pos in diag_iter(&n, &e) {
-            if pos.x >= 0
-                && pos.x <= MAX
-                && pos.y >= 0
-                && pos.y <= MAX
-                && input
-                    .iter()
-                    .all(|(sensor2, beacon2)| sensor2.distance(&pos) > sensor2.distance(beacon2))
-            {
-                return Some(pos.tuning_frequency());
-            }
+/// Searches the edges of the sensors on all cores if `parallel` is set, which finds the same
+/// position as the sequential search.
+pub fn find_beacon_pos(input: &[(Pos, Pos)], max: i64, parallel: bool) -> Option<i64> {
+    assert!(max >= 0);
+    // the only free position is just outside the range of the sensors around it, so it is
+    // on the edges of their enlarged diamonds or the borders of the search area, either
+    // where two of them cross or somewhere along two that overlap
+    let area = Rect::new(Vec2r::zeros(), Vec2r::new(max.into(), max.into()));
+    let covered = input
+        .iter()
+        .map(|(sensor, beacon)| {
+            let r = Rational64::from_integer(manhattan(sensor, beacon));
+            let center = rotation().apply(&sensor.map(Rational64::from_integer));
+            Rect::new(center.add_scalar(-r), center.add_scalar(r))
+        })
+        .collect::<Vec<_>>();
+    let edges = input
+        .iter()
+        .flat_map(|(sensor, beacon)| diamond_edges(sensor, manhattan(sensor, beacon) + 1))
+        .chain(area.edges())
+        .collect::<Vec<_>>();
+    let to_free_pos = |p: Vec2r| {
+        if !area.contains(&p) || !p.iter().all(Rational64::is_integer) {
+            return None;
         }
 
-        for pos in diag_iter(&s, &w) {
-            if pos.x >= 0
-                && pos.x <= MAX
-                && pos.y >= 0
-                && pos.y <= MAX
-                && input
-                    .iter()
-                    .all(|(sensor2, beacon2)| sensor2.distance(&pos) > sensor2.distance(beacon2))
-            {
-                return Some(pos.tuning_frequency());
-            }
+        let pos = p.map(|c| c.to_integer());
+        input
+            .iter()
+            .all(|(sensor, beacon)| manhattan(sensor, &pos) > manhattan(sensor, beacon))
+            .then_some(pos)
+    };
+
+    let pos = if parallel {
+        (0..edges.len())
+            .into_par_iter()
+            .filter_map(|i| candidates(&edges, i, &area, &covered).find_map(to_free_pos))
+            .find_first(|_| true)
+    } else {
+        (0..edges.len()).find_map(|i| candidates(&edges, i, &area, &covered).find_map(to_free_pos))
+    };
+    pos.map(|pos| tuning_frequency(&pos))
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Params {
+    /// row to count the positions without a beacon in
+    pub row: i64,
+    /// maximum x and y coordinate of the distress beacon
+    pub max: i64,
+    /// search the sensors on all cores
+    pub parallel: bool,
+}
+
+impl Default for Params {
+    fn default() -> Self {
+        Self {
+            row: 2000000,
+            max: 4000000,
+            parallel: false,
         }
+    }
+}
 
-        for pos in diag_iter(&s, &e) {
-            if pos.x >= 0
-                && pos.x <= MAX
-                && pos.y >= 0
-                && pos.y <= MAX
-                && input
-                    .iter()
-                    .all(|(sensor2, beacon2)| sensor2.distance(&pos) > sensor2.distance(beacon2))
-            {
-                return Some(pos.tuning_frequency());
-            }
+impl PuzzleParams for Params {
+    fn set(&mut self, key: &str, value: &str) -> Result<(), ParamError> {
+        match key {
+            "row" => self.row = parse_param(key, value)?,
+            "max" => self.max = parse_param_at_least(key, value, 0)?,
+            "parallel" => self.parallel = parse_param(key, value)?,
+            _ => return Err(ParamError::Unknown(key.to_string())),
         }
+        Ok(())
     }
 
-    None
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+pub fn part1_with(input: &[(Pos, Pos)], params: &Params) -> usize {
+    no_beacon_pos(input, params.row)
+}
+
+pub fn part2_with(input: &[(Pos, Pos)], params: &Params) -> Result<i64, SolveError> {
+    find_beacon_pos(input, params.max, params.parallel)
+        .ok_or_else(|| SolveError::new("no distress beacon in range"))
+}
+
+pub fn solve_part1(input: &[(Pos, Pos)]) -> usize {
+    part1_with(input, &Params::default())
 }
 
+pub fn solve_part2(input: &[(Pos, Pos)]) -> Result<i64, SolveError> {
+    part2_with(input, &Params::default())
+}
+
+#[doc(hidden)]
+#[aoc_generator(day15)]
+pub fn input_generator(input: &str) -> Result<Vec<(Pos, Pos)>, ParseError> {
+    parse(input)
+}
+
+#[doc(hidden)]
 #[aoc(day15 part1)]
 pub fn part1(input: &[(Pos, Pos)]) -> usize {
-    no_beacon_pos::<2000000>(input)
+    solve_part1(input)
 }
 
+#[doc(hidden)]
 #[aoc(day15, part2)]
-pub fn part2(input: &[(Pos, Pos)]) -> i64 {
-    find_beacon_pos::<4000000>(input).unwrap()
+pub fn part2(input: &[(Pos, Pos)]) -> Result<i64, SolveError> {
+    solve_part2(input)
 }
 
+pub(crate) static SOLUTION: Day<Vec<(Pos, Pos)>, Params> = Day {
+    day: 15,
+    parse,
+    part1: |input, params| Ok(part1_with(input, params).into()),
+    part2: Some(|input, params| Ok(part2_with(input, params)?.into())),
+};
+
 #[cfg(test)]
 mod tests {
     use pretty_assertions::assert_eq;
 
     use super::*;
+    use crate::common::input::windows_input;
 
     #[test]
     fn test_1() {
@@ -216,8 +275,9 @@ Sensor at x=16, y=7: closest beacon is at x=15, y=3
 Sensor at x=14, y=3: closest beacon is at x=15, y=3
 Sensor at x=20, y=1: closest beacon is at x=15, y=3
 ",
-        );
-        assert_eq!(26, no_beacon_pos::<10>(&input))
+        )
+        .unwrap();
+        assert_eq!(26, no_beacon_pos(&input, 10))
     }
 
     #[test]
@@ -238,7 +298,81 @@ Sensor at x=16, y=7: closest beacon is at x=15, y=3
 Sensor at x=14, y=3: closest beacon is at x=15, y=3
 Sensor at x=20, y=1: closest beacon is at x=15, y=3
 ",
+        )
+        .unwrap();
+        assert_eq!(Some(56000011), find_beacon_pos(&input, 20, false));
+        assert_eq!(Some(56000011), find_beacon_pos(&input, 20, true));
+        let params = Params {
+            max: 5,
+            ..Params::default()
+        };
+        assert_eq!(
+            Err(SolveError::new("no distress beacon in range")),
+            part2_with(&input, &params)
+        );
+    }
+
+    #[test]
+    fn test_overlapping_edges() {
+        // only the collinear edges of the two diagonal sensors run through the free position
+        let input = parse(
+            r"Sensor at x=13, y=13: closest beacon is at x=18, y=13
+Sensor at x=7, y=7: closest beacon is at x=12, y=7
+Sensor at x=13, y=7: closest beacon is at x=17, y=7
+Sensor at x=7, y=13: closest beacon is at x=11, y=13
+Sensor at x=-5, y=20: closest beacon is at x=18, y=20
+Sensor at x=20, y=-5: closest beacon is at x=43, y=-5
+Sensor at x=-5, y=-5: closest beacon is at x=23, y=-5
+Sensor at x=12, y=19: closest beacon is at x=21, y=19
+",
+        )
+        .unwrap();
+        assert_eq!(Some(40000010), find_beacon_pos(&input, 20, false));
+        assert_eq!(Some(40000010), find_beacon_pos(&input, 20, true));
+    }
+
+    #[test]
+    fn test_params() {
+        let mut params = Params::default();
+        params.set("row", "10").unwrap();
+        params.set("max", "20").unwrap();
+        assert_eq!(
+            Params {
+                row: 10,
+                max: 20,
+                parallel: false
+            },
+            params
+        );
+        assert!(matches!(
+            params.set("max", "x"),
+            Err(ParamError::InvalidValue { .. })
+        ));
+        assert!(matches!(
+            params.set("max", "-1"),
+            Err(ParamError::InvalidValue { .. })
+        ));
+        assert_eq!(
+            Err(ParamError::Unknown("y".to_string())),
+            params.set("y", "1")
+        );
+    }
+
+    #[test]
+    fn test_crlf() {
+        let input = parse(&windows_input(include_str!(
+            "../fixtures/day15/example.txt"
+        )))
+        .unwrap();
+        assert_eq!(
+            26,
+            part1_with(
+                &input,
+                &Params {
+                    row: 10,
+                    ..Params::default()
+                }
+            )
         );
-        assert_eq!(Some(56000011), find_beacon_pos::<20>(&input))
     }
 }
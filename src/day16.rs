@@ -1,20 +1,32 @@
+//! Day 16: Proboscidea Volcanium, releasing the most pressure by opening valves in a tunnel network.
+
+use std::any::Any;
 use std::cmp::Reverse;
 use std::collections::VecDeque;
+use std::sync::atomic::{AtomicU32, Ordering};
 
 use aoc_runner_derive::{aoc, aoc_generator};
 use itertools::Itertools;
-use regex::Regex;
+use rayon::prelude::*;
 use rustc_hash::FxHashMap;
 
+use crate::common::input::normalize;
+use crate::common::parser::{parse_all, Parser};
+use crate::common::{parse_lines_with, ParseError};
+use crate::solution::{parse_param, Day, ParamError, PuzzleParams};
+
 const START: [u8; 2] = [b'A', b'A'];
 
-#[aoc_generator(day16)]
-pub fn input_generator(input: &str) -> Vec<Valve> {
+/// Parses the scan and compresses it to the valves with a positive flow rate, followed by the
+/// start valve `AA` as the last one.
+pub fn parse(input: &str) -> Result<Vec<Valve>, ParseError> {
+    let input = &normalize(input, true);
     type NameT = [u8; 2];
-    fn parse_name(name: &str) -> NameT {
-        assert_eq!(name.len(), 2);
-        let b = name.as_bytes();
-        [b[0], b[1]]
+    fn parse_name(p: &mut Parser) -> Result<NameT, ParseError> {
+        let name = p.token("a valve name", |c| c.is_ascii_uppercase())?;
+        name.as_bytes()
+            .try_into()
+            .map_err(|_| p.error_at(name, "expected two letters"))
     }
 
     struct ParseValve {
@@ -24,17 +36,19 @@ pub fn input_generator(input: &str) -> Vec<Valve> {
         connections: Vec<NameT>,
     }
 
-    let r = Regex::new(r"^Valve ([A-Z]{2}) has flow rate=(\d+); tunnels? leads? to valves? ([A-Z]{2}(?:, [A-Z]{2})*)$").unwrap();
-    let mut valves: FxHashMap<NameT, ParseValve> = input
-        .lines()
-        .map(str::trim)
-        .filter(|l| !l.is_empty())
-        .map(|l| {
-            let c = r.captures(l).unwrap();
-            let name = parse_name(&c[1]);
-            let flow = c[2].parse().unwrap();
-            let connections = c[3].split(", ").map(parse_name).collect();
-            (
+    let mut valves: FxHashMap<NameT, ParseValve> = parse_lines_with(input, |l| {
+        parse_all(l, |p| {
+            p.keyword("Valve")?;
+            let name = parse_name(p)?;
+            p.keyword("has flow rate=")?;
+            let flow = p.integer()?;
+            p.keyword(";")?;
+            p.one_of(&[
+                ("tunnels lead to valves", ()),
+                ("tunnel leads to valve", ()),
+            ])?;
+            let connections = p.separated(",", parse_name)?;
+            Ok((
                 name,
                 ParseValve {
                     name,
@@ -42,9 +56,28 @@ pub fn input_generator(input: &str) -> Vec<Valve> {
                     flow,
                     connections,
                 },
-            )
+            ))
         })
-        .collect();
+    })
+    .map_err(|e| e.in_day(16))?;
+
+    if !valves.contains_key(&START) {
+        return Err(ParseError::new("missing start valve AA").in_day(16));
+    }
+    if let Some(to) = valves
+        .values()
+        .flat_map(|v| &v.connections)
+        .find(|to| !valves.contains_key(*to))
+    {
+        return Err(ParseError::new(format!(
+            "tunnel leads to unknown valve {}",
+            String::from_utf8_lossy(to)
+        ))
+        .in_day(16));
+    }
+    if valves.values().filter(|v| v.flow > 0).count() >= 63 {
+        return Err(ParseError::new("too many valves with positive flow rate").in_day(16));
+    }
 
     // order will be [ relevant node 1, ..., relevant node m, AA, ..]
     valves
@@ -78,7 +111,7 @@ pub fn input_generator(input: &str) -> Vec<Valve> {
 
     let sorted_valves: Vec<&ParseValve> =
         valves.values().sorted_by_key(|node| node.index).collect();
-    sorted_valves
+    Ok(sorted_valves
         .iter()
         .filter(|node| node.flow > 0 || node.name == START)
         .map(|node| {
@@ -89,15 +122,28 @@ pub fn input_generator(input: &str) -> Vec<Valve> {
                 connections: dist,
             }
         })
-        .collect()
+        .collect())
 }
 
+/// A valve with its flow rate and the minutes it takes to walk to each valve that has a
+/// positive flow rate.
 #[derive(Debug, Clone)]
 pub struct Valve {
     flow: u32,
     connections: FxHashMap<usize, u32>,
 }
 
+impl Valve {
+    pub fn flow(&self) -> u32 {
+        self.flow
+    }
+
+    /// The distances to the other valves, by their index.
+    pub fn connections(&self) -> &FxHashMap<usize, u32> {
+        &self.connections
+    }
+}
+
 fn gain(time_left: u32, d: u32, flow: u32) -> u32 {
     if d + 1 >= time_left {
         0
@@ -106,8 +152,8 @@ fn gain(time_left: u32, d: u32, flow: u32) -> u32 {
     }
 }
 
-#[aoc(day16 part1)]
-pub fn part1(valves: &[Valve]) -> u32 {
+/// The most pressure you can release alone in `minutes`, starting at the last valve.
+pub fn max_pressure(valves: &[Valve], minutes: u32) -> u32 {
     assert!(!valves.is_empty() && valves.len() <= 63);
     let start = valves.len() - 1;
 
@@ -122,7 +168,7 @@ pub fn part1(valves: &[Valve]) -> u32 {
     let mut max = 0;
     let mut q = VecDeque::new();
     q.push_back(State {
-        time_left: 30,
+        time_left: minutes,
         total_pressure: 0,
         pos: start,
         open: 0,
@@ -164,114 +210,152 @@ pub fn part1(valves: &[Valve]) -> u32 {
     max
 }
 
-#[aoc(day16, part2)]
-pub fn part2(valves: &[Valve]) -> u32 {
-    assert!(!valves.is_empty() && valves.len() <= 63);
+/// The most pressure a single agent can release in `minutes` for every set of valves it can
+/// open in that time.
+fn max_pressure_by_open_valves(valves: &[Valve], minutes: u32) -> FxHashMap<u64, u32> {
     let start = valves.len() - 1;
+    let mut max = FxHashMap::default();
+    let mut stack = vec![(minutes, 0, start, 0u64)];
+    while let Some((time_left, total_pressure, pos, open)) = stack.pop() {
+        let max_for_open = max.entry(open).or_insert(0);
+        *max_for_open = total_pressure.max(*max_for_open);
 
-    #[derive(Debug, Clone, Copy)]
-    struct State {
-        time_left1: u32,
-        time_left2: u32,
-        total_pressure: u32,
-        pos1: usize,
-        pos2: usize,
-        open: u64,
+        for (n, d) in valves[pos]
+            .connections
+            .iter()
+            .filter(|(n, d)| **d + 1 < time_left && (open & (1 << **n)) == 0)
+        {
+            stack.push((
+                time_left - *d - 1,
+                total_pressure + gain(time_left, *d, valves[*n].flow),
+                *n,
+                open | (1 << n),
+            ));
+        }
     }
 
-    let mut max = 0;
-    let mut q = VecDeque::new();
-    q.push_back(State {
-        time_left1: 26,
-        time_left2: 26,
-        total_pressure: 0,
-        pos1: start,
-        pos2: start,
-        open: 0,
-    });
+    max
+}
 
-    while let Some(state) = q.pop_front() {
-        if state.total_pressure > max {
-            max = state.total_pressure; // new max found
-        }
+/// The most pressure you and an elephant can release together in `minutes`.
+///
+/// Pairs up the valve sets on all cores if `parallel` is set, with the same result as the
+/// sequential search.
+pub fn max_pressure_with_elephant(valves: &[Valve], minutes: u32, parallel: bool) -> u32 {
+    assert!(!valves.is_empty() && valves.len() <= 63);
 
-        if state.time_left1 <= 1 && state.time_left2 <= 1 {
-            continue;
+    // you and the elephant open disjoint sets of valves, so pair up the best of those sets
+    let by_open_valves = max_pressure_by_open_valves(valves, minutes)
+        .into_iter()
+        .sorted_by_key(|&(_, pressure)| Reverse(pressure))
+        .collect_vec();
+    let max = AtomicU32::new(0);
+    let best_pair = |i: usize| {
+        let (open1, pressure1) = by_open_valves[i];
+        // every later pair releases at most twice as much as its first set
+        if 2 * pressure1 <= max.load(Ordering::Relaxed) {
+            return;
         }
 
-        // branch pruning, can use idx because valves is sorted by flow rate
-        let mut idx = 0;
-        let max_pressure_to_get: u32 = valves
+        if let Some((_, pressure2)) = by_open_valves[i..]
             .iter()
-            .enumerate()
-            .filter(|(n, v)| v.flow > 0 && state.open & (1 << *n) == 0)
-            .map(|(n, v)| {
-                let d1 = valves[state.pos1].connections[&n];
-                let d2 = valves[state.pos2].connections[&n];
-                idx += 1;
-                gain(
-                    if idx <= state.time_left1 {
-                        state.time_left1 - (idx - 1)
-                    } else {
-                        0
-                    },
-                    d1,
-                    v.flow,
-                )
-                .max(gain(
-                    if idx <= state.time_left2 {
-                        state.time_left2 - (idx - 1)
-                    } else {
-                        0
-                    },
-                    d2,
-                    v.flow,
-                ))
-            })
-            .sum();
-        if state.total_pressure + max_pressure_to_get <= max {
-            continue;
+            .find(|(open2, _)| open1 & open2 == 0)
+        {
+            max.fetch_max(pressure1 + pressure2, Ordering::Relaxed);
         }
+    };
 
-        // for pos1: iterate over all unopened valves you can travel to and open in time
-        for (n, d) in valves[state.pos1].connections.iter().filter(|(n, d)| {
-            **d + 1 < state.time_left1
-                && (state.open & (1 << **n)) == 0
-                && **d <= valves[state.pos2].connections[*n]
-        }) {
-            let mut new_state = state;
-            new_state.time_left1 -= *d + 1;
-            new_state.total_pressure += gain(state.time_left1, *d, valves[*n].flow);
-            new_state.pos1 = *n;
-            new_state.open |= 1 << n;
-            q.push_back(new_state);
+    if parallel {
+        (0..by_open_valves.len())
+            .into_par_iter()
+            .for_each(best_pair);
+    } else {
+        (0..by_open_valves.len()).for_each(best_pair);
+    }
+    max.into_inner()
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Params {
+    pub part1_minutes: u32,
+    pub part2_minutes: u32,
+    /// pair up the valve sets of part 2 on all cores
+    pub parallel: bool,
+}
+
+impl Default for Params {
+    fn default() -> Self {
+        Self {
+            part1_minutes: 30,
+            part2_minutes: 26,
+            parallel: false,
         }
+    }
+}
 
-        if state.pos1 != state.pos2 {
-            // for pos2: iterate over all unopened valves you can travel to and open in time
-            for (n, d) in valves[state.pos2].connections.iter().filter(|(n, d)| {
-                **d + 1 < state.time_left2
-                    && (state.open & (1 << **n)) == 0
-                    && **d <= valves[state.pos1].connections[*n]
-            }) {
-                let mut new_state = state;
-                new_state.time_left2 -= *d + 1;
-                new_state.total_pressure += gain(state.time_left2, *d, valves[*n].flow);
-                new_state.pos2 = *n;
-                new_state.open |= 1 << n;
-                q.push_back(new_state);
-            }
+impl PuzzleParams for Params {
+    fn set(&mut self, key: &str, value: &str) -> Result<(), ParamError> {
+        match key {
+            "part1_minutes" => self.part1_minutes = parse_param(key, value)?,
+            "part2_minutes" => self.part2_minutes = parse_param(key, value)?,
+            "parallel" => self.parallel = parse_param(key, value)?,
+            _ => return Err(ParamError::Unknown(key.to_string())),
         }
+        Ok(())
     }
 
-    max
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+pub fn part1_with(valves: &[Valve], params: &Params) -> u32 {
+    max_pressure(valves, params.part1_minutes)
+}
+
+pub fn part2_with(valves: &[Valve], params: &Params) -> u32 {
+    max_pressure_with_elephant(valves, params.part2_minutes, params.parallel)
 }
 
+pub fn solve_part1(valves: &[Valve]) -> u32 {
+    part1_with(valves, &Params::default())
+}
+
+pub fn solve_part2(valves: &[Valve]) -> u32 {
+    part2_with(valves, &Params::default())
+}
+
+#[doc(hidden)]
+#[aoc_generator(day16)]
+pub fn input_generator(input: &str) -> Result<Vec<Valve>, ParseError> {
+    parse(input)
+}
+
+#[doc(hidden)]
+#[aoc(day16 part1)]
+pub fn part1(valves: &[Valve]) -> u32 {
+    solve_part1(valves)
+}
+
+#[doc(hidden)]
+#[aoc(day16, part2)]
+pub fn part2(valves: &[Valve]) -> u32 {
+    solve_part2(valves)
+}
+
+pub(crate) static SOLUTION: Day<Vec<Valve>, Params> = Day {
+    day: 16,
+    parse,
+    part1: |input, params| Ok(part1_with(input, params).into()),
+    part2: Some(|input, params| Ok(part2_with(input, params).into())),
+};
+
 #[cfg(test)]
 mod tests {
     use pretty_assertions::assert_eq;
 
     use super::*;
+    use crate::common::input::windows_input;
 
     #[test]
     fn test_1() {
@@ -287,7 +371,8 @@ Valve HH has flow rate=22; tunnel leads to valve GG
 Valve II has flow rate=0; tunnels lead to valves AA, JJ
 Valve JJ has flow rate=21; tunnel leads to valve II
 ",
-        );
+        )
+        .unwrap();
         assert_eq!(1651, part1(&input))
     }
 
@@ -305,7 +390,22 @@ Valve HH has flow rate=22; tunnel leads to valve GG
 Valve II has flow rate=0; tunnels lead to valves AA, JJ
 Valve JJ has flow rate=21; tunnel leads to valve II
 ",
-        );
-        assert_eq!(1707, part2(&input))
+        )
+        .unwrap();
+        assert_eq!(1707, part2(&input));
+        let params = Params {
+            parallel: true,
+            ..Params::default()
+        };
+        assert_eq!(1707, part2_with(&input, &params))
+    }
+
+    #[test]
+    fn test_crlf() {
+        let input = parse(&windows_input(include_str!(
+            "../fixtures/day16/example.txt"
+        )))
+        .unwrap();
+        assert_eq!(1651, solve_part1(&input));
     }
 }
@@ -1,8 +1,14 @@
-use std::collections::hash_map::Entry;
+//! Day 17: Pyroclastic Flow, the height of a tower of falling rocks pushed by jets of gas.
+
+use std::any::Any;
 
 use aoc_runner_derive::{aoc, aoc_generator};
-use itertools::Itertools;
-use rustc_hash::{FxHashMap, FxHashSet};
+use rustc_hash::FxHashSet;
+
+use crate::common::input::normalize;
+use crate::common::{cycle, Direction, ParseError};
+use crate::solution::{parse_param, Day, ParamError, PuzzleParams};
+use crate::visualize::{notify, Frame, Observer, Palette, Visualize};
 
 const BLOCKS: [[(u64, u64); 7]; 5] = [
     [(0, 0), (0, 0), (0, 1), (0, 1), (0, 1), (0, 1), (0, 0)],
@@ -12,30 +18,28 @@ const BLOCKS: [[(u64, u64); 7]; 5] = [
     [(0, 0), (0, 0), (0, 2), (0, 2), (0, 0), (0, 0), (0, 0)],
 ];
 
-#[derive(Debug, Clone, Copy)]
-pub enum Dir {
-    Left,
-    Right,
+fn parse_jet(c: char) -> Result<Direction, ParseError> {
+    match Direction::try_from(c) {
+        Ok(dir) if dir.is_horizontal() && dir.arrow() == c => Ok(dir),
+        _ => Err(ParseError::new(format!(
+            "expected '<' or '>' but got '{c}'"
+        ))),
+    }
 }
 
-impl TryFrom<u8> for Dir {
-    type Error = ();
-
-    fn try_from(value: u8) -> Result<Self, Self::Error> {
-        match value {
-            b'<' => Ok(Dir::Left),
-            b'>' => Ok(Dir::Right),
-            _ => Err(()),
-        }
+/// Parses the jet pattern of `<` and `>`.
+pub fn parse(input: &str) -> Result<Vec<Direction>, ParseError> {
+    let input = &normalize(input, true);
+    let jets = input.trim();
+    if jets.is_empty() {
+        return Err(ParseError::new("expected at least one jet").in_day(17));
     }
-}
 
-#[aoc_generator(day17)]
-pub fn input_generator(input: &str) -> Vec<Dir> {
-    input
-        .trim()
-        .bytes()
-        .map(|b| Dir::try_from(b).unwrap())
+    jets.chars()
+        .enumerate()
+        .map(|(i, c)| {
+            parse_jet(c).map_err(|e| e.at_column(i + 1).located_in(input, jets).in_day(17))
+        })
         .collect()
 }
 
@@ -83,60 +87,78 @@ fn calculate_normalized_surface(levels: &[FxHashSet<u64>]) -> Vec<(u64, u64)> {
     all_pos.into_iter().map(|(x, y)| (x, y - min_y)).collect()
 }
 
-fn tetris<const N: usize>(jet_dirs: &[Dir]) -> u64 {
-    let mut cache = FxHashMap::default();
-
-    let mut levels: Vec<FxHashSet<u64>> = vec![FxHashSet::default(); WIDTH];
-    levels.iter_mut().for_each(|x| {
-        x.insert(0);
-    });
-
-    let mut saved_y_growth = 0;
-    let mut jet_index = 0;
-    let mut i = 0;
-    loop {
-        let block_index = i % BLOCKS.len();
-
-        let max_y = *levels.iter().flatten().max().unwrap();
-        let cache_key = (
-            block_index,
-            jet_index,
-            calculate_normalized_surface(&levels),
-        );
-        let entry = cache.entry(cache_key);
-        match entry {
-            Entry::Occupied(e) => {
-                let (first_i, first_max_y) = e.get();
-                let cycle_length = i - first_i;
-                let y_growth = max_y - first_max_y;
-                let periods = (N - i) / cycle_length;
-                i += periods * cycle_length;
-                saved_y_growth += (periods as u64) * y_growth;
-            }
-            Entry::Vacant(e) => {
-                e.insert((i, max_y));
-            }
+/// The tower of rocks that came to rest so far.
+#[derive(Debug, Clone)]
+pub struct Chamber {
+    levels: Vec<FxHashSet<u64>>,
+    rocks: usize,
+    jet_index: usize,
+    /// the block, its horizontal offset and its height for the rock that came to rest last
+    rock: Option<(usize, isize, u64)>,
+}
+
+impl Default for Chamber {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Chamber {
+    /// The empty chamber, with the first rock and the first jet up next.
+    pub fn new() -> Self {
+        let mut levels: Vec<FxHashSet<u64>> = vec![FxHashSet::default(); WIDTH];
+        levels.iter_mut().for_each(|x| {
+            x.insert(0);
+        });
+
+        Self {
+            levels,
+            rocks: 0,
+            jet_index: 0,
+            rock: None,
         }
+    }
+
+    /// The height of the tower.
+    pub fn height(&self) -> u64 {
+        *self.levels.iter().flatten().max().unwrap()
+    }
 
+    /// Everything that decides how the tower grows from here on.
+    fn key(&self) -> (usize, usize, Vec<(u64, u64)>) {
+        (
+            self.rocks % BLOCKS.len(),
+            self.jet_index,
+            calculate_normalized_surface(&self.levels),
+        )
+    }
+
+    /// The number of rocks that came to rest.
+    pub fn rocks(&self) -> usize {
+        self.rocks
+    }
+
+    /// Lets the next rock fall until it comes to rest, pushed by `jet_dirs` which have to be
+    /// the same for every call.
+    pub fn drop_rock(&mut self, jet_dirs: &[Direction]) {
+        let block_index = self.rocks % BLOCKS.len();
         let block = &BLOCKS[block_index];
-        let _print_levels = levels.iter().map(|x| x.iter().max().unwrap()).collect_vec();
+        let levels = &mut self.levels;
 
-        let mut block_y = max_y + 4;
+        let mut block_y = *levels.iter().flatten().max().unwrap() + 4;
         let mut offset_x = 0isize;
         'fall: loop {
-            let _d = jet_dirs[jet_index];
             let horizontal_check = |dx| {
-                for bx in 0..WIDTH {
+                for (bx, &(y_start, height)) in block.iter().enumerate() {
                     let x = bx as isize + (offset_x + dx);
                     if x < 0 || x as usize >= WIDTH {
-                        if block[bx].1 != 0 {
+                        if height != 0 {
                             return 0;
                         }
 
                         continue;
                     }
                     let x = x as usize;
-                    let (y_start, height) = block[bx];
                     if height > 0
                         && ((y_start + block_y)..(y_start + block_y + height))
                             .any(|y| levels[x].contains(&y))
@@ -148,12 +170,9 @@ fn tetris<const N: usize>(jet_dirs: &[Dir]) -> u64 {
                 dx
             };
 
-            offset_x += match jet_dirs[jet_index] {
-                Dir::Left => horizontal_check(-1),
-                Dir::Right => horizontal_check(1),
-            };
+            offset_x += horizontal_check(jet_dirs[self.jet_index].vec().x as isize);
 
-            jet_index = (jet_index + 1) % jet_dirs.len();
+            self.jet_index = (self.jet_index + 1) % jet_dirs.len();
 
             for bx in 0..WIDTH {
                 let x = bx as isize + offset_x;
@@ -166,7 +185,7 @@ fn tetris<const N: usize>(jet_dirs: &[Dir]) -> u64 {
                 let (y_start, height) = block[bx];
                 let current_y = y_start + block_y;
                 if height > 0 && levels[x].contains(&(current_y - 1)) {
-                    for x in 0..WIDTH {
+                    for (x, level) in levels.iter_mut().enumerate() {
                         let bx = x as isize - offset_x;
                         if bx < 0 || bx as usize >= WIDTH {
                             continue;
@@ -175,7 +194,7 @@ fn tetris<const N: usize>(jet_dirs: &[Dir]) -> u64 {
 
                         let (y_start, height) = block[bx];
                         if height > 0 {
-                            levels[x].extend((y_start + block_y)..(y_start + block_y + height));
+                            level.extend((y_start + block_y)..(y_start + block_y + height));
                         }
                     }
 
@@ -186,37 +205,166 @@ fn tetris<const N: usize>(jet_dirs: &[Dir]) -> u64 {
             block_y -= 1;
         }
 
-        i += 1;
-        if i >= N {
-            break;
+        self.rocks += 1;
+        self.rock = Some((block_index, offset_x, block_y));
+    }
+}
+
+const PALETTE: Palette = Palette(&[
+    ('#', [140, 140, 160]),
+    ('@', [255, 140, 0]),
+    ('|', [90, 90, 90]),
+    ('+', [90, 90, 90]),
+    ('-', [90, 90, 90]),
+    ('.', [10, 10, 20]),
+]);
+
+impl Visualize for Chamber {
+    fn frame(&self) -> Frame {
+        // the number of rows shown below the top of the tower
+        const ROWS: u64 = 40;
+
+        let max_y = self.height();
+        let top = max_y.max(ROWS - 1);
+        let mut frame = Frame::new(WIDTH + 2, ROWS as usize, '.', PALETTE);
+        for row in 0..ROWS {
+            let y = top - row;
+            frame.set(0, row as usize, if y == 0 { '+' } else { '|' });
+            frame.set(WIDTH + 1, row as usize, if y == 0 { '+' } else { '|' });
+        }
+        let mut set = |x: usize, y: u64, c| {
+            if y <= top && top - y < ROWS {
+                frame.set(x + 1, (top - y) as usize, if y == 0 { '-' } else { c });
+            }
+        };
+        for (x, level) in self.levels.iter().enumerate() {
+            level.iter().for_each(|&y| set(x, y, '#'));
+        }
+        if let Some((block_index, offset_x, block_y)) = self.rock {
+            for (bx, &(y_start, height)) in BLOCKS[block_index].iter().enumerate() {
+                let x = bx as isize + offset_x;
+                if height > 0 && (0..WIDTH as isize).contains(&x) {
+                    (y_start + block_y..y_start + block_y + height)
+                        .for_each(|y| set(x as usize, y, '@'));
+                }
+            }
+        }
+
+        frame
+    }
+}
+
+/// The height of the tower after `n` rocks. Repeating surfaces of the tower are skipped
+/// with [`cycle::extrapolate`], so `n` can be huge.
+pub fn tetris(jet_dirs: &[Direction], n: usize, mut observer: Option<&mut dyn Observer>) -> u64 {
+    cycle::extrapolate(
+        Chamber::new(),
+        n,
+        |chamber| {
+            chamber.drop_rock(jet_dirs);
+            notify(&mut observer, chamber);
+        },
+        Chamber::key,
+        Chamber::height,
+    )
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Params {
+    pub part1_rocks: usize,
+    pub part2_rocks: usize,
+}
+
+impl Default for Params {
+    fn default() -> Self {
+        Self {
+            part1_rocks: 2022,
+            part2_rocks: 1000000000000,
         }
     }
+}
 
-    saved_y_growth + *levels.iter().flatten().max().unwrap()
+impl PuzzleParams for Params {
+    fn set(&mut self, key: &str, value: &str) -> Result<(), ParamError> {
+        match key {
+            "part1_rocks" => self.part1_rocks = parse_param(key, value)?,
+            "part2_rocks" => self.part2_rocks = parse_param(key, value)?,
+            _ => return Err(ParamError::Unknown(key.to_string())),
+        }
+        Ok(())
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+pub fn part1_with(jet_dirs: &[Direction], params: &Params) -> u64 {
+    tetris(jet_dirs, params.part1_rocks, None)
+}
+
+pub fn part2_with(jet_dirs: &[Direction], params: &Params) -> u64 {
+    tetris(jet_dirs, params.part2_rocks, None)
 }
 
+/// Shows the top of the chamber after each rock came to rest, with default params.
+pub fn visualize(jet_dirs: &[Direction], part: u32, observer: &mut dyn Observer) {
+    let params = Params::default();
+    let rocks = if part == 1 {
+        params.part1_rocks
+    } else {
+        params.part2_rocks
+    };
+    tetris(jet_dirs, rocks, Some(observer));
+}
+
+pub fn solve_part1(jet_dirs: &[Direction]) -> u64 {
+    part1_with(jet_dirs, &Params::default())
+}
+
+pub fn solve_part2(jet_dirs: &[Direction]) -> u64 {
+    part2_with(jet_dirs, &Params::default())
+}
+
+#[doc(hidden)]
+#[aoc_generator(day17)]
+pub fn input_generator(input: &str) -> Result<Vec<Direction>, ParseError> {
+    parse(input)
+}
+
+#[doc(hidden)]
 #[aoc(day17 part1)]
-pub fn part1(jet_dirs: &[Dir]) -> u64 {
-    tetris::<2022>(jet_dirs)
+pub fn part1(jet_dirs: &[Direction]) -> u64 {
+    solve_part1(jet_dirs)
 }
 
+#[doc(hidden)]
 #[aoc(day17, part2)]
-pub fn part2(jet_dirs: &[Dir]) -> u64 {
-    tetris::<1000000000000>(jet_dirs)
+pub fn part2(jet_dirs: &[Direction]) -> u64 {
+    solve_part2(jet_dirs)
 }
 
+pub(crate) static SOLUTION: Day<Vec<Direction>, Params> = Day {
+    day: 17,
+    parse,
+    part1: |input, params| Ok(part1_with(input, params).into()),
+    part2: Some(|input, params| Ok(part2_with(input, params).into())),
+};
+
 #[cfg(test)]
 mod tests {
     use pretty_assertions::assert_eq;
 
     use super::*;
+    use crate::common::input::windows_input;
 
     #[test]
     fn test_1() {
         let input = input_generator(
             r">>><<><>><<<>><>>><<<>>><<<><<<>><>><<>>
 ",
-        );
+        )
+        .unwrap();
         assert_eq!(3068, part1(&input))
     }
 
@@ -225,7 +373,36 @@ mod tests {
         let input = input_generator(
             r">>><<><>><<<>><>>><<<>>><<<><<<>><>><<>>
 ",
-        );
+        )
+        .unwrap();
         assert_eq!(1514285714288, part2(&input))
     }
+
+    #[test]
+    fn test_chamber() {
+        let jet_dirs = input_generator(">>><<><>><<<>><>>><<<>>><<<><<<>><>><<>>").unwrap();
+        let mut chamber = Chamber::new();
+        let heights = (0..10)
+            .map(|_| {
+                chamber.drop_rock(&jet_dirs);
+                chamber.height()
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(vec![1, 4, 6, 7, 9, 10, 13, 15, 17, 17], heights);
+
+        // stop as soon as the tower is 100 units high
+        while chamber.height() < 100 {
+            chamber.drop_rock(&jet_dirs);
+        }
+        assert_eq!(chamber.height(), tetris(&jet_dirs, chamber.rocks(), None));
+    }
+
+    #[test]
+    fn test_crlf() {
+        let input = parse(&windows_input(include_str!(
+            "../fixtures/day17/example.txt"
+        )))
+        .unwrap();
+        assert_eq!(3068, solve_part1(&input));
+    }
 }
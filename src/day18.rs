@@ -1,114 +1,75 @@
-use std::collections::VecDeque;
-use std::str::FromStr;
+//! Day 18: Boiling Boulders, the surface area of a droplet made of unit cubes.
 
 use aoc_runner_derive::{aoc, aoc_generator};
 use itertools::Itertools;
-use rustc_hash::FxHashSet;
-
-const NEIGHBORS: [Cube; 6] = [
-    Cube { x: 1, y: 0, z: 0 },
-    Cube { x: -1, y: 0, z: 0 },
-    Cube { x: 0, y: 1, z: 0 },
-    Cube { x: 0, y: -1, z: 0 },
-    Cube { x: 0, y: 0, z: 1 },
-    Cube { x: 0, y: 0, z: -1 },
-];
-
-#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
-pub struct Cube {
-    x: i32,
-    y: i32,
-    z: i32,
-}
 
-impl Cube {
-    pub fn neighbors(&self) -> impl Iterator<Item = Cube> + '_ {
-        NEIGHBORS.iter().map(|d| Cube {
-            x: self.x + d.x,
-            y: self.y + d.y,
-            z: self.z + d.z,
-        })
+use crate::common::input::normalize;
+use crate::common::{parse_lines_with, parse_located, ParseError, SparseGrid3, Vec3i};
+use crate::solution::Day;
+
+fn parse_cube(s: &str) -> Result<(Vec3i, ()), ParseError> {
+    if let Some((x, y, z)) = s.splitn(3, ',').collect_tuple() {
+        let pos = Vec3i::new(
+            parse_located(s, x)?,
+            parse_located(s, y)?,
+            parse_located(s, z)?,
+        );
+        Ok((pos, ()))
+    } else {
+        Err(ParseError::new("expected cube 'x,y,z'"))
     }
 }
 
-impl FromStr for Cube {
-    type Err = ();
-
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        if let Some((x, y, z)) = s.splitn(3, ',').collect_tuple() {
-            Ok(Cube {
-                x: x.parse().map_err(|_| ())?,
-                y: y.parse().map_err(|_| ())?,
-                z: z.parse().map_err(|_| ())?,
-            })
-        } else {
-            Err(())
-        }
+/// Parses the positions of the cubes, the surface areas are computed by [`SparseGrid3`].
+pub fn parse(input: &str) -> Result<SparseGrid3<()>, ParseError> {
+    let input = &normalize(input, true);
+    let cubes: SparseGrid3<()> = parse_lines_with(input, parse_cube).map_err(|e| e.in_day(18))?;
+    if cubes.is_empty() {
+        return Err(ParseError::new("expected at least one cube").in_day(18));
     }
+
+    Ok(cubes)
+}
+
+pub fn solve_part1(cubes: &SparseGrid3<()>) -> usize {
+    cubes.surface_area()
+}
+
+pub fn solve_part2(cubes: &SparseGrid3<()>) -> usize {
+    cubes.exterior_surface_area()
 }
 
+#[doc(hidden)]
 #[aoc_generator(day18)]
-pub fn input_generator(input: &str) -> FxHashSet<Cube> {
-    input
-        .lines()
-        .map(str::trim)
-        .filter(|l| !l.is_empty())
-        .map(|l| l.parse().unwrap())
-        .collect()
+pub fn input_generator(input: &str) -> Result<SparseGrid3<()>, ParseError> {
+    parse(input)
 }
 
+#[doc(hidden)]
 #[aoc(day18 part1)]
-pub fn part1(cubes: &FxHashSet<Cube>) -> usize {
-    cubes
-        .iter()
-        .map(|c| c.neighbors().filter(|n| !cubes.contains(n)).count())
-        .sum()
+pub fn part1(cubes: &SparseGrid3<()>) -> usize {
+    solve_part1(cubes)
 }
 
+#[doc(hidden)]
 #[aoc(day18, part2)]
-pub fn part2(cubes: &FxHashSet<Cube>) -> usize {
-    let min_x = cubes.iter().map(|c| c.x).min().unwrap() - 1;
-    let max_x = cubes.iter().map(|c| c.x).max().unwrap() + 1;
-    let min_y = cubes.iter().map(|c| c.y).min().unwrap() - 1;
-    let max_y = cubes.iter().map(|c| c.y).max().unwrap() + 1;
-    let min_z = cubes.iter().map(|c| c.z).min().unwrap() - 1;
-    let max_z = cubes.iter().map(|c| c.z).max().unwrap() + 1;
-
-    let mut outside_air = FxHashSet::default();
-    let mut q = VecDeque::new();
-    q.push_back(Cube {
-        x: min_x,
-        y: min_y,
-        z: min_z,
-    });
-    while let Some(c) = q.pop_front() {
-        if outside_air.insert(c) {
-            q.extend(
-                c.neighbors()
-                    .filter(|n| {
-                        n.x >= min_x
-                            && n.x <= max_x
-                            && n.y >= min_y
-                            && n.y <= max_y
-                            && n.z >= min_z
-                            && n.z <= max_z
-                    })
-                    .filter(|n| !cubes.contains(n)),
-            )
-        }
-    }
-
-    cubes
-        .iter()
-        .map(|c| c.neighbors().filter(|n| outside_air.contains(n)).count())
-        .sum()
+pub fn part2(cubes: &SparseGrid3<()>) -> usize {
+    solve_part2(cubes)
 }
 
+pub(crate) static SOLUTION: Day<SparseGrid3<()>> = Day {
+    day: 18,
+    parse,
+    part1: |input, _| Ok(solve_part1(input).into()),
+    part2: Some(|input, _| Ok(solve_part2(input).into())),
+};
+
 #[cfg(test)]
 mod tests {
     use pretty_assertions::assert_eq;
 
     use super::*;
+    use crate::common::input::windows_input;
 
     #[test]
     fn test_1() {
@@ -127,7 +88,8 @@ mod tests {
 2,1,5
 2,3,5
 ",
-        );
+        )
+        .unwrap();
         assert_eq!(64, part1(&input))
     }
 
@@ -148,7 +110,17 @@ mod tests {
 2,1,5
 2,3,5
 ",
-        );
+        )
+        .unwrap();
         assert_eq!(58, part2(&input))
     }
+
+    #[test]
+    fn test_crlf() {
+        let input = parse(&windows_input(include_str!(
+            "../fixtures/day18/example.txt"
+        )))
+        .unwrap();
+        assert_eq!(64, solve_part1(&input));
+    }
 }
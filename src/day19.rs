@@ -1,38 +1,54 @@
-use std::str::FromStr;
+//! Day 19: Not Enough Minerals, building robots from blueprints to crack the most geodes.
+
+use std::any::Any;
 
 use aoc_runner_derive::{aoc, aoc_generator};
-use lazy_static::lazy_static;
 use num::Integer;
-use regex::Regex;
+use rayon::prelude::*;
+
+use crate::common::input::normalize;
+use crate::common::parser::{parse_all, Parser};
+use crate::common::ParseError;
+use crate::solution::{parse_param, Day, ParamError, PuzzleParams};
 
+/// The costs of the four kinds of robot, in the resources of the previous kinds.
 #[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
 pub struct Blueprint {
-    index: u32,
-    ore_robot_ore_cost: u32,
-    clay_robot_ore_cost: u32,
-    obsidian_robot_ore_cost: u32,
-    obsidian_robot_clay_cost: u32,
-    geode_robot_ore_cost: u32,
-    geode_robot_obsidian_cost: u32,
+    pub index: u32,
+    pub ore_robot_ore_cost: u32,
+    pub clay_robot_ore_cost: u32,
+    pub obsidian_robot_ore_cost: u32,
+    pub obsidian_robot_clay_cost: u32,
+    pub geode_robot_ore_cost: u32,
+    pub geode_robot_obsidian_cost: u32,
 }
 
-impl FromStr for Blueprint {
-    type Err = ();
-
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        lazy_static! {
-            static ref RE: Regex = Regex::new(r"^Blueprint (\d+): Each ore robot costs (\d+) ore. Each clay robot costs (\d+) ore. Each obsidian robot costs (\d+) ore and (\d+) clay. Each geode robot costs (\d+) ore and (\d+) obsidian.$").unwrap();
-        }
-
-        let c = RE.captures(s).unwrap();
+impl Blueprint {
+    /// Parses a blueprint, which may span several lines.
+    pub fn parse(p: &mut Parser) -> Result<Self, ParseError> {
+        p.keyword("Blueprint")?;
+        let index = p.integer()?;
+        p.keyword(": Each ore robot costs")?;
+        let ore_robot_ore_cost = p.integer()?;
+        p.keyword("ore. Each clay robot costs")?;
+        let clay_robot_ore_cost = p.integer()?;
+        p.keyword("ore. Each obsidian robot costs")?;
+        let obsidian_robot_ore_cost = p.integer()?;
+        p.keyword("ore and")?;
+        let obsidian_robot_clay_cost = p.integer()?;
+        p.keyword("clay. Each geode robot costs")?;
+        let geode_robot_ore_cost = p.integer()?;
+        p.keyword("ore and")?;
+        let geode_robot_obsidian_cost = p.integer()?;
+        p.keyword("obsidian.")?;
         Ok(Blueprint {
-            index: c[1].parse().map_err(|_| ())?,
-            ore_robot_ore_cost: c[2].parse().map_err(|_| ())?,
-            clay_robot_ore_cost: c[3].parse().map_err(|_| ())?,
-            obsidian_robot_ore_cost: c[4].parse().map_err(|_| ())?,
-            obsidian_robot_clay_cost: c[5].parse().map_err(|_| ())?,
-            geode_robot_ore_cost: c[6].parse().map_err(|_| ())?,
-            geode_robot_obsidian_cost: c[7].parse().map_err(|_| ())?,
+            index,
+            ore_robot_ore_cost,
+            clay_robot_ore_cost,
+            obsidian_robot_ore_cost,
+            obsidian_robot_clay_cost,
+            geode_robot_ore_cost,
+            geode_robot_obsidian_cost,
         })
     }
 }
@@ -189,13 +205,16 @@ impl State {
     }
 }
 
-fn max_geode_count(time_left: u32, blueprint: &Blueprint) -> u32 {
+/// The most geodes one ore robot can crack in `time_left` minutes with `blueprint`.
+pub fn max_geode_count(time_left: u32, blueprint: &Blueprint) -> u32 {
     let mut max_geode_count = 0;
     let mut q = vec![];
     q.push(State::initial(time_left));
     while let Some(s) = q.pop() {
-        if s.geode_count > max_geode_count {
-            max_geode_count = s.geode_count;
+        // the geode robots keep cracking geodes even if no other robot can be built in time
+        let geode_count = s.geode_count + s.geode_robot_count * s.time_left;
+        if geode_count > max_geode_count {
+            max_geode_count = geode_count;
         }
 
         if s.geode_count_upper_bound() <= max_geode_count {
@@ -208,38 +227,109 @@ fn max_geode_count(time_left: u32, blueprint: &Blueprint) -> u32 {
     max_geode_count
 }
 
+pub fn parse(input: &str) -> Result<Vec<Blueprint>, ParseError> {
+    let input = &normalize(input, true);
+    // blueprints may be wrapped over several lines like in the puzzle description
+    parse_all(input, |p| p.until_end(Blueprint::parse)).map_err(|e| e.in_day(19))
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Params {
+    pub part1_minutes: u32,
+    pub part2_minutes: u32,
+    /// number of blueprints that survived the elephants in part 2
+    pub part2_blueprints: usize,
+    /// search the blueprints on all cores
+    pub parallel: bool,
+}
+
+impl Default for Params {
+    fn default() -> Self {
+        Self {
+            part1_minutes: 24,
+            part2_minutes: 32,
+            part2_blueprints: 3,
+            parallel: false,
+        }
+    }
+}
+
+impl PuzzleParams for Params {
+    fn set(&mut self, key: &str, value: &str) -> Result<(), ParamError> {
+        match key {
+            "part1_minutes" => self.part1_minutes = parse_param(key, value)?,
+            "part2_minutes" => self.part2_minutes = parse_param(key, value)?,
+            "part2_blueprints" => self.part2_blueprints = parse_param(key, value)?,
+            "parallel" => self.parallel = parse_param(key, value)?,
+            _ => return Err(ParamError::Unknown(key.to_string())),
+        }
+        Ok(())
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+pub fn part1_with(input: &[Blueprint], params: &Params) -> u32 {
+    let quality_level =
+        |blueprint: &Blueprint| blueprint.index * max_geode_count(params.part1_minutes, blueprint);
+    if params.parallel {
+        input.par_iter().map(quality_level).sum()
+    } else {
+        input.iter().map(quality_level).sum()
+    }
+}
+
+pub fn part2_with(input: &[Blueprint], params: &Params) -> u32 {
+    let input = &input[..params.part2_blueprints.min(input.len())];
+    let geode_count = |blueprint: &Blueprint| max_geode_count(params.part2_minutes, blueprint);
+    if params.parallel {
+        input.par_iter().map(geode_count).product()
+    } else {
+        input.iter().map(geode_count).product()
+    }
+}
+
+pub fn solve_part1(input: &[Blueprint]) -> u32 {
+    part1_with(input, &Params::default())
+}
+
+pub fn solve_part2(input: &[Blueprint]) -> u32 {
+    part2_with(input, &Params::default())
+}
+
+#[doc(hidden)]
 #[aoc_generator(day19)]
-pub fn input_generator(input: &str) -> Vec<Blueprint> {
-    input
-        .lines()
-        .map(str::trim)
-        .filter(|l| !l.is_empty())
-        .map(|l| l.parse().unwrap())
-        .collect()
+pub fn input_generator(input: &str) -> Result<Vec<Blueprint>, ParseError> {
+    parse(input)
 }
 
+#[doc(hidden)]
 #[aoc(day19, part1)]
 pub fn part1(input: &[Blueprint]) -> u32 {
-    input
-        .iter()
-        .map(|blueprint| (blueprint.index as u32) * max_geode_count(24, blueprint))
-        .sum()
+    solve_part1(input)
 }
 
+#[doc(hidden)]
 #[aoc(day19, part2)]
 pub fn part2(input: &[Blueprint]) -> u32 {
-    input
-        .iter()
-        .take(3)
-        .map(|blueprint| max_geode_count(32, blueprint))
-        .product()
+    solve_part2(input)
 }
 
+pub(crate) static SOLUTION: Day<Vec<Blueprint>, Params> = Day {
+    day: 19,
+    parse,
+    part1: |input, params| Ok(part1_with(input, params).into()),
+    part2: Some(|input, params| Ok(part2_with(input, params).into())),
+};
+
 #[cfg(test)]
 mod tests {
     use pretty_assertions::assert_eq;
 
     use super::*;
+    use crate::common::input::windows_input;
 
     #[test]
     fn test_1() {
@@ -247,8 +337,14 @@ mod tests {
             r"Blueprint 1: Each ore robot costs 4 ore. Each clay robot costs 2 ore. Each obsidian robot costs 3 ore and 14 clay. Each geode robot costs 2 ore and 7 obsidian.
 Blueprint 2: Each ore robot costs 2 ore. Each clay robot costs 3 ore. Each obsidian robot costs 3 ore and 8 clay. Each geode robot costs 3 ore and 12 obsidian.
 ",
-        );
-        assert_eq!(33, part1(&input))
+        )
+        .unwrap();
+        assert_eq!(33, part1(&input));
+        let params = Params {
+            parallel: true,
+            ..Params::default()
+        };
+        assert_eq!(33, part1_with(&input, &params))
     }
 
     #[test]
@@ -257,7 +353,17 @@ Blueprint 2: Each ore robot costs 2 ore. Each clay robot costs 3 ore. Each obsid
             r"Blueprint 1: Each ore robot costs 4 ore. Each clay robot costs 2 ore. Each obsidian robot costs 3 ore and 14 clay. Each geode robot costs 2 ore and 7 obsidian.
 Blueprint 2: Each ore robot costs 2 ore. Each clay robot costs 3 ore. Each obsidian robot costs 3 ore and 8 clay. Each geode robot costs 3 ore and 12 obsidian.
 ",
-        );
+        )
+        .unwrap();
         assert_eq!(3472, part2(&input))
     }
+
+    #[test]
+    fn test_crlf() {
+        let input = parse(&windows_input(include_str!(
+            "../fixtures/day19/example.txt"
+        )))
+        .unwrap();
+        assert_eq!(33, solve_part1(&input));
+    }
 }
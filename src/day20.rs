@@ -1,17 +1,26 @@
+//! Day 20: Grove Positioning System, mixing a circular list of numbers.
+
 use aoc_runner_derive::{aoc, aoc_generator};
 use itertools::Itertools;
 
-#[aoc_generator(day20)]
-pub fn input_generator(input: &str) -> Vec<isize> {
-    input
-        .lines()
-        .map(str::trim)
-        .filter(|l| !l.is_empty())
-        .map(|l| l.parse().unwrap())
-        .collect()
+use crate::common::input::normalize;
+use crate::common::{parse_lines_with, ParseError};
+use crate::solution::Day;
+
+pub fn parse(input: &str) -> Result<Vec<isize>, ParseError> {
+    let input = &normalize(input, true);
+    let numbers: Vec<isize> =
+        parse_lines_with(input, |l| Ok(l.parse()?)).map_err(|e| e.in_day(20))?;
+    if numbers.iter().filter(|&&n| n == 0).count() != 1 {
+        return Err(ParseError::new("expected exactly one 0").in_day(20));
+    }
+
+    Ok(numbers)
 }
 
-fn mix(input: &[isize], decryption_key: isize, iterations: usize) -> isize {
+/// Multiplies the numbers by `decryption_key`, mixes them `iterations` times and returns the
+/// sum of the grove coordinates, the numbers 1000, 2000 and 3000 places after the 0.
+pub fn mix(input: &[isize], decryption_key: isize, iterations: usize) -> isize {
     let mut ans = (0..input.len()).collect_vec();
     for _ in 0..iterations {
         for (i, &n) in input.iter().enumerate() {
@@ -34,21 +43,45 @@ fn mix(input: &[isize], decryption_key: isize, iterations: usize) -> isize {
         .sum()
 }
 
+pub fn solve_part1(input: &[isize]) -> isize {
+    mix(input, 1, 1)
+}
+
+pub fn solve_part2(input: &[isize]) -> isize {
+    mix(input, 811589153, 10)
+}
+
+#[doc(hidden)]
+#[aoc_generator(day20)]
+pub fn input_generator(input: &str) -> Result<Vec<isize>, ParseError> {
+    parse(input)
+}
+
+#[doc(hidden)]
 #[aoc(day20, part1)]
 pub fn part1(input: &[isize]) -> isize {
-    mix(input, 1, 1)
+    solve_part1(input)
 }
 
+#[doc(hidden)]
 #[aoc(day20, part2)]
 pub fn part2(input: &[isize]) -> isize {
-    mix(input, 811589153, 10)
+    solve_part2(input)
 }
 
+pub(crate) static SOLUTION: Day<Vec<isize>> = Day {
+    day: 20,
+    parse,
+    part1: |input, _| Ok(solve_part1(input).into()),
+    part2: Some(|input, _| Ok(solve_part2(input).into())),
+};
+
 #[cfg(test)]
 mod tests {
     use pretty_assertions::assert_eq;
 
     use super::*;
+    use crate::common::input::windows_input;
 
     #[test]
     fn test_1() {
@@ -61,7 +94,8 @@ mod tests {
 0
 4
 ",
-        );
+        )
+        .unwrap();
         assert_eq!(3, part1(&input))
     }
 
@@ -76,7 +110,17 @@ mod tests {
 0
 4
 ",
-        );
+        )
+        .unwrap();
         assert_eq!(1623178306, part2(&input))
     }
+
+    #[test]
+    fn test_crlf() {
+        let input = parse(&windows_input(include_str!(
+            "../fixtures/day20/example.txt"
+        )))
+        .unwrap();
+        assert_eq!(3, solve_part1(&input));
+    }
 }
@@ -1,13 +1,19 @@
-use std::str;
+//! Day 21: Monkey Math, evaluating and solving a tree of monkeys yelling numbers.
 
 use aoc_runner_derive::{aoc, aoc_generator};
 use num::{One, Rational64, Zero};
 use pathfinding::prelude::*;
 use rustc_hash::FxHashMap;
 
+use crate::common::input::normalize;
+use crate::common::parser::{parse_all, Parser};
+use crate::common::{parse_lines_with, ParseError};
+use crate::solution::Day;
+
 pub type Name = [u8; 4];
 pub type Num = Rational64;
 
+/// A monkey that yells a number or the result of an operation on the numbers of two others.
 #[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
 pub enum Monkey {
     Num(Num),
@@ -18,7 +24,8 @@ pub enum Monkey {
 }
 
 impl Monkey {
-    fn evaluate(&self, evaluated: &FxHashMap<Name, Num>) -> Num {
+    /// The number of this monkey, given the numbers of the monkeys it waits for.
+    pub fn evaluate(&self, evaluated: &FxHashMap<Name, Num>) -> Num {
         match self {
             Monkey::Num(n) => *n,
             Monkey::Add(op1, op2) => evaluated[op1] + evaluated[op2],
@@ -32,7 +39,8 @@ impl Monkey {
 const ROOT: Name = [b'r', b'o', b'o', b't'];
 const HUMN: Name = [b'h', b'u', b'm', b'n'];
 
-fn topo_sort(input: &FxHashMap<Name, Monkey>) -> Vec<Name> {
+/// The monkeys `root` depends on, dependencies first, or one that depends on itself.
+fn try_topo_sort(input: &FxHashMap<Name, Monkey>) -> Result<Vec<Name>, Name> {
     let mut ordering = topological_sort(&[ROOT], |name: &Name| {
         match input[name] {
             Monkey::Num(_) => [None, None],
@@ -43,13 +51,17 @@ fn topo_sort(input: &FxHashMap<Name, Monkey>) -> Vec<Name> {
         }
         .into_iter()
         .flatten()
-    })
-    .unwrap();
+    })?;
     ordering.reverse();
-    ordering
+    Ok(ordering)
+}
+
+fn topo_sort(input: &FxHashMap<Name, Monkey>) -> Vec<Name> {
+    try_topo_sort(input).expect("parse rejects cycles")
 }
 
-fn evaluate_p1(input: &FxHashMap<Name, Monkey>) -> Num {
+/// The number the monkey `root` yells.
+pub fn evaluate(input: &FxHashMap<Name, Monkey>) -> Num {
     let mut evaluated: FxHashMap<Name, Num> = FxHashMap::default();
     for name in topo_sort(input) {
         let eval = input[&name].evaluate(&evaluated);
@@ -59,14 +71,15 @@ fn evaluate_p1(input: &FxHashMap<Name, Monkey>) -> Num {
     evaluated[&ROOT]
 }
 
-fn evaluate_p2(input: &FxHashMap<Name, Monkey>) -> Num {
+/// The number `humn` has to yell so that both monkeys `root` waits for yell the same number.
+pub fn solve_for_humn(input: &FxHashMap<Name, Monkey>) -> Num {
     let root_monkey = &input[&ROOT];
     let fixed_root_monkey = match root_monkey {
         Monkey::Sub(_, _) => *root_monkey,
         Monkey::Add(op1, op2) | Monkey::Mul(op1, op2) | Monkey::Div(op1, op2) => {
             Monkey::Sub(*op1, *op2)
         }
-        _ => panic!(),
+        Monkey::Num(_) => unreachable!("parse rejects a number for root"),
     };
 
     let ordering = topo_sort(input);
@@ -111,50 +124,121 @@ fn evaluate_p2(input: &FxHashMap<Name, Monkey>) -> Num {
     }
 }
 
-#[aoc_generator(day21)]
-pub fn input_generator(input: &str) -> FxHashMap<Name, Monkey> {
-    input
-        .lines()
-        .map(str::trim)
-        .filter(|l| !l.is_empty())
-        .map(|l| {
-            let bytes = l.as_bytes();
-            let name: Name = bytes[..4].try_into().unwrap();
-
-            let suffix = &bytes[6..];
-            let monkey = if (b'0'..=b'9').contains(&suffix[0]) {
-                Monkey::Num(str::from_utf8(suffix).unwrap().parse().unwrap())
-            } else {
-                let op1 = suffix[..4].try_into().unwrap();
-                let op2 = suffix[7..].try_into().unwrap();
-                match suffix[5] {
-                    b'+' => Monkey::Add(op1, op2),
-                    b'-' => Monkey::Sub(op1, op2),
-                    b'*' => Monkey::Mul(op1, op2),
-                    b'/' => Monkey::Div(op1, op2),
-                    _ => panic!(),
-                }
-            };
-            (name, monkey)
+pub fn parse(input: &str) -> Result<FxHashMap<Name, Monkey>, ParseError> {
+    let input = &normalize(input, true);
+    fn parse_name(p: &mut Parser) -> Result<Name, ParseError> {
+        let name = p.token("a monkey name", |c| c.is_ascii_lowercase())?;
+        name.as_bytes()
+            .try_into()
+            .map_err(|_| p.error_at(name, "expected four letters"))
+    }
+
+    let monkeys: FxHashMap<Name, Monkey> = parse_lines_with(input, |l| {
+        parse_all(l, |p| {
+            let name = parse_name(p)?;
+            p.keyword(":")?;
+            if p.rest()
+                .trim_start()
+                .starts_with(|c: char| c.is_ascii_digit())
+            {
+                return Ok((name, Monkey::Num(p.integer()?)));
+            }
+
+            let op1 = parse_name(p)?;
+            let op = p.one_of(&[
+                ("+", Monkey::Add as fn(Name, Name) -> Monkey),
+                ("-", Monkey::Sub),
+                ("*", Monkey::Mul),
+                ("/", Monkey::Div),
+            ])?;
+            let op2 = parse_name(p)?;
+            Ok((name, op(op1, op2)))
+        })
+    })
+    .map_err(|e| e.in_day(21))?;
+
+    for required in [ROOT, HUMN] {
+        if !monkeys.contains_key(&required) {
+            return Err(ParseError::new(format!(
+                "missing monkey '{}'",
+                String::from_utf8_lossy(&required)
+            ))
+            .in_day(21));
+        }
+    }
+
+    if let Some(unknown) = monkeys
+        .values()
+        .flat_map(|monkey| match monkey {
+            Monkey::Num(_) => [None, None],
+            Monkey::Add(op1, op2)
+            | Monkey::Sub(op1, op2)
+            | Monkey::Mul(op1, op2)
+            | Monkey::Div(op1, op2) => [Some(op1), Some(op2)],
         })
-        .collect()
+        .flatten()
+        .find(|op| !monkeys.contains_key(*op))
+    {
+        return Err(ParseError::new(format!(
+            "reference to unknown monkey '{}'",
+            String::from_utf8_lossy(unknown)
+        ))
+        .in_day(21));
+    }
+
+    if let Monkey::Num(_) = monkeys[&ROOT] {
+        return Err(ParseError::new("expected monkey 'root' to wait for two others").in_day(21));
+    }
+    if let Err(name) = try_topo_sort(&monkeys) {
+        return Err(ParseError::new(format!(
+            "monkey '{}' waits for itself",
+            String::from_utf8_lossy(&name)
+        ))
+        .in_day(21));
+    }
+
+    Ok(monkeys)
+}
+
+pub fn solve_part1(input: &FxHashMap<Name, Monkey>) -> Num {
+    evaluate(input)
 }
 
+pub fn solve_part2(input: &FxHashMap<Name, Monkey>) -> Num {
+    solve_for_humn(input)
+}
+
+#[doc(hidden)]
+#[aoc_generator(day21)]
+pub fn input_generator(input: &str) -> Result<FxHashMap<Name, Monkey>, ParseError> {
+    parse(input)
+}
+
+#[doc(hidden)]
 #[aoc(day21, part1)]
 pub fn part1(input: &FxHashMap<Name, Monkey>) -> Num {
-    evaluate_p1(input)
+    solve_part1(input)
 }
 
+#[doc(hidden)]
 #[aoc(day21, part2)]
 pub fn part2(input: &FxHashMap<Name, Monkey>) -> Num {
-    evaluate_p2(input)
+    solve_part2(input)
 }
 
+pub(crate) static SOLUTION: Day<FxHashMap<Name, Monkey>> = Day {
+    day: 21,
+    parse,
+    part1: |input, _| Ok(solve_part1(input).into()),
+    part2: Some(|input, _| Ok(solve_part2(input).into())),
+};
+
 #[cfg(test)]
 mod tests {
     use pretty_assertions::assert_eq;
 
     use super::*;
+    use crate::common::input::windows_input;
 
     #[test]
     fn test_1() {
@@ -175,7 +259,8 @@ lgvd: ljgn * ptdq
 drzm: hmdt - zczc
 hmdt: 32
 ",
-        );
+        )
+        .unwrap();
         assert_eq!(Num::from(152), part1(&input))
     }
 
@@ -198,7 +283,40 @@ lgvd: ljgn * ptdq
 drzm: hmdt - zczc
 hmdt: 32
 ",
-        );
+        )
+        .unwrap();
         assert_eq!(Num::from(301), part2(&input))
     }
+
+    #[test]
+    fn test_monkeys() {
+        let monkeys = FxHashMap::from_iter([
+            (ROOT, Monkey::Mul(*b"aaaa", *b"bbbb")),
+            (*b"aaaa", Monkey::Div(HUMN, *b"cccc")),
+            (*b"bbbb", Monkey::Num(Num::from(3))),
+            (*b"cccc", Monkey::Num(Num::from(4))),
+            (HUMN, Monkey::Num(Num::from(2))),
+        ]);
+        assert_eq!(Num::new(3, 2), evaluate(&monkeys));
+        assert_eq!(Num::from(12), solve_for_humn(&monkeys));
+    }
+
+    #[test]
+    fn test_invalid() {
+        let e = parse("root: 5\nhumn: 1\n").unwrap_err();
+        assert_eq!("expected monkey 'root' to wait for two others", e.reason);
+
+        let e = parse("root: aaaa + humn\naaaa: bbbb + humn\nbbbb: aaaa * humn\nhumn: 1\n")
+            .unwrap_err();
+        assert!(e.reason.ends_with("waits for itself"));
+    }
+
+    #[test]
+    fn test_crlf() {
+        let input = parse(&windows_input(include_str!(
+            "../fixtures/day21/example.txt"
+        )))
+        .unwrap();
+        assert_eq!(Num::from(152), solve_part1(&input));
+    }
 }
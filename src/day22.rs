@@ -1,34 +1,17 @@
+//! Day 22: Monkey Map, following a path on a board that wraps around either flat or as a cube.
+
 use aoc_runner_derive::{aoc, aoc_generator};
 use itertools::Itertools;
 use nalgebra::Vector2;
 use num::integer::gcd;
 use rustc_hash::FxHashMap;
 
-pub type Pos = Vector2<i32>;
-
-const NORTH: Pos = Pos::new(0, -1);
-const EAST: Pos = Pos::new(1, 0);
-const SOUTH: Pos = Pos::new(0, 1);
-const WEST: Pos = Pos::new(-1, 0);
-
-#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
-pub enum Dir {
-    North,
-    East,
-    South,
-    West,
-}
+use crate::common::input::{normalize, split_at_blank_line};
+use crate::common::{Direction, Grid, ParseError, Vec2i};
+use crate::solution::Day;
+use crate::visualize::{notify, Frame, Observer, Palette, Visualize};
 
-impl Dir {
-    pub fn vec(&self) -> &'static Pos {
-        match self {
-            Dir::North => &NORTH,
-            Dir::East => &EAST,
-            Dir::South => &SOUTH,
-            Dir::West => &WEST,
-        }
-    }
-}
+pub type Pos = Vector2<i32>;
 
 #[derive(Debug, Copy, Clone, Eq, PartialEq)]
 pub enum Tile {
@@ -36,23 +19,33 @@ pub enum Tile {
     Wall,
 }
 
+/// A square face of the board, `size` tiles wide.
 #[derive(Debug, Clone, Eq, PartialEq)]
 pub struct Face {
-    tiles: Vec<Tile>,
-    size: usize,
+    tiles: Grid<Tile>,
 }
 
 impl Face {
+    pub fn tiles(&self) -> &Grid<Tile> {
+        &self.tiles
+    }
+
+    /// The first open tile in reading order.
     pub fn first_free(&self) -> Pos {
-        let i = self.tiles.iter().position(|t| *t == Tile::Empty).unwrap();
-        Pos::new((i % self.size) as i32, (i / self.size) as i32)
+        let (pos, _) = self
+            .tiles
+            .pos_iter()
+            .find(|(_, t)| **t == Tile::Empty)
+            .unwrap();
+        Pos::new(pos.x as i32, pos.y as i32)
     }
+
     pub fn can_go(&self, pos: &Pos) -> bool {
-        let i = pos.x as usize + self.size * pos.y as usize;
-        self.tiles[i] == Tile::Empty
+        self.tiles[Vec2i::new(pos.x as i64, pos.y as i64)] == Tile::Empty
     }
 }
 
+/// A turn or a number of steps forward on the path.
 #[derive(Debug, Copy, Clone, Eq, PartialEq)]
 pub enum Command {
     Left,
@@ -60,9 +53,16 @@ pub enum Command {
     Forward(usize),
 }
 
-#[aoc_generator(day22)]
-pub fn input_generator(input: &str) -> (usize, usize, FxHashMap<Pos, Face>, Vec<Command>) {
-    fn min_face_len(it: impl IntoIterator<Item = char>) -> usize {
+/// face size, offset of the first face, faces keyed by their position and the path commands
+pub type Board = (usize, usize, FxHashMap<Pos, Face>, Vec<Command>);
+
+pub fn parse(input: &str) -> Result<Board, ParseError> {
+    let input = &normalize(input, true);
+    parse_board(input).map_err(|e| e.in_day(22))
+}
+
+fn parse_board(input: &str) -> Result<Board, ParseError> {
+    fn min_face_len(it: impl IntoIterator<Item = char>) -> Option<usize> {
         it.into_iter()
             .chunk_by(|&c| c == ' ')
             .into_iter()
@@ -75,125 +75,180 @@ pub fn input_generator(input: &str) -> (usize, usize, FxHashMap<Pos, Face>, Vec<
                 }
             })
             .min()
-            .unwrap()
     }
 
-    let (map, commands) = input.split_once("\n\n").unwrap();
-    let map = map.lines().collect_vec();
-    let min_face_len_horizontal = map.iter().map(|&l| min_face_len(l.chars())).min().unwrap();
-    let min_face_len_vertical = (0..(map.iter().map(|l| l.len()).max().unwrap()))
-        .map(|i| {
-            map.iter().map(move |&l| {
-                let bytes = l.as_bytes();
-                if i >= bytes.len() {
-                    ' '
-                } else {
-                    bytes[i] as char
-                }
-            })
-        })
-        .map(min_face_len)
+    let (map, commands) = split_at_blank_line(input)
+        .ok_or_else(|| ParseError::new("expected a blank line between map and commands"))?;
+    let map: Grid<char> = Grid::parse_padded(map, ' ').map_err(|_| ParseError::new("empty map"))?;
+
+    let min_face_len_horizontal = (0..map.size_y as i64)
+        .filter_map(|y| min_face_len(map.iter_row(y).copied()))
         .min()
-        .unwrap();
+        .unwrap_or(0);
+    let min_face_len_vertical = (0..map.size_x as i64)
+        .filter_map(|x| min_face_len(map.iter_col(x).copied()))
+        .min()
+        .unwrap_or(0);
     let size = gcd(min_face_len_horizontal, min_face_len_vertical);
-
-    let max_width = map.iter().map(|l| l.len()).max().unwrap();
-    assert_eq!(0, max_width % size);
-    let max_width = max_width / size;
+    if size == 0 || !map.size_x.is_multiple_of(size) || !map.size_y.is_multiple_of(size) {
+        return Err(ParseError::new("map is not made of square faces"));
+    }
 
     let mut faces: FxHashMap<Pos, Face> = FxHashMap::default();
-    let first_face_offset = map[0].bytes().position(|b| b != b' ').unwrap();
-    assert_eq!(0, first_face_offset % size);
+    let first_face_offset = map.iter_row(0).position(|&c| c != ' ').unwrap_or(0);
+    if first_face_offset % size != 0 {
+        return Err(ParseError::new("first face is not aligned to the face size").at_line(1));
+    }
     let first_face_offset = first_face_offset / size;
 
-    for (y, y_block) in map.chunks(size).enumerate() {
-        for x in 0..max_width {
-            if y_block[0].len() <= x * size || y_block[0].as_bytes()[x * size] == b' ' {
-                continue;
-            }
+    for (tile, view) in map.tiles(size, size) {
+        if view[Vec2i::zeros()] == ' ' {
+            continue;
+        }
 
-            let tiles = y_block
-                .iter()
-                .flat_map(|l| l[(x * size)..((x + 1) * size)].bytes())
-                .map(|b| match b {
-                    b'.' => Tile::Empty,
-                    b'#' => Tile::Wall,
-                    _ => panic!(),
-                })
-                .collect_vec();
-
-            let real_x = x as i32 - first_face_offset as i32;
-            let real_y = y as i32;
-            let pos = Pos::new(real_x, real_y);
-            let face = Face { tiles, size };
-
-            faces.insert(pos, face);
+        let mut tiles = Grid::new_from_element(size, size, Tile::Empty);
+        for (pos, &c) in view.pos_iter() {
+            tiles[pos] = match c {
+                '.' => Tile::Empty,
+                '#' => Tile::Wall,
+                c => {
+                    let pos = view.origin() + pos;
+                    return Err(ParseError::new(format!("invalid tile '{c}'"))
+                        .at_column(pos.x as usize + 1)
+                        .at_line(pos.y as usize + 1));
+                }
+            };
         }
+
+        let real_x = tile.x as i32 - first_face_offset as i32;
+        let real_y = tile.y as i32;
+        faces.insert(Pos::new(real_x, real_y), Face { tiles });
+    }
+
+    if !faces
+        .get(&Pos::new(0, 0))
+        .is_some_and(|face| face.tiles.iter().any(|t| *t == Tile::Empty))
+    {
+        return Err(ParseError::new("first face has no free tile").at_line(1));
     }
 
     let commands = commands.trim();
     let mut command_vec = vec![];
     let mut number_start = None;
+    let forward = |number: &str| -> Result<Command, ParseError> {
+        Ok(Command::Forward(number.parse().map_err(|e| {
+            ParseError::from(e).located_in(input, number)
+        })?))
+    };
     for (i, c) in commands.char_indices() {
         match c {
-            'L' => {
+            'L' | 'R' => {
                 if let Some(start) = number_start {
-                    command_vec.push(Command::Forward(commands[start..i].parse().unwrap()));
+                    command_vec.push(forward(&commands[start..i])?);
                     number_start = None;
                 }
-                command_vec.push(Command::Left);
-            }
-            'R' => {
-                if let Some(start) = number_start {
-                    command_vec.push(Command::Forward(commands[start..i].parse().unwrap()));
-                    number_start = None;
-                }
-                command_vec.push(Command::Right);
+                command_vec.push(if c == 'L' {
+                    Command::Left
+                } else {
+                    Command::Right
+                });
             }
-            _ => {
+            '0'..='9' => {
                 if number_start.is_none() {
                     number_start = Some(i)
                 }
             }
+            _ => {
+                return Err(ParseError::new(format!("unknown command '{c}'"))
+                    .located_in(input, &commands[i..]))
+            }
         }
     }
 
     if let Some(start) = number_start {
-        command_vec.push(Command::Forward(commands[start..].parse().unwrap()));
+        command_vec.push(forward(&commands[start..])?);
     }
 
-    (size, first_face_offset, faces, command_vec)
+    Ok((size, first_face_offset, faces, command_vec))
 }
 
-#[aoc(day22, part1)]
-pub fn part1(
-    (size, first_face_offset, faces, commands): &(usize, usize, FxHashMap<Pos, Face>, Vec<Command>),
-) -> i32 {
+/// The board with the path walked so far.
+struct Trail<'a> {
+    board: &'a Board,
+    /// positions on the whole board and the direction faced there
+    trail: &'a [(Pos, Direction)],
+}
+
+const PALETTE: Palette = Palette(&[
+    ('.', [200, 200, 200]),
+    ('#', [60, 60, 60]),
+    ('>', [255, 90, 0]),
+    ('v', [255, 90, 0]),
+    ('<', [255, 90, 0]),
+    ('^', [255, 90, 0]),
+]);
+
+impl Visualize for Trail<'_> {
+    fn frame(&self) -> Frame {
+        let (size, first_face_offset, faces, _) = self.board;
+        let width = faces
+            .keys()
+            .map(|f| (f.x + *first_face_offset as i32 + 1) as usize * size)
+            .max()
+            .unwrap();
+        let height = faces
+            .keys()
+            .map(|f| (f.y + 1) as usize * size)
+            .max()
+            .unwrap();
+        let mut frame = Frame::new(width, height, ' ', PALETTE);
+        for (face_pos, face) in faces.iter() {
+            for (tile_pos, tile) in face.tiles.pos_iter() {
+                let pos = absolute_pos(
+                    self.board,
+                    face_pos,
+                    &Pos::new(tile_pos.x as i32, tile_pos.y as i32),
+                );
+                let c = if *tile == Tile::Wall { '#' } else { '.' };
+                frame.set(pos.x as usize, pos.y as usize, c);
+            }
+        }
+        for (pos, dir) in self.trail {
+            let c = dir.arrow();
+            frame.set(pos.x as usize, pos.y as usize, c);
+        }
+
+        frame
+    }
+}
+
+/// The position of `pos` on face `face_pos` on the whole board, starting at 0.
+fn absolute_pos((size, first_face_offset, _, _): &Board, face_pos: &Pos, pos: &Pos) -> Pos {
+    Pos::new(
+        pos.x + (face_pos.x + *first_face_offset as i32) * *size as i32,
+        pos.y + face_pos.y * *size as i32,
+    )
+}
+
+/// Walks the path, wrapping around to the other side of the board, and returns the password.
+pub fn walk_flat(board: &Board, mut observer: Option<&mut dyn Observer>) -> i32 {
+    let (size, first_face_offset, faces, commands) = board;
+    let mut trail = vec![];
     let mut face_pos = Pos::new(0, 0);
     let mut pos = faces[&face_pos].first_free();
-    let mut dir = Dir::East;
+    let mut dir = Direction::East;
     for &cmd in commands {
         match cmd {
             Command::Left => {
-                dir = match dir {
-                    Dir::North => Dir::West,
-                    Dir::East => Dir::North,
-                    Dir::South => Dir::East,
-                    Dir::West => Dir::South,
-                }
+                dir = dir.rotate_ccw();
             }
             Command::Right => {
-                dir = match dir {
-                    Dir::North => Dir::East,
-                    Dir::East => Dir::South,
-                    Dir::South => Dir::West,
-                    Dir::West => Dir::North,
-                }
+                dir = dir.rotate_cw();
             }
             Command::Forward(distance) => {
                 for _ in 0..distance {
                     let mut new_face_pos = face_pos;
-                    let mut new_pos: Pos = pos + dir.vec();
+                    let mut new_pos: Pos = pos + dir.vector();
                     if new_pos.x < 0
                         || new_pos.x >= *size as i32
                         || new_pos.y < 0
@@ -202,16 +257,10 @@ pub fn part1(
                         new_pos.x = new_pos.x.rem_euclid(*size as i32);
                         new_pos.y = new_pos.y.rem_euclid(*size as i32);
 
-                        new_face_pos += dir.vec();
+                        new_face_pos += dir.vector();
                         if !faces.contains_key(&new_face_pos) {
                             new_face_pos = face_pos;
-                            let opposite_dir = match dir {
-                                Dir::North => Dir::South,
-                                Dir::East => Dir::West,
-                                Dir::South => Dir::North,
-                                Dir::West => Dir::East,
-                            }
-                            .vec();
+                            let opposite_dir = dir.opposite().vector();
                             loop {
                                 let new_new_face_pos: Pos = new_face_pos + opposite_dir;
                                 if !faces.contains_key(&new_new_face_pos) {
@@ -226,54 +275,57 @@ pub fn part1(
                     if faces[&new_face_pos].can_go(&new_pos) {
                         face_pos = new_face_pos;
                         pos = new_pos;
+                        if observer.is_some() {
+                            trail.push((absolute_pos(board, &face_pos, &pos), dir));
+                        }
                     } else {
                         break;
                     }
                 }
             }
         }
+        if observer.is_some() {
+            trail.push((absolute_pos(board, &face_pos, &pos), dir));
+        }
+        notify(
+            &mut observer,
+            &Trail {
+                board,
+                trail: &trail,
+            },
+        );
     }
 
     let row = 1 + pos.y + face_pos.y * *size as i32;
     let col = 1 + pos.x + (face_pos.x + *first_face_offset as i32) * *size as i32;
     let dir_value = match dir {
-        Dir::North => 3,
-        Dir::East => 0,
-        Dir::South => 1,
-        Dir::West => 2,
+        Direction::North => 3,
+        Direction::East => 0,
+        Direction::South => 1,
+        Direction::West => 2,
     };
     1000 * row + 4 * col + dir_value
 }
 
-#[aoc(day22, part2)]
-pub fn part2(
-    (size, first_face_offset, faces, commands): &(usize, usize, FxHashMap<Pos, Face>, Vec<Command>),
-) -> i32 {
+/// Walks the path with the board folded into a cube and returns the password.
+pub fn walk_cube(board: &Board, mut observer: Option<&mut dyn Observer>) -> i32 {
+    let (size, first_face_offset, faces, commands) = board;
+    let mut trail = vec![];
     let mut face_pos = Pos::new(0, 0);
     let mut pos = faces[&face_pos].first_free();
-    let mut dir = Dir::East;
+    let mut dir = Direction::East;
     for &cmd in commands {
         match cmd {
             Command::Left => {
-                dir = match dir {
-                    Dir::North => Dir::West,
-                    Dir::East => Dir::North,
-                    Dir::South => Dir::East,
-                    Dir::West => Dir::South,
-                }
+                dir = dir.rotate_ccw();
             }
             Command::Right => {
-                dir = match dir {
-                    Dir::North => Dir::East,
-                    Dir::East => Dir::South,
-                    Dir::South => Dir::West,
-                    Dir::West => Dir::North,
-                }
+                dir = dir.rotate_cw();
             }
             Command::Forward(distance) => {
                 for _ in 0..distance {
                     let mut new_face_pos = face_pos;
-                    let mut new_pos: Pos = pos + dir.vec();
+                    let mut new_pos: Pos = pos + dir.vector();
                     let mut new_dir = dir;
                     if new_pos.x < 0
                         || new_pos.x >= *size as i32
@@ -283,114 +335,114 @@ pub fn part2(
                         new_pos.x = new_pos.x.rem_euclid(*size as i32);
                         new_pos.y = new_pos.y.rem_euclid(*size as i32);
 
-                        new_face_pos += dir.vec();
+                        new_face_pos += dir.vector();
                         if !faces.contains_key(&new_face_pos) {
                             match size {
                                 4 => {
                                     match (face_pos.x, face_pos.y, dir) {
                                         // Top <-> North
-                                        (0, 0, Dir::North) => {
+                                        (0, 0, Direction::North) => {
                                             new_face_pos = Pos::new(-2, 1);
                                             new_pos = Pos::new(*size as i32 - 1 - pos.x, 0);
-                                            new_dir = Dir::South;
+                                            new_dir = Direction::South;
                                         }
-                                        (-2, 1, Dir::North) => {
+                                        (-2, 1, Direction::North) => {
                                             new_face_pos = Pos::new(0, 0);
                                             new_pos = Pos::new(*size as i32 - 1 - pos.x, 0);
-                                            new_dir = Dir::South;
+                                            new_dir = Direction::South;
                                         }
 
                                         // Top <-> West
-                                        (0, 0, Dir::West) => {
+                                        (0, 0, Direction::West) => {
                                             new_face_pos = Pos::new(-1, 1);
                                             new_pos = Pos::new(pos.y, 0);
-                                            new_dir = Dir::South;
+                                            new_dir = Direction::South;
                                         }
-                                        (-1, 1, Dir::North) => {
+                                        (-1, 1, Direction::North) => {
                                             new_face_pos = Pos::new(0, 0);
                                             new_pos = Pos::new(0, pos.x);
-                                            new_dir = Dir::East;
+                                            new_dir = Direction::East;
                                         }
 
                                         // Top <-> East
-                                        (0, 0, Dir::East) => {
+                                        (0, 0, Direction::East) => {
                                             new_face_pos = Pos::new(1, 2);
                                             new_pos = Pos::new(
                                                 *size as i32 - 1,
                                                 *size as i32 - 1 - pos.y,
                                             );
-                                            new_dir = Dir::West;
+                                            new_dir = Direction::West;
                                         }
-                                        (1, 2, Dir::East) => {
+                                        (1, 2, Direction::East) => {
                                             new_face_pos = Pos::new(0, 0);
                                             new_pos = Pos::new(
                                                 *size as i32 - 1,
                                                 *size as i32 - 1 - pos.y,
                                             );
-                                            new_dir = Dir::West;
+                                            new_dir = Direction::West;
                                         }
 
                                         // South <-> East
-                                        (0, 1, Dir::East) => {
+                                        (0, 1, Direction::East) => {
                                             new_face_pos = Pos::new(1, 2);
                                             new_pos = Pos::new(*size as i32 - 1 - pos.y, 0);
-                                            new_dir = Dir::South;
+                                            new_dir = Direction::South;
                                         }
-                                        (1, 2, Dir::North) => {
+                                        (1, 2, Direction::North) => {
                                             new_face_pos = Pos::new(0, 1);
                                             new_pos = Pos::new(
                                                 *size as i32 - 1,
                                                 *size as i32 - 1 - pos.x,
                                             );
-                                            new_dir = Dir::West;
+                                            new_dir = Direction::West;
                                         }
 
                                         // West <-> Down
-                                        (-1, 1, Dir::South) => {
+                                        (-1, 1, Direction::South) => {
                                             new_face_pos = Pos::new(0, 2);
                                             new_pos = Pos::new(0, *size as i32 - 1 - pos.x);
-                                            new_dir = Dir::East;
+                                            new_dir = Direction::East;
                                         }
-                                        (0, 2, Dir::West) => {
+                                        (0, 2, Direction::West) => {
                                             new_face_pos = Pos::new(-1, 1);
                                             new_pos = Pos::new(
                                                 *size as i32 - 1 - pos.y,
                                                 *size as i32 - 1,
                                             );
-                                            new_dir = Dir::North;
+                                            new_dir = Direction::North;
                                         }
 
                                         // North <-> Down
-                                        (-2, 1, Dir::South) => {
+                                        (-2, 1, Direction::South) => {
                                             new_face_pos = Pos::new(0, 2);
                                             new_pos = Pos::new(
                                                 *size as i32 - 1 - pos.x,
                                                 *size as i32 - 1,
                                             );
-                                            new_dir = Dir::North;
+                                            new_dir = Direction::North;
                                         }
-                                        (0, 2, Dir::South) => {
+                                        (0, 2, Direction::South) => {
                                             new_face_pos = Pos::new(-2, 1);
                                             new_pos = Pos::new(
                                                 *size as i32 - 1 - pos.x,
                                                 *size as i32 - 1,
                                             );
-                                            new_dir = Dir::North;
+                                            new_dir = Direction::North;
                                         }
 
                                         // North <-> East
-                                        (-2, 1, Dir::West) => {
+                                        (-2, 1, Direction::West) => {
                                             new_face_pos = Pos::new(1, 2);
                                             new_pos = Pos::new(
                                                 *size as i32 - 1 - pos.y,
                                                 *size as i32 - 1,
                                             );
-                                            new_dir = Dir::North;
+                                            new_dir = Direction::North;
                                         }
-                                        (1, 2, Dir::South) => {
+                                        (1, 2, Direction::South) => {
                                             new_face_pos = Pos::new(-2, 1);
                                             new_pos = Pos::new(0, *size as i32 - 1 - pos.x);
-                                            new_dir = Dir::East;
+                                            new_dir = Direction::East;
                                         }
 
                                         _ => unreachable!(),
@@ -399,93 +451,93 @@ pub fn part2(
                                 50 => {
                                     match (face_pos.x, face_pos.y, dir) {
                                         // Top <-> North
-                                        (0, 0, Dir::North) => {
+                                        (0, 0, Direction::North) => {
                                             new_face_pos = Pos::new(-1, 3);
                                             new_pos = Pos::new(0, pos.x);
-                                            new_dir = Dir::East;
+                                            new_dir = Direction::East;
                                         }
-                                        (-1, 3, Dir::West) => {
+                                        (-1, 3, Direction::West) => {
                                             new_face_pos = Pos::new(0, 0);
                                             new_pos = Pos::new(pos.y, 0);
-                                            new_dir = Dir::South;
+                                            new_dir = Direction::South;
                                         }
 
                                         // Top <-> West
-                                        (0, 0, Dir::West) => {
+                                        (0, 0, Direction::West) => {
                                             new_face_pos = Pos::new(-1, 2);
                                             new_pos = Pos::new(0, *size as i32 - 1 - pos.y);
-                                            new_dir = Dir::East;
+                                            new_dir = Direction::East;
                                         }
-                                        (-1, 2, Dir::West) => {
+                                        (-1, 2, Direction::West) => {
                                             new_face_pos = Pos::new(0, 0);
                                             new_pos = Pos::new(0, *size as i32 - 1 - pos.y);
-                                            new_dir = Dir::East;
+                                            new_dir = Direction::East;
                                         }
 
                                         // East <-> North
-                                        (1, 0, Dir::North) => {
+                                        (1, 0, Direction::North) => {
                                             new_face_pos = Pos::new(-1, 3);
                                             new_pos = Pos::new(pos.x, *size as i32 - 1);
-                                            new_dir = Dir::North;
+                                            new_dir = Direction::North;
                                         }
-                                        (-1, 3, Dir::South) => {
+                                        (-1, 3, Direction::South) => {
                                             new_face_pos = Pos::new(1, 0);
                                             new_pos = Pos::new(pos.x, 0);
-                                            new_dir = Dir::South;
+                                            new_dir = Direction::South;
                                         }
 
                                         // East <-> Down
-                                        (1, 0, Dir::East) => {
+                                        (1, 0, Direction::East) => {
                                             new_face_pos = Pos::new(0, 2);
                                             new_pos = Pos::new(
                                                 *size as i32 - 1,
                                                 *size as i32 - 1 - pos.y,
                                             );
-                                            new_dir = Dir::West;
+                                            new_dir = Direction::West;
                                         }
-                                        (0, 2, Dir::East) => {
+                                        (0, 2, Direction::East) => {
                                             new_face_pos = Pos::new(1, 0);
                                             new_pos = Pos::new(
                                                 *size as i32 - 1,
                                                 *size as i32 - 1 - pos.y,
                                             );
-                                            new_dir = Dir::West;
+                                            new_dir = Direction::West;
                                         }
 
                                         // East <-> South
-                                        (1, 0, Dir::South) => {
+                                        (1, 0, Direction::South) => {
                                             new_face_pos = Pos::new(0, 1);
                                             new_pos = Pos::new(*size as i32 - 1, pos.x);
-                                            new_dir = Dir::West;
+                                            new_dir = Direction::West;
                                         }
-                                        (0, 1, Dir::East) => {
+                                        (0, 1, Direction::East) => {
                                             new_face_pos = Pos::new(1, 0);
                                             new_pos = Pos::new(pos.y, *size as i32 - 1);
-                                            new_dir = Dir::North;
+                                            new_dir = Direction::North;
                                         }
 
                                         // South <-> West
-                                        (0, 1, Dir::West) => {
+                                        (0, 1, Direction::West) => {
                                             new_face_pos = Pos::new(-1, 2);
                                             new_pos = Pos::new(pos.y, 0);
-                                            new_dir = Dir::South;
+                                            new_dir = Direction::South;
                                         }
-                                        (-1, 2, Dir::North) => {
+                                        (-1, 2, Direction::North) => {
                                             new_face_pos = Pos::new(0, 1);
                                             new_pos = Pos::new(0, pos.x);
-                                            new_dir = Dir::East;
+                                            new_dir = Direction::East;
                                         }
 
                                         // Down <-> North
-                                        (0, 2, Dir::South) => {
+                                        (0, 2, Direction::South) => {
                                             new_face_pos = Pos::new(-1, 3);
                                             new_pos = Pos::new(*size as i32 - 1, pos.x);
-                                            new_dir = Dir::West;
+                                            new_dir = Direction::West;
                                         }
-                                        (-1, 3, Dir::East) => {
+                                        (-1, 3, Direction::East) => {
                                             new_face_pos = Pos::new(0, 2);
                                             new_pos = Pos::new(pos.y, *size as i32 - 1);
-                                            new_dir = Dir::North;
+                                            new_dir = Direction::North;
                                         }
 
                                         _ => unreachable!(),
@@ -500,25 +552,80 @@ pub fn part2(
                         face_pos = new_face_pos;
                         pos = new_pos;
                         dir = new_dir;
+                        if observer.is_some() {
+                            trail.push((absolute_pos(board, &face_pos, &pos), dir));
+                        }
                     } else {
                         break;
                     }
                 }
             }
         }
+        if observer.is_some() {
+            trail.push((absolute_pos(board, &face_pos, &pos), dir));
+        }
+        notify(
+            &mut observer,
+            &Trail {
+                board,
+                trail: &trail,
+            },
+        );
     }
 
     let row = 1 + pos.y + face_pos.y * *size as i32;
     let col = 1 + pos.x + (face_pos.x + *first_face_offset as i32) * *size as i32;
     let dir_value = match dir {
-        Dir::North => 3,
-        Dir::East => 0,
-        Dir::South => 1,
-        Dir::West => 2,
+        Direction::North => 3,
+        Direction::East => 0,
+        Direction::South => 1,
+        Direction::West => 2,
     };
     1000 * row + 4 * col + dir_value
 }
 
+pub fn solve_part1(board: &Board) -> i32 {
+    walk_flat(board, None)
+}
+
+pub fn solve_part2(board: &Board) -> i32 {
+    walk_cube(board, None)
+}
+
+/// Shows the path walked so far after each command.
+pub fn visualize(board: &Board, part: u32, observer: &mut dyn Observer) {
+    if part == 1 {
+        walk_flat(board, Some(observer));
+    } else {
+        walk_cube(board, Some(observer));
+    }
+}
+
+#[doc(hidden)]
+#[aoc_generator(day22)]
+pub fn input_generator(input: &str) -> Result<Board, ParseError> {
+    parse(input)
+}
+
+#[doc(hidden)]
+#[aoc(day22, part1)]
+pub fn part1(board: &Board) -> i32 {
+    solve_part1(board)
+}
+
+#[doc(hidden)]
+#[aoc(day22, part2)]
+pub fn part2(board: &Board) -> i32 {
+    solve_part2(board)
+}
+
+pub(crate) static SOLUTION: Day<Board> = Day {
+    day: 22,
+    parse,
+    part1: |input, _| Ok(solve_part1(input).into()),
+    part2: Some(|input, _| Ok(solve_part2(input).into())),
+};
+
 #[cfg(test)]
 mod tests {
     use pretty_assertions::assert_eq;
@@ -543,7 +650,8 @@ mod tests {
 
 10R5L5R10L4R5L5
 ",
-        );
+        )
+        .unwrap();
         assert_eq!(6032, part1(&input))
     }
 
@@ -565,7 +673,32 @@ mod tests {
 
 10R5L5R10L4R5L5
 ",
-        );
+        )
+        .unwrap();
         assert_eq!(5031, part2(&input))
     }
+
+    #[test]
+    fn test_crlf() {
+        let input = input_generator(
+            &r"        ...#
+        .#..
+        #...
+        ....
+...#.......#
+........#...
+..#....#....
+..........#.
+        ...#....
+        .....#..
+        .#......
+        ......#.
+
+10R5L5R10L4R5L5
+"
+            .replace('\n', "\r\n"),
+        )
+        .unwrap();
+        assert_eq!(6032, part1(&input))
+    }
 }
@@ -1,125 +1,205 @@
+//! Day 23: Unstable Diffusion, elves spreading out over a grove.
+
 use aoc_runner_derive::{aoc, aoc_generator};
-use nalgebra::Vector2;
-use rustc_hash::FxHashSet;
 
-pub type Pos = Vector2<i32>;
+use crate::common::input::normalize;
+use crate::common::{Direction, Direction8, ParseError, SparseGrid, Vec2i};
+use crate::solution::Day;
+use crate::visualize::{notify, Frame, Observer, Palette, Visualize};
 
-const CARD_DIRS: [Pos; 4] = [
-    Pos::new(0, -1),
-    Pos::new(0, 1),
-    Pos::new(-1, 0),
-    Pos::new(1, 0),
-];
-const NEIGHBORS: [Pos; 8] = [
-    Pos::new(-1, -1),
-    Pos::new(0, -1),
-    Pos::new(1, -1),
-    Pos::new(-1, 0),
-    Pos::new(1, 0),
-    Pos::new(-1, 1),
-    Pos::new(0, 1),
-    Pos::new(1, 1),
-];
-const CLEARANCE: [[Pos; 3]; 4] = [
-    [Pos::new(-1, -1), Pos::new(0, -1), Pos::new(1, -1)],
-    [Pos::new(-1, 1), Pos::new(0, 1), Pos::new(1, 1)],
-    [Pos::new(-1, -1), Pos::new(-1, 0), Pos::new(-1, 1)],
-    [Pos::new(1, -1), Pos::new(1, 0), Pos::new(1, 1)],
+pub type Pos = Vec2i;
+pub type Elves = SparseGrid<()>;
+
+/// The order in which the elves consider the directions, rotated by one every round.
+const PROPOSALS: [Direction; 4] = [
+    Direction::North,
+    Direction::South,
+    Direction::West,
+    Direction::East,
 ];
 
-#[aoc_generator(day23)]
-pub fn input_generator(input: &str) -> FxHashSet<Pos> {
-    input
+/// Parses the positions of the elves, marked with `#`.
+pub fn parse(input: &str) -> Result<Elves, ParseError> {
+    let input = &normalize(input, true);
+    let elves: Elves = input
         .lines()
         .map(str::trim)
         .filter(|l| !l.is_empty())
         .enumerate()
         .flat_map(|(y, l)| {
-            l.chars()
-                .enumerate()
-                .filter(|(_, c)| *c == '#')
-                .map(move |(x, _)| Pos::new(x as i32, y as i32))
+            l.chars().enumerate().filter_map(move |(x, c)| match c {
+                '#' => Some(Ok((Pos::new(x as i64, y as i64), ()))),
+                '.' => None,
+                _ => Some(Err(ParseError::new(format!("invalid tile '{c}'"))
+                    .at_column(x + 1)
+                    .located_in(input, l)
+                    .in_day(23))),
+            })
         })
-        .collect()
+        .collect::<Result<_, _>>()?;
+    if elves.is_empty() {
+        return Err(ParseError::new("expected at least one elf").in_day(23));
+    }
+
+    Ok(elves)
+}
+
+/// The elves spreading out over the grove, one round at a time.
+#[derive(Debug, Clone)]
+pub struct Diffusion {
+    elves: Elves,
+    rounds: usize,
+}
+
+impl Diffusion {
+    pub fn new(elves: &Elves) -> Self {
+        Self {
+            elves: elves.clone(),
+            rounds: 0,
+        }
+    }
+
+    pub fn elves(&self) -> &Elves {
+        &self.elves
+    }
+
+    /// The number of rounds played so far.
+    pub fn rounds(&self) -> usize {
+        self.rounds
+    }
+
+    /// Plays the next round and returns whether any elf moved.
+    pub fn step(&mut self) -> bool {
+        let moved = play_round(&mut self.elves, self.rounds);
+        self.rounds += 1;
+        moved
+    }
+}
+
+const PALETTE: Palette = Palette(&[('#', [60, 200, 60]), ('.', [40, 25, 10])]);
+
+impl Visualize for Diffusion {
+    fn frame(&self) -> Frame {
+        // the elves within the smallest rectangle containing all of them
+        let (min, max) = self.elves.bounds().unwrap();
+        let size = max - min;
+        let mut frame = Frame::new((size.x + 1) as usize, (size.y + 1) as usize, '.', PALETTE);
+        for p in self.elves.positions() {
+            frame.set((p.x - min.x) as usize, (p.y - min.y) as usize, '#');
+        }
+
+        frame
+    }
 }
 
-fn step<const MAX_STEPS: usize>(grid: &FxHashSet<Pos>) -> (usize, FxHashSet<Pos>) {
-    fn step_once(grid: &mut FxHashSet<Pos>, i: usize) -> bool {
-        let mut moves = 0usize;
-        let old_grid = grid.clone();
-        grid.clear();
-
-        'outer: for pos in &old_grid {
-            if NEIGHBORS.iter().any(|p| old_grid.contains(&(pos + p))) {
-                for orig_dir_index in 0..4 {
-                    let dir_index = (orig_dir_index + i) % 4;
-                    if CLEARANCE[dir_index]
-                        .iter()
-                        .all(|p| !old_grid.contains(&(pos + p)))
-                    {
-                        let dir = &CARD_DIRS[dir_index];
-                        let target = pos + dir;
-                        if !grid.insert(target) {
-                            grid.remove(&target);
-                            grid.insert(*pos);
-                            grid.insert(target + dir);
-                            moves -= 2;
-                        } else {
-                            moves += 1;
-                        }
-
-                        continue 'outer;
+/// Plays round `i`, counting from 0, and returns whether any elf moved.
+fn play_round(grid: &mut Elves, i: usize) -> bool {
+    let mut moves = 0usize;
+    let old_grid = grid.clone();
+    grid.clear();
+
+    'outer: for pos in old_grid.positions() {
+        if Direction8::VALUES
+            .iter()
+            .any(|d| old_grid.contains(&d.offset(pos)))
+        {
+            for orig_dir_index in 0..4 {
+                let dir_index = (orig_dir_index + i) % 4;
+                let dir = Direction8::from(PROPOSALS[dir_index]);
+                if [dir.rotate_ccw(), dir, dir.rotate_cw()]
+                    .iter()
+                    .all(|d| !old_grid.contains(&d.offset(pos)))
+                {
+                    let dir = dir.vec();
+                    let target = pos + dir;
+                    if grid.insert(target, ()).is_some() {
+                        grid.remove(&target);
+                        grid.insert(*pos, ());
+                        grid.insert(target + dir, ());
+                        moves -= 2;
+                    } else {
+                        moves += 1;
                     }
+
+                    continue 'outer;
                 }
             }
-
-            grid.insert(*pos);
         }
 
-        moves > 0
+        grid.insert(*pos, ());
     }
 
-    let mut grid = grid.clone();
-    let mut i = 0;
-    loop {
-        if i >= MAX_STEPS {
-            break;
-        }
+    moves > 0
+}
 
-        i += 1;
-        if !step_once(&mut grid, i - 1) {
+/// Plays rounds until no elf moves any more, or at most `max_rounds`.
+fn spread(elves: &Elves, max_rounds: usize, mut observer: Option<&mut dyn Observer>) -> Diffusion {
+    let mut diffusion = Diffusion::new(elves);
+    notify(&mut observer, &diffusion);
+    while diffusion.rounds() < max_rounds {
+        let moved = diffusion.step();
+        notify(&mut observer, &diffusion);
+        if !moved {
             break;
         }
     }
 
-    (i, grid)
+    diffusion
+}
+
+/// The empty ground tiles within the smallest rectangle containing all elves.
+pub fn open_spaces(grid: &Elves) -> usize {
+    grid.area() - grid.len()
+}
+
+pub fn solve_part1(input: &Elves) -> usize {
+    open_spaces(spread(input, 10, None).elves())
 }
 
-fn open_spaces(grid: &FxHashSet<Pos>) -> usize {
-    let min_x = grid.iter().map(|p| p.x).min().unwrap();
-    let max_x = grid.iter().map(|p| p.x).max().unwrap();
-    let min_y = grid.iter().map(|p| p.y).min().unwrap();
-    let max_y = grid.iter().map(|p| p.y).max().unwrap();
-    (max_x - min_x + 1) as usize * (max_y - min_y + 1) as usize - grid.len()
+pub fn solve_part2(input: &Elves) -> usize {
+    spread(input, usize::MAX, None).rounds()
 }
 
+/// Shows the elves before the first and after every round.
+pub fn visualize(input: &Elves, part: u32, observer: &mut dyn Observer) {
+    if part == 1 {
+        spread(input, 10, Some(observer));
+    } else {
+        spread(input, usize::MAX, Some(observer));
+    }
+}
+
+#[doc(hidden)]
+#[aoc_generator(day23)]
+pub fn input_generator(input: &str) -> Result<Elves, ParseError> {
+    parse(input)
+}
+
+#[doc(hidden)]
 #[aoc(day23, part1)]
-pub fn part1(input: &FxHashSet<Pos>) -> usize {
-    let (_, grid) = step::<10>(input);
-    open_spaces(&grid)
+pub fn part1(input: &Elves) -> usize {
+    solve_part1(input)
 }
 
+#[doc(hidden)]
 #[aoc(day23, part2)]
-pub fn part2(input: &FxHashSet<Pos>) -> usize {
-    let (n, _) = step::<{ usize::MAX }>(input);
-    n
+pub fn part2(input: &Elves) -> usize {
+    solve_part2(input)
 }
 
+pub(crate) static SOLUTION: Day<Elves> = Day {
+    day: 23,
+    parse,
+    part1: |input, _| Ok(solve_part1(input).into()),
+    part2: Some(|input, _| Ok(solve_part2(input).into())),
+};
+
 #[cfg(test)]
 mod tests {
     use pretty_assertions::assert_eq;
 
     use super::*;
+    use crate::common::input::windows_input;
 
     #[test]
     fn test_small() {
@@ -131,7 +211,8 @@ mod tests {
 ..##.
 .....
 ",
-        );
+        )
+        .unwrap();
         assert_eq!(25, part1(&input))
     }
 
@@ -146,7 +227,8 @@ mod tests {
 ##.#.##
 .#..#..
 ",
-        );
+        )
+        .unwrap();
         assert_eq!(110, part1(&input))
     }
 
@@ -161,7 +243,36 @@ mod tests {
 ##.#.##
 .#..#..
 ",
-        );
+        )
+        .unwrap();
         assert_eq!(20, part2(&input))
     }
+
+    #[test]
+    fn test_diffusion_steps() {
+        let input = input_generator(
+            r".....
+..##.
+..#..
+.....
+..##.
+.....
+",
+        )
+        .unwrap();
+        let mut diffusion = Diffusion::new(&input);
+        while diffusion.step() {}
+        assert_eq!(4, diffusion.rounds());
+        assert_eq!(5, diffusion.elves().len());
+        assert_eq!(part2(&input), diffusion.rounds());
+    }
+
+    #[test]
+    fn test_crlf() {
+        let input = parse(&windows_input(include_str!(
+            "../fixtures/day23/example.txt"
+        )))
+        .unwrap();
+        assert_eq!(110, solve_part1(&input));
+    }
 }
@@ -1,42 +1,27 @@
+//! Day 24: Blizzard Basin, the fastest way through a valley of moving blizzards.
+
 use aoc_runner_derive::{aoc, aoc_generator};
-use itertools::Itertools;
 use nalgebra::Vector2;
-use pathfinding::prelude::*;
-use rustc_hash::FxHashSet;
-
-pub type Pos = Vector2<i32>;
+use num::integer::lcm;
+use rustc_hash::{FxHashMap, FxHashSet};
 
-const NORTH: Pos = Pos::new(0, -1);
-const EAST: Pos = Pos::new(1, 0);
-const SOUTH: Pos = Pos::new(0, 1);
-const WEST: Pos = Pos::new(-1, 0);
-const DIRS: [Pos; 5] = [Pos::new(0, 0), NORTH, EAST, SOUTH, WEST];
+use crate::common::input::normalize;
+use crate::common::search::astar;
+use crate::common::{Direction, ParseError};
+use crate::solution::Day;
+use crate::visualize::{notify, Frame, Observer, Palette, Visualize};
 
-#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
-pub enum Dir {
-    North,
-    East,
-    South,
-    West,
-}
-
-impl Dir {
-    pub fn vec(&self) -> &'static Pos {
-        match self {
-            Dir::North => &NORTH,
-            Dir::East => &EAST,
-            Dir::South => &SOUTH,
-            Dir::West => &WEST,
-        }
-    }
-}
+pub type Pos = Vector2<i32>;
 
+/// A blizzard that moves one tile in `dir` every minute.
 #[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
 pub struct Blizzard {
-    dir: Dir,
-    pos: Pos,
+    pub dir: Direction,
+    pub pos: Pos,
 }
 
+/// The valley including its walls, with the entrance in the top left and the exit in the bottom
+/// right corner.
 #[derive(Debug, Clone, Eq, PartialEq, Hash)]
 pub struct Valley {
     blizzards: Vec<Blizzard>,
@@ -45,21 +30,83 @@ pub struct Valley {
 }
 
 impl Valley {
+    /// The width and height including the walls.
+    pub fn size(&self) -> (i32, i32) {
+        (self.size_x, self.size_y)
+    }
+
+    /// The blizzards after `steps` minutes.
+    pub fn blizzards_at(&self, steps: i32) -> impl Iterator<Item = Blizzard> + '_ {
+        self.blizzards.iter().map(move |b| {
+            let mut pos = b.pos + steps * b.dir.vector::<i32>();
+            pos.x = (pos.x - 1).rem_euclid(self.size_x - 2) + 1;
+            pos.y = (pos.y - 1).rem_euclid(self.size_y - 2) + 1;
+            Blizzard { dir: b.dir, pos }
+        })
+    }
+
+    /// The positions covered by a blizzard after `steps` minutes.
     pub fn get_blizzard_state(&self, steps: i32) -> FxHashSet<Pos> {
-        self.blizzards
-            .iter()
-            .map(|b| {
-                let mut pos = b.pos + steps * b.dir.vec();
-                pos.x = (pos.x - 1).rem_euclid(self.size_x - 2) + 1;
-                pos.y = (pos.y - 1).rem_euclid(self.size_y - 2) + 1;
-                pos
-            })
-            .collect()
+        self.blizzards_at(steps).map(|b| b.pos).collect()
+    }
+
+    /// The blizzards at every minute, starting with minute 0. The iterator never ends, the
+    /// blizzards return to their start after the least common multiple of the inner width and
+    /// height.
+    pub fn timeline(&self) -> impl Iterator<Item = Vec<Blizzard>> + '_ {
+        (0..).map(|minute| self.blizzards_at(minute).collect())
     }
 }
 
-#[aoc_generator(day24)]
-pub fn input_generator(input: &str) -> Valley {
+/// The valley with the blizzards and the expedition at a minute.
+struct Expedition<'a> {
+    valley: &'a Valley,
+    minute: i32,
+    pos: Pos,
+}
+
+const PALETTE: Palette = Palette(&[
+    ('#', [100, 100, 100]),
+    ('.', [20, 20, 40]),
+    ('E', [255, 80, 0]),
+    ('^', [150, 200, 255]),
+    ('>', [150, 200, 255]),
+    ('v', [150, 200, 255]),
+    ('<', [150, 200, 255]),
+]);
+
+impl Visualize for Expedition<'_> {
+    fn frame(&self) -> Frame {
+        let (size_x, size_y) = (self.valley.size_x, self.valley.size_y);
+        let mut frame = Frame::new(size_x as usize, size_y as usize, '#', PALETTE);
+        for y in 1..size_y - 1 {
+            for x in 1..size_x - 1 {
+                frame.set(x as usize, y as usize, '.');
+            }
+        }
+        frame.set(1, 0, '.');
+        frame.set(size_x as usize - 2, size_y as usize - 1, '.');
+
+        // like in the puzzle description, cells with several blizzards show their number
+        let mut counts = FxHashMap::default();
+        for b in self.valley.blizzards_at(self.minute) {
+            let count = counts.entry(b.pos).or_insert(0);
+            *count += 1;
+            let c = match *count {
+                1 => b.dir.arrow(),
+                n => char::from_digit(n.min(9), 10).unwrap(),
+            };
+            frame.set(b.pos.x as usize, b.pos.y as usize, c);
+        }
+        frame.set(self.pos.x as usize, self.pos.y as usize, 'E');
+
+        frame
+    }
+}
+
+/// Parses the map of the valley with the blizzards at minute 0.
+pub fn parse(input: &str) -> Result<Valley, ParseError> {
+    let input = &normalize(input, true);
     let mut size_x = 0;
     let mut size_y = 0;
     let blizzards = input
@@ -70,83 +117,171 @@ pub fn input_generator(input: &str) -> Valley {
         .flat_map(|(y, l)| {
             size_y = size_y.max(y as i32 + 1);
             size_x = l.len() as i32;
-            l.chars()
-                .enumerate()
-                .filter(|(_, c)| matches!(c, '^' | '>' | 'v' | '<'))
-                .map(move |(x, c)| {
-                    let dir = match c {
-                        '^' => Dir::North,
-                        '>' => Dir::East,
-                        'v' => Dir::South,
-                        '<' => Dir::West,
-                        _ => unreachable!(),
-                    };
-                    Blizzard {
-                        dir,
-                        pos: Pos::new(x as i32, y as i32),
+            l.chars().enumerate().filter_map(move |(x, c)| {
+                let dir = match c {
+                    '^' | '>' | 'v' | '<' => Direction::try_from(c).unwrap(),
+                    '#' | '.' => return None,
+                    _ => {
+                        return Some(Err(ParseError::new(format!("invalid tile '{c}'"))
+                            .at_column(x + 1)
+                            .located_in(input, l)
+                            .in_day(24)))
                     }
-                })
+                };
+                Some(Ok(Blizzard {
+                    dir,
+                    pos: Pos::new(x as i32, y as i32),
+                }))
+            })
         })
-        .collect();
-    assert!(size_x >= 3);
-    assert!(size_y >= 3);
-    Valley {
+        .collect::<Result<_, _>>()?;
+    if size_x < 3 || size_y < 3 {
+        return Err(ParseError::new("valley must be at least 3x3").in_day(24));
+    }
+
+    Ok(Valley {
         blizzards,
         size_x,
         size_y,
+    })
+}
+
+/// The positions covered by a blizzard, computed once for each minute of the period after
+/// which the blizzards are back at their start.
+struct BlizzardCache<'a> {
+    valley: &'a Valley,
+    states: Vec<Option<FxHashSet<Pos>>>,
+}
+
+impl<'a> BlizzardCache<'a> {
+    fn new(valley: &'a Valley) -> Self {
+        let period = lcm(valley.size_x - 2, valley.size_y - 2) as usize;
+        Self {
+            valley,
+            states: vec![None; period],
+        }
+    }
+
+    fn at(&mut self, minute: i32) -> &FxHashSet<Pos> {
+        let valley = self.valley;
+        let i = minute as usize % self.states.len();
+        self.states[i].get_or_insert_with(|| valley.get_blizzard_state(minute))
     }
 }
 
-fn do_pathfinding(valley: &Valley, start_minutes: i32, start_pos: Pos, end_pos: Pos) -> i32 {
+/// The minutes and positions of the fastest way from `start_pos` to `end_pos`.
+fn do_pathfinding(
+    blizzards: &mut BlizzardCache,
+    start_minutes: i32,
+    start_pos: Pos,
+    end_pos: Pos,
+) -> Vec<(i32, Pos)> {
+    let (size_x, size_y) = blizzards.valley.size();
     let (path, _cost) = astar(
-        &(start_minutes, start_pos),
+        (start_minutes, start_pos),
         |&(i, pos)| {
-            let blizzards = valley.get_blizzard_state(i + 1);
-            DIRS.iter()
-                .map(|&dir| {
-                    let pos: Pos = pos + dir;
-                    pos
-                })
-                .filter(|&pos| {
-                    pos == end_pos
-                        || pos == start_pos
-                        || (pos.x >= 1
-                            && pos.x <= valley.size_x - 2
-                            && pos.y >= 1
-                            && pos.y <= valley.size_y - 2)
-                })
-                .filter(|pos| !blizzards.contains(pos))
-                .map(|pos| ((i + 1, pos), 1))
-                .collect_vec()
+            let blizzards = blizzards.at(i + 1);
+            let is_free = |pos: Pos| {
+                let inside = pos.x >= 1 && pos.x <= size_x - 2 && pos.y >= 1 && pos.y <= size_y - 2;
+                (inside || pos == end_pos || pos == start_pos) && !blizzards.contains(&pos)
+            };
+            // move or wait, in an array rather than a Vec as this runs for every node
+            let [north, east, south, west] = Direction::VALUES.map(|dir| pos + dir.vector());
+            [north, east, south, west, pos]
+                .map(|pos| is_free(pos).then_some(((i + 1, pos), 1)))
+                .into_iter()
+                .flatten()
         },
         |&(_, pos)| (end_pos - pos).abs().sum(),
         |&(_, pos)| pos == end_pos,
     )
     .unwrap();
-    path.last().unwrap().0
+    path
+}
+
+/// The minutes and positions of the expedition while it crosses the valley `trips` times back
+/// and forth, starting at the entrance at minute 0. Each trip is only searched once the previous
+/// one has been walked.
+pub fn expedition(valley: &Valley, trips: usize) -> impl Iterator<Item = (i32, Pos)> + '_ {
+    let entrance = Pos::new(1, 0);
+    let exit = Pos::new(valley.size_x - 2, valley.size_y - 1);
+    (0..trips)
+        .scan(
+            (BlizzardCache::new(valley), 0, entrance, exit),
+            |(blizzards, minute, from, to), trip| {
+                let path = do_pathfinding(blizzards, *minute, *from, *to);
+                *minute = path.last().unwrap().0;
+                (*from, *to) = (*to, *from);
+                // the first position of a trip is the last one of the previous trip
+                Some(path.into_iter().skip(usize::from(trip > 0)))
+            },
+        )
+        .flatten()
+}
+
+/// Crosses the valley `trips` times back and forth and returns the minute of the last arrival.
+pub fn cross(valley: &Valley, trips: usize, mut observer: Option<&mut dyn Observer>) -> i32 {
+    let mut last_minute = 0;
+    for (minute, pos) in expedition(valley, trips) {
+        notify(
+            &mut observer,
+            &Expedition {
+                valley,
+                minute,
+                pos,
+            },
+        );
+        last_minute = minute;
+    }
+
+    last_minute
+}
+
+pub fn solve_part1(valley: &Valley) -> i32 {
+    cross(valley, 1, None)
+}
+
+pub fn solve_part2(valley: &Valley) -> i32 {
+    cross(valley, 3, None)
 }
 
+/// Shows the valley at every minute of the expedition.
+pub fn visualize(valley: &Valley, part: u32, observer: &mut dyn Observer) {
+    cross(valley, if part == 1 { 1 } else { 3 }, Some(observer));
+}
+
+#[doc(hidden)]
+#[aoc_generator(day24)]
+pub fn input_generator(input: &str) -> Result<Valley, ParseError> {
+    parse(input)
+}
+
+#[doc(hidden)]
 #[aoc(day24, part1)]
 pub fn part1(valley: &Valley) -> i32 {
-    let start = Pos::new(1, 0);
-    let goal = Pos::new(valley.size_x - 2, valley.size_y - 1);
-    do_pathfinding(valley, 0, start, goal)
+    solve_part1(valley)
 }
 
+#[doc(hidden)]
 #[aoc(day24, part2)]
 pub fn part2(valley: &Valley) -> i32 {
-    let start = Pos::new(1, 0);
-    let goal = Pos::new(valley.size_x - 2, valley.size_y - 1);
-    let start_to_goal = do_pathfinding(valley, 0, start, goal);
-    let goal_to_start = do_pathfinding(valley, start_to_goal, goal, start);
-    do_pathfinding(valley, goal_to_start, start, goal)
+    solve_part2(valley)
 }
 
+pub(crate) static SOLUTION: Day<Valley> = Day {
+    day: 24,
+    parse,
+    part1: |input, _| Ok(solve_part1(input).into()),
+    part2: Some(|input, _| Ok(solve_part2(input).into())),
+};
+
 #[cfg(test)]
 mod tests {
+    use itertools::Itertools;
     use pretty_assertions::assert_eq;
 
     use super::*;
+    use crate::common::input::windows_input;
 
     #[test]
     fn test_1() {
@@ -158,7 +293,8 @@ mod tests {
 #<^v^^>#
 ######.#
 ",
-        );
+        )
+        .unwrap();
         assert_eq!(18, part1(&input))
     }
 
@@ -172,7 +308,69 @@ mod tests {
 #<^v^^>#
 ######.#
 ",
-        );
+        )
+        .unwrap();
         assert_eq!(54, part2(&input))
     }
+
+    #[test]
+    fn test_timeline() {
+        let input = input_generator(
+            r"#.#####
+#.....#
+#>....#
+#.....#
+#...v.#
+#.....#
+#####.#
+",
+        )
+        .unwrap();
+        let positions = |blizzards: Vec<Blizzard>| blizzards.iter().map(|b| b.pos).collect_vec();
+        let mut timeline = input.timeline();
+        assert_eq!(
+            vec![Pos::new(1, 2), Pos::new(4, 4)],
+            positions(timeline.next().unwrap())
+        );
+        assert_eq!(
+            vec![Pos::new(3, 2), Pos::new(4, 1)],
+            positions(timeline.nth(1).unwrap())
+        );
+        // both blizzards are back after 5 minutes
+        assert_eq!(input.blizzards, timeline.nth(2).unwrap());
+    }
+
+    #[test]
+    fn test_expedition() {
+        let input = input_generator(
+            r"#.######
+#>>.<^<#
+#.<..<<#
+#>v.><>#
+#<^v^^>#
+######.#
+",
+        )
+        .unwrap();
+        let steps = expedition(&input, 3).collect_vec();
+        assert_eq!((0, Pos::new(1, 0)), steps[0]);
+        assert_eq!((18, Pos::new(6, 5)), steps[18]);
+        assert_eq!((54, Pos::new(6, 5)), *steps.last().unwrap());
+        assert_eq!(55, steps.len());
+
+        // stop at the first minute the expedition is back at the entrance
+        let back = expedition(&input, 3)
+            .skip(1)
+            .find(|&(_, pos)| pos == Pos::new(1, 0));
+        assert_eq!(Some(41), back.map(|(minute, _)| minute));
+    }
+
+    #[test]
+    fn test_crlf() {
+        let input = parse(&windows_input(include_str!(
+            "../fixtures/day24/example.txt"
+        )))
+        .unwrap();
+        assert_eq!(18, solve_part1(&input));
+    }
 }
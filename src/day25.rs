@@ -1,16 +1,24 @@
+//! Day 25: Full of Hot Air, numbers in the balanced base five SNAFU notation.
+
 use aoc_runner_derive::{aoc, aoc_generator};
 
-#[aoc_generator(day25)]
-pub fn input_generator(input: &str) -> Vec<String> {
-    input
-        .lines()
-        .map(str::trim)
-        .filter(|l| !l.is_empty())
-        .map(str::to_string)
-        .collect()
+use crate::common::input::normalize;
+use crate::common::{parse_lines_with, ParseError};
+use crate::solution::Day;
+
+pub fn parse(input: &str) -> Result<Vec<String>, ParseError> {
+    let input = &normalize(input, true);
+    parse_lines_with(input, |l| {
+        match l.find(|c| !matches!(c, '2' | '1' | '0' | '-' | '=')) {
+            Some(i) => Err(ParseError::new("invalid SNAFU digit").at_column(i + 1)),
+            None => Ok(l.to_string()),
+        }
+    })
+    .map_err(|e| e.in_day(25))
 }
 
-fn from_snafu(s: &str) -> i64 {
+/// The value of a SNAFU number, panics on invalid digits.
+pub fn from_snafu(s: &str) -> i64 {
     fn from_snafu_digit(c: char) -> i8 {
         match c {
             '2' => 2,
@@ -31,7 +39,8 @@ fn from_snafu(s: &str) -> i64 {
     n
 }
 
-fn to_snafu(mut n: i64) -> String {
+/// The SNAFU representation of a non-negative number.
+pub fn to_snafu(mut n: i64) -> String {
     fn to_snafu_digit(d: i64) -> char {
         match d {
             0 => '=',
@@ -59,16 +68,35 @@ fn to_snafu(mut n: i64) -> String {
     s
 }
 
+pub fn solve_part1(input: &[String]) -> String {
+    to_snafu(input.iter().map(|s| from_snafu(s)).sum())
+}
+
+#[doc(hidden)]
+#[aoc_generator(day25)]
+pub fn input_generator(input: &str) -> Result<Vec<String>, ParseError> {
+    parse(input)
+}
+
+#[doc(hidden)]
 #[aoc(day25, part1)]
 pub fn part1(input: &[String]) -> String {
-    to_snafu(input.iter().map(|s| from_snafu(s)).sum())
+    solve_part1(input)
 }
 
+pub(crate) static SOLUTION: Day<Vec<String>> = Day {
+    day: 25,
+    parse,
+    part1: |input, _| Ok(solve_part1(input).into()),
+    part2: None,
+};
+
 #[cfg(test)]
 mod tests {
     use pretty_assertions::assert_eq;
 
     use super::*;
+    use crate::common::input::windows_input;
 
     #[test]
     fn test_from_0_to_ten() {
@@ -99,4 +127,13 @@ mod tests {
         assert_eq!("2-", to_snafu(9));
         assert_eq!("20", to_snafu(10));
     }
+
+    #[test]
+    fn test_crlf() {
+        let input = parse(&windows_input(include_str!(
+            "../fixtures/day25/example.txt"
+        )))
+        .unwrap();
+        assert_eq!("2=-1=0", solve_part1(&input));
+    }
 }
@@ -0,0 +1,930 @@
+//! Seeded random puzzle inputs of a given size for every day.
+
+use std::collections::{BTreeSet, VecDeque};
+
+use itertools::Itertools;
+use num::integer::lcm;
+use rustc_hash::FxHashSet;
+
+/// Small seedable pseudo random number generator (SplitMix64).
+#[derive(Debug, Clone)]
+pub struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        Self { state: seed }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e3779b97f4a7c15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+        z ^ (z >> 31)
+    }
+
+    /// Returns a number in `0..n`.
+    pub fn below(&mut self, n: usize) -> usize {
+        assert!(n > 0, "empty range");
+        ((self.next_u64() as u128 * n as u128) >> 64) as usize
+    }
+
+    /// Returns a number in `lo..=hi`.
+    pub fn range(&mut self, lo: i64, hi: i64) -> i64 {
+        assert!(lo <= hi, "empty range");
+        lo + ((self.next_u64() as u128 * (hi.abs_diff(lo) as u128 + 1)) >> 64) as i64
+    }
+
+    /// Returns `true` with probability `p`.
+    pub fn chance(&mut self, p: f64) -> bool {
+        ((self.next_u64() >> 11) as f64 / (1u64 << 53) as f64) < p
+    }
+
+    pub fn choose<'a, T>(&mut self, items: &'a [T]) -> &'a T {
+        &items[self.below(items.len())]
+    }
+
+    pub fn shuffle<T>(&mut self, items: &mut [T]) {
+        for i in (1..items.len()).rev() {
+            items.swap(i, self.below(i + 1));
+        }
+    }
+}
+
+/// A generated puzzle input together with the parameters it has to be solved with.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Sample {
+    pub input: String,
+    pub params: Vec<(String, String)>,
+}
+
+impl Sample {
+    fn new(input: String) -> Self {
+        Self {
+            input,
+            params: vec![],
+        }
+    }
+
+    fn with_param(mut self, key: &str, value: impl ToString) -> Self {
+        self.params.push((key.to_string(), value.to_string()));
+        self
+    }
+}
+
+/// Generates a random but valid input for `day`, or `None` for unknown days.
+///
+/// The same `seed` always yields the same input. `size` roughly scales the input, e.g. the
+/// number of lines or the side length of a grid. Inputs are kept solvable: day 12 always has
+/// a path, day 15 has exactly one uncovered position within `max`, day 21 has a linear
+/// equation in `humn` and day 24 can be crossed. Day 22 uses the cube net of the example for
+/// `size < 50` and the one of the real inputs otherwise, as part 2 only knows those two.
+pub fn generate(day: u32, seed: u64, size: usize) -> Option<Sample> {
+    let rng = &mut Rng::new(seed ^ ((day as u64) << 56));
+    let size = size.max(1);
+    Some(match day {
+        1 => Sample::new(day01(rng, size)),
+        2 => Sample::new(day02(rng, size)),
+        3 => Sample::new(day03(rng, size)),
+        4 => Sample::new(day04(rng, size)),
+        5 => Sample::new(day05(rng, size)),
+        6 => Sample::new(day06(rng, size)),
+        7 => Sample::new(day07(rng, size)),
+        8 => Sample::new(day08(rng, size)),
+        9 => Sample::new(day09(rng, size)),
+        10 => Sample::new(day10(rng)),
+        11 => Sample::new(day11(rng, size)),
+        12 => Sample::new(day12(rng, size)),
+        13 => Sample::new(day13(rng, size)),
+        14 => Sample::new(day14(rng, size)),
+        15 => day15(rng, size),
+        16 => Sample::new(day16(rng, size)),
+        17 => Sample::new(day17(rng, size)),
+        18 => Sample::new(day18(rng, size)),
+        19 => Sample::new(day19(rng, size)),
+        20 => Sample::new(day20(rng, size)),
+        21 => Sample::new(day21(rng, size)),
+        22 => Sample::new(day22(rng, size)),
+        23 => Sample::new(day23(rng, size)),
+        24 => Sample::new(day24(rng, size)),
+        25 => Sample::new(day25(rng, size)),
+        _ => return None,
+    })
+}
+
+fn lines(lines: impl IntoIterator<Item = String>) -> String {
+    lines.into_iter().map(|l| l + "\n").collect()
+}
+
+fn day01(rng: &mut Rng, size: usize) -> String {
+    (0..size)
+        .map(|_| {
+            let count = rng.range(1, 6);
+            lines((0..count).map(|_| rng.range(1000, 60000).to_string()))
+        })
+        .join("\n")
+}
+
+fn day02(rng: &mut Rng, size: usize) -> String {
+    lines((0..size).map(|_| {
+        format!(
+            "{} {}",
+            rng.choose(&['A', 'B', 'C']),
+            rng.choose(&['X', 'Y', 'Z'])
+        )
+    }))
+}
+
+fn day03(rng: &mut Rng, size: usize) -> String {
+    let letters = ('a'..='z').chain('A'..='Z').collect_vec();
+    let mut result = vec![];
+    for _ in 0..size.div_ceil(3) {
+        // the three rucksacks draw from disjoint pools, so the badge is their only common item
+        let mut pool = letters.clone();
+        rng.shuffle(&mut pool);
+        let badge = pool.pop().unwrap();
+        for pool in pool.chunks(17) {
+            // both compartments draw from disjoint halves of the pool and share one item
+            let (shared, pool) = pool.split_first().unwrap();
+            let (left_pool, right_pool) = pool.split_at(pool.len() / 2);
+            let len = rng.range(3, 12) as usize;
+            let badge_left = rng.chance(0.5);
+            let mut compartment = |pool: &[char], with_badge: bool| {
+                let mut items = vec![*shared];
+                if with_badge {
+                    items.push(badge);
+                }
+                while items.len() < len {
+                    items.push(*rng.choose(pool));
+                }
+                rng.shuffle(&mut items);
+                items.into_iter().collect::<String>()
+            };
+            let left = compartment(left_pool, badge_left);
+            let right = compartment(right_pool, !badge_left);
+            result.push(left + &right);
+        }
+    }
+
+    lines(result)
+}
+
+fn day04(rng: &mut Rng, size: usize) -> String {
+    let mut range = || {
+        let a = rng.range(1, 99);
+        (a, rng.range(a, 99))
+    };
+    lines((0..size).map(|_| {
+        let ((a, b), (c, d)) = (range(), range());
+        format!("{a}-{b},{c}-{d}")
+    }))
+}
+
+fn day05(rng: &mut Rng, size: usize) -> String {
+    let count = size.clamp(2, 9);
+    let mut stacks = (0..count)
+        .map(|_| {
+            (0..rng.range(0, size.min(20) as i64))
+                .map(|_| (b'A' + rng.below(26) as u8) as char)
+                .collect_vec()
+        })
+        .collect_vec();
+    if stacks.iter().all(Vec::is_empty) {
+        stacks[0].push('A');
+    }
+
+    let height = stacks.iter().map(Vec::len).max().unwrap();
+    let mut drawing = (0..height)
+        .rev()
+        .map(|level| {
+            stacks
+                .iter()
+                .map(|s| s.get(level).map_or("   ".to_string(), |c| format!("[{c}]")))
+                .join(" ")
+        })
+        .collect_vec();
+    drawing.push((1..=count).map(|n| format!(" {n} ")).join(" "));
+
+    // simulate the moves so that they never take more crates than a stack holds
+    let mut moves = vec![];
+    for _ in 0..size * 2 {
+        let from = loop {
+            let from = rng.below(count);
+            if !stacks[from].is_empty() {
+                break from;
+            }
+        };
+        let to = (from + 1 + rng.below(count - 1)) % count;
+        let n = rng.range(1, stacks[from].len() as i64) as usize;
+        let len = stacks[from].len();
+        let crates = stacks[from].split_off(len - n);
+        stacks[to].extend(crates);
+        moves.push(format!("move {n} from {} to {}", from + 1, to + 1));
+    }
+
+    lines(drawing) + "\n" + &lines(moves)
+}
+
+fn day06(rng: &mut Rng, size: usize) -> String {
+    let mut signal = (0..size.max(14))
+        .map(|_| (b'a' + rng.below(26) as u8) as char)
+        .collect_vec();
+    let mut marker = ('a'..='z').collect_vec();
+    rng.shuffle(&mut marker);
+    let at = rng.below(signal.len() - 13);
+    signal.splice(at..at + 14, marker.into_iter().take(14));
+    signal.into_iter().collect::<String>() + "\n"
+}
+
+fn day07(rng: &mut Rng, size: usize) -> String {
+    fn name(rng: &mut Rng, taken: &mut FxHashSet<String>, ext: bool) -> String {
+        loop {
+            let len = rng.range(1, 8);
+            let mut name = (0..len)
+                .map(|_| (b'a' + rng.below(26) as u8) as char)
+                .collect::<String>();
+            if ext && rng.chance(0.5) {
+                name += &format!(".{}", ["txt", "dat", "log", "lst", "ext"][rng.below(5)]);
+            }
+            if taken.insert(name.clone()) {
+                return name;
+            }
+        }
+    }
+
+    fn dir(rng: &mut Rng, output: &mut Vec<String>, dirs_left: &mut usize, depth: usize) {
+        let mut taken = FxHashSet::default();
+        let mut subdirs = vec![];
+        output.push("$ ls".to_string());
+        for _ in 0..rng.range(1, 4) {
+            let name = name(rng, &mut taken, true);
+            output.push(format!("{} {name}", rng.range(1000, 300000)));
+        }
+        while *dirs_left > 0 && depth < 8 && subdirs.len() < 3 && rng.chance(0.6) {
+            *dirs_left -= 1;
+            let name = name(rng, &mut taken, false);
+            output.push(format!("dir {name}"));
+            subdirs.push(name);
+        }
+        for name in subdirs {
+            output.push(format!("$ cd {name}"));
+            dir(rng, output, dirs_left, depth + 1);
+            output.push("$ cd ..".to_string());
+        }
+    }
+
+    let mut output = vec!["$ cd /".to_string()];
+    let mut dirs_left = size;
+    dir(rng, &mut output, &mut dirs_left, 0);
+    lines(output)
+}
+
+fn day08(rng: &mut Rng, size: usize) -> String {
+    lines((0..size).map(|_| (0..size).map(|_| rng.below(10).to_string()).join("")))
+}
+
+fn day09(rng: &mut Rng, size: usize) -> String {
+    lines((0..size).map(|_| format!("{} {}", rng.choose(&['U', 'D', 'L', 'R']), rng.range(1, 20))))
+}
+
+fn day10(rng: &mut Rng) -> String {
+    // exactly the 240 cycles needed to draw the screen
+    let mut cycles = 0;
+    let mut program = vec![];
+    while cycles < 240 {
+        if cycles < 239 && rng.chance(0.7) {
+            program.push(format!("addx {}", rng.range(-20, 20)));
+            cycles += 2;
+        } else {
+            program.push("noop".to_string());
+            cycles += 1;
+        }
+    }
+
+    lines(program)
+}
+
+fn day11(rng: &mut Rng, size: usize) -> String {
+    let count = size.clamp(2, 9);
+    let mut primes = vec![2, 3, 5, 7, 11, 13, 17, 19, 23];
+    rng.shuffle(&mut primes);
+    (0..count)
+        .map(|i| {
+            let items = (0..rng.range(1, 5))
+                .map(|_| rng.range(50, 99).to_string())
+                .join(", ");
+            let operation = match rng.below(3) {
+                0 => format!("old + {}", rng.range(1, 8)),
+                1 => format!("old * {}", rng.range(2, 19)),
+                _ => "old * old".to_string(),
+            };
+            let true_target = (i + 1 + rng.below(count - 1)) % count;
+            let false_target = loop {
+                let target = rng.below(count);
+                if target != i && (target != true_target || count == 2) {
+                    break target;
+                }
+            };
+            format!(
+                "Monkey {i}:\n  Starting items: {items}\n  Operation: new = {operation}\n  Test: divisible by {}\n    If true: throw to monkey {true_target}\n    If false: throw to monkey {false_target}\n",
+                primes[i]
+            )
+        })
+        .join("\n")
+}
+
+fn day12(rng: &mut Rng, size: usize) -> String {
+    // a monotone path from S to E climbs at most one step at a time, so E is always reachable
+    let (width, height) = (size.max(14), (size / 2).max(14));
+    let mut grid = (0..height)
+        .map(|_| (0..width).map(|_| b'a' + rng.below(26) as u8).collect_vec())
+        .collect_vec();
+
+    let mut path = vec![(0, 0)];
+    let (mut x, mut y) = (0, 0);
+    while (x, y) != (width - 1, height - 1) {
+        if y == height - 1 || (x < width - 1 && rng.chance(0.5)) {
+            x += 1;
+        } else {
+            y += 1;
+        }
+        path.push((x, y));
+    }
+
+    let mut climbs = vec![true; 25];
+    climbs.resize(path.len() - 1, false);
+    rng.shuffle(&mut climbs);
+    let mut h = b'a';
+    grid[0][0] = b'S';
+    for (&(x, y), climb) in path[1..].iter().zip(climbs) {
+        h += climb as u8;
+        grid[y][x] = h;
+    }
+    grid[height - 1][width - 1] = b'E';
+
+    lines(grid.into_iter().map(|row| String::from_utf8(row).unwrap()))
+}
+
+fn day13(rng: &mut Rng, size: usize) -> String {
+    fn packet(rng: &mut Rng, depth: usize) -> String {
+        let items = (0..rng.range(0, 4))
+            .map(|_| {
+                if depth < 3 && rng.chance(0.3) {
+                    packet(rng, depth + 1)
+                } else {
+                    rng.range(0, 10).to_string()
+                }
+            })
+            .join(",");
+        format!("[{items}]")
+    }
+
+    (0..size)
+        .map(|_| format!("{}\n{}\n", packet(rng, 0), packet(rng, 0)))
+        .join("\n")
+}
+
+fn day14(rng: &mut Rng, size: usize) -> String {
+    loop {
+        let paths = (0..size)
+            .map(|_| {
+                let (mut x, mut y) = (rng.range(480, 520), rng.range(2, 2 + size as i64));
+                let mut points = vec![(x, y)];
+                let mut horizontal = rng.chance(0.5);
+                for _ in 0..rng.range(1, 4) {
+                    let delta = rng.range(1, 6) * if rng.chance(0.5) { -1 } else { 1 };
+                    if horizontal {
+                        x += delta;
+                    } else {
+                        y = (y + delta).max(1);
+                    }
+                    horizontal = !horizontal;
+                    points.push((x, y));
+                }
+                points
+            })
+            .collect_vec();
+        if sand_escapes(&paths) {
+            return lines(
+                paths
+                    .iter()
+                    .map(|path| path.iter().map(|(x, y)| format!("{x},{y}")).join(" -> ")),
+            );
+        }
+    }
+}
+
+/// Whether sand eventually falls past the lowest rock instead of filling a basin up to the
+/// source, which would keep part 1 from finishing.
+fn sand_escapes(paths: &[Vec<(i64, i64)>]) -> bool {
+    let mut blocked = FxHashSet::default();
+    for path in paths {
+        for window in path.windows(2) {
+            let ((x0, y0), (x1, y1)) = (window[0], window[1]);
+            for x in x0.min(x1)..=x0.max(x1) {
+                for y in y0.min(y1)..=y0.max(y1) {
+                    blocked.insert((x, y));
+                }
+            }
+        }
+    }
+    let max_y = blocked.iter().map(|&(_, y)| y).max().unwrap_or(0);
+
+    while !blocked.contains(&(500, 0)) {
+        let (mut x, mut y) = (500, 0);
+        loop {
+            if y > max_y {
+                return true;
+            }
+            match [x, x - 1, x + 1]
+                .into_iter()
+                .find(|&nx| !blocked.contains(&(nx, y + 1)))
+            {
+                Some(nx) => (x, y) = (nx, y + 1),
+                None => break,
+            }
+        }
+        blocked.insert((x, y));
+    }
+
+    false
+}
+
+fn day15(rng: &mut Rng, size: usize) -> Sample {
+    type P = (i64, i64);
+    fn dist(a: P, b: P) -> i64 {
+        (a.0 - b.0).abs() + (a.1 - b.1).abs()
+    }
+
+    let max = size.clamp(10, 200) as i64;
+    let hidden = (rng.range(0, max), rng.range(0, max));
+    let mut sensors: Vec<(P, i64)> = vec![];
+    // with a gap around the hidden beacon, only the collinear edges of the diagonal sensors
+    // run through it, and no two edges cross there
+    let gap = rng.chance(0.5);
+    let add_sensor = |rng: &mut Rng, sensors: &mut Vec<(P, i64)>, s: P, diagonal: bool| {
+        // the radius just misses the hidden beacon
+        let r = dist(s, hidden)
+            - if gap && !diagonal && dist(s, hidden) > 2 {
+                2
+            } else {
+                1
+            };
+        let dx = rng.range(-r, r);
+        let dy = (r - dx.abs()) * if rng.chance(0.5) { -1 } else { 1 };
+        sensors.push((s, r));
+        format!(
+            "Sensor at x={}, y={}: closest beacon is at x={}, y={}",
+            s.0,
+            s.1,
+            s.0 + dx,
+            s.1 + dy
+        )
+    };
+
+    let mut output = vec![];
+    // diagonal sensors cover all neighbours of the hidden beacon
+    for sign in [1, -1] {
+        let k = rng.range(1, max / 2);
+        let s = (hidden.0 + sign * k, hidden.1 + sign * k);
+        output.push(add_sensor(rng, &mut sensors, s, true));
+    }
+    for _ in 0..rng.range(1, 4) {
+        let s = (
+            rng.range(-max / 2, max * 3 / 2),
+            rng.range(-max / 2, max * 3 / 2),
+        );
+        if dist(s, hidden) >= 2 {
+            output.push(add_sensor(rng, &mut sensors, s, false));
+        }
+    }
+    let mut uncovered = (0..=max)
+        .cartesian_product(0..=max)
+        .filter(|&p| p != hidden && sensors.iter().all(|&(s, r)| dist(s, p) > r))
+        .collect_vec();
+    while !uncovered.is_empty() {
+        let s = *rng.choose(&uncovered);
+        output.push(add_sensor(rng, &mut sensors, s, false));
+        let &(s, r) = sensors.last().unwrap();
+        uncovered.retain(|&p| dist(s, p) > r);
+    }
+
+    rng.shuffle(&mut output);
+    Sample::new(lines(output))
+        .with_param("row", rng.range(0, max))
+        .with_param("max", max)
+}
+
+fn two_letter_names(rng: &mut Rng, count: usize, first: &str) -> Vec<String> {
+    let mut names = (b'A'..=b'Z')
+        .cartesian_product(b'A'..=b'Z')
+        .map(|(a, b)| String::from_utf8(vec![a, b]).unwrap())
+        .filter(|name| name != first)
+        .collect_vec();
+    rng.shuffle(&mut names);
+    names.truncate(count - 1);
+    names.insert(0, first.to_string());
+    names
+}
+
+fn day16(rng: &mut Rng, size: usize) -> String {
+    let count = size.clamp(2, 60);
+    let names = two_letter_names(rng, count, "AA");
+    let mut tunnels = vec![BTreeSet::new(); count];
+    let mut connect = |a: usize, b: usize| {
+        if a != b {
+            tunnels[a].insert(b);
+            tunnels[b].insert(a);
+        }
+    };
+    // a random spanning tree keeps all valves reachable
+    for i in 1..count {
+        let j = rng.below(i);
+        connect(i, j);
+    }
+    for _ in 0..count / 3 {
+        let (a, b) = (rng.below(count), rng.below(count));
+        connect(a, b);
+    }
+
+    let mut flowing = 0;
+    let mut output = (0..count)
+        .map(|i| {
+            let flow = if i > 0 && flowing < 15 && rng.chance(0.5) {
+                flowing += 1;
+                rng.range(1, 25)
+            } else {
+                0
+            };
+            let targets = tunnels[i].iter().map(|&j| &names[j]).join(", ");
+            if tunnels[i].len() == 1 {
+                format!(
+                    "Valve {} has flow rate={flow}; tunnel leads to valve {targets}",
+                    names[i]
+                )
+            } else {
+                format!(
+                    "Valve {} has flow rate={flow}; tunnels lead to valves {targets}",
+                    names[i]
+                )
+            }
+        })
+        .collect_vec();
+    rng.shuffle(&mut output);
+    lines(output)
+}
+
+fn day17(rng: &mut Rng, size: usize) -> String {
+    // very short or one-sided patterns leave a column empty forever, so the surface never repeats
+    loop {
+        let jets = (0..size.max(40))
+            .map(|_| rng.choose(&['<', '>']))
+            .collect::<String>();
+        let lefts = jets.chars().filter(|&c| c == '<').count();
+        if (jets.len() / 3..=jets.len() * 2 / 3).contains(&lefts) {
+            return jets + "\n";
+        }
+    }
+}
+
+fn day18(rng: &mut Rng, size: usize) -> String {
+    let extent = ((size as f64).cbrt() * 1.5).ceil().max(3.0) as i64;
+    let mut cubes = FxHashSet::default();
+    while cubes.len() < size.min((extent * extent * extent) as usize) {
+        cubes.insert((
+            rng.range(1, extent),
+            rng.range(1, extent),
+            rng.range(1, extent),
+        ));
+    }
+
+    let mut cubes = cubes.into_iter().collect_vec();
+    cubes.sort_unstable();
+    rng.shuffle(&mut cubes);
+    lines(cubes.into_iter().map(|(x, y, z)| format!("{x},{y},{z}")))
+}
+
+fn day19(rng: &mut Rng, size: usize) -> String {
+    lines((1..=size.min(30)).map(|i| {
+        format!(
+            "Blueprint {i}: Each ore robot costs {} ore. Each clay robot costs {} ore. Each obsidian robot costs {} ore and {} clay. Each geode robot costs {} ore and {} obsidian.",
+            rng.range(2, 4),
+            rng.range(2, 4),
+            rng.range(2, 4),
+            rng.range(5, 20),
+            rng.range(2, 4),
+            rng.range(5, 20)
+        )
+    }))
+}
+
+fn day20(rng: &mut Rng, size: usize) -> String {
+    let mut numbers = (0..size.max(2))
+        .map(|_| {
+            let n = rng.range(1, 10000);
+            if rng.chance(0.5) {
+                -n
+            } else {
+                n
+            }
+        })
+        .collect_vec();
+    let zero = rng.below(numbers.len());
+    numbers[zero] = 0;
+    lines(numbers.into_iter().map(|n| n.to_string()))
+}
+
+fn day21(rng: &mut Rng, size: usize) -> String {
+    enum Node {
+        Num(i64),
+        Op(usize, char, usize),
+    }
+
+    // builds the tree top-down from the value each monkey has to yell, so that all values
+    // are positive integers and no monkey divides by zero
+    fn build(rng: &mut Rng, nodes: &mut Vec<Node>, value: i64, leaves: usize) -> usize {
+        let node = if leaves == 1 {
+            Node::Num(value)
+        } else {
+            let left_leaves = rng.range(1, leaves as i64 - 1) as usize;
+            let ops = match (value >= 2, value < 100_000) {
+                (true, true) => &['+', '-', '*', '/'][..],
+                (true, false) => &['+', '*'][..],
+                (false, true) => &['-', '*', '/'][..],
+                (false, false) => unreachable!(),
+            };
+            let op = *rng.choose(ops);
+            let (a, b) = match op {
+                '+' => {
+                    let a = rng.range(1, value - 1);
+                    (a, value - a)
+                }
+                '-' => {
+                    let b = rng.range(1, 20);
+                    (value + b, b)
+                }
+                '*' => {
+                    let divisors = (1..=value.min(1000))
+                        .filter(|d| value % d == 0)
+                        .collect_vec();
+                    let a = *rng.choose(&divisors);
+                    (a, value / a)
+                }
+                _ => {
+                    let b = rng.range(1, 5);
+                    (value * b, b)
+                }
+            };
+            let a = build(rng, nodes, a, left_leaves);
+            let b = build(rng, nodes, b, leaves - left_leaves);
+            Node::Op(a, op, b)
+        };
+        nodes.push(node);
+        nodes.len() - 1
+    }
+
+    let mut nodes = vec![];
+    let value = rng.range(1, 1000);
+    let root = build(rng, &mut nodes, value, size.max(2));
+
+    // humn must not end up in a divisor, otherwise part 2 would not be linear
+    let mut candidates = vec![];
+    let mut stack = vec![(root, false)];
+    while let Some((i, in_divisor)) = stack.pop() {
+        match nodes[i] {
+            Node::Num(_) if !in_divisor => candidates.push(i),
+            Node::Num(_) => {}
+            Node::Op(a, op, b) => {
+                stack.push((a, in_divisor));
+                stack.push((b, in_divisor || op == '/'));
+            }
+        }
+    }
+    let humn = *rng.choose(&candidates);
+
+    let mut taken = FxHashSet::from_iter(["root".to_string(), "humn".to_string()]);
+    let names = (0..nodes.len())
+        .map(|i| match i {
+            _ if i == root => "root".to_string(),
+            _ if i == humn => "humn".to_string(),
+            _ => loop {
+                let name = (0..4)
+                    .map(|_| (b'a' + rng.below(26) as u8) as char)
+                    .collect::<String>();
+                if taken.insert(name.clone()) {
+                    break name;
+                }
+            },
+        })
+        .collect_vec();
+
+    let mut output = nodes
+        .iter()
+        .enumerate()
+        .map(|(i, node)| match *node {
+            Node::Num(n) => format!("{}: {n}", names[i]),
+            Node::Op(a, op, b) => format!("{}: {} {op} {}", names[i], names[a], names[b]),
+        })
+        .collect_vec();
+    rng.shuffle(&mut output);
+    lines(output)
+}
+
+fn day22(rng: &mut Rng, size: usize) -> String {
+    const EXAMPLE_NET: [(usize, usize); 6] = [(2, 0), (0, 1), (1, 1), (2, 1), (2, 2), (3, 2)];
+    const REAL_NET: [(usize, usize); 6] = [(1, 0), (2, 0), (1, 1), (0, 2), (1, 2), (0, 3)];
+
+    let (face_size, net) = if size < 50 {
+        (4, EXAMPLE_NET)
+    } else {
+        (50, REAL_NET)
+    };
+    let rows = net.iter().map(|&(_, y)| y).max().unwrap() + 1;
+    let mut map = vec![];
+    for face_y in 0..rows {
+        let width = net
+            .iter()
+            .filter(|&&(_, y)| y == face_y)
+            .map(|&(x, _)| x + 1)
+            .max()
+            .unwrap();
+        for y in 0..face_size {
+            map.push(
+                (0..width * face_size)
+                    .map(|x| {
+                        if !net.contains(&(x / face_size, face_y)) {
+                            ' '
+                        } else if (face_y, y, x % face_size) != (0, 0, 0) && rng.chance(0.1) {
+                            '#'
+                        } else {
+                            '.'
+                        }
+                    })
+                    .collect::<String>(),
+            );
+        }
+    }
+
+    let mut path = rng.range(1, 2 * face_size as i64).to_string();
+    for _ in 0..size {
+        path.push(*rng.choose(&['L', 'R']));
+        path += &rng.range(1, 2 * face_size as i64).to_string();
+    }
+
+    lines(map) + "\n" + &path + "\n"
+}
+
+fn day23(rng: &mut Rng, size: usize) -> String {
+    let mut grove = (0..size)
+        .map(|_| {
+            (0..size)
+                .map(|_| if rng.chance(0.3) { '#' } else { '.' })
+                .collect::<Vec<_>>()
+        })
+        .collect_vec();
+    if !grove.iter().flatten().any(|&c| c == '#') {
+        let (y, x) = (rng.below(size), rng.below(size));
+        grove[y][x] = '#';
+    }
+    lines(grove.into_iter().map(String::from_iter))
+}
+
+fn day24(rng: &mut Rng, size: usize) -> String {
+    let (width, height) = (size.max(3), (size / 2).max(2));
+    loop {
+        let valley = (0..height)
+            .map(|_| {
+                (0..width)
+                    .map(|_| {
+                        if rng.chance(0.3) {
+                            *rng.choose(&['^', '>', 'v', '<'])
+                        } else {
+                            '.'
+                        }
+                    })
+                    .collect_vec()
+            })
+            .collect_vec();
+        if !valley_crossable(&valley) {
+            continue;
+        }
+
+        let mut output = vec![format!("#.{}", "#".repeat(width))];
+        output.extend(
+            valley
+                .iter()
+                .map(|row| format!("#{}#", row.iter().collect::<String>())),
+        );
+        output.push(format!("{}.#", "#".repeat(width)));
+        return lines(output);
+    }
+}
+
+/// Checks whether the expedition can go from the entrance to the exit, back and once more.
+fn valley_crossable(valley: &[Vec<char>]) -> bool {
+    let (width, height) = (valley[0].len() as i64, valley.len() as i64);
+    let period = lcm(width, height);
+    let blocked = |x: i64, y: i64, t: i64| {
+        if y < 0 || y >= height {
+            return false;
+        }
+        let row = &valley[y as usize];
+        let col = |y: i64| valley[y.rem_euclid(height) as usize][x as usize];
+        row[(x - t).rem_euclid(width) as usize] == '>'
+            || row[(x + t).rem_euclid(width) as usize] == '<'
+            || col(y - t) == 'v'
+            || col(y + t) == '^'
+    };
+    let start = (0, -1);
+    let end = (width - 1, height);
+    let leg = |from: (i64, i64), to: (i64, i64), t0: i64| -> Option<i64> {
+        let mut seen = FxHashSet::default();
+        let mut queue = VecDeque::from([(from, t0)]);
+        while let Some(((x, y), t)) = queue.pop_front() {
+            if (x, y) == to {
+                return Some(t);
+            }
+            for (dx, dy) in [(0, 0), (1, 0), (-1, 0), (0, 1), (0, -1)] {
+                let (nx, ny) = (x + dx, y + dy);
+                let inside = nx >= 0 && nx < width && ny >= 0 && ny < height;
+                if (inside || (nx, ny) == from || (nx, ny) == to)
+                    && !blocked(nx, ny, t + 1)
+                    && seen.insert(((nx, ny), (t + 1) % period))
+                {
+                    queue.push_back(((nx, ny), t + 1));
+                }
+            }
+        }
+        None
+    };
+
+    leg(start, end, 0)
+        .and_then(|t| leg(end, start, t))
+        .and_then(|t| leg(start, end, t))
+        .is_some()
+}
+
+fn day25(rng: &mut Rng, size: usize) -> String {
+    lines((0..size).map(|_| {
+        let digits = rng.range(1, 12) as u32;
+        let mut n = rng.range(1, 10i64.pow(digits));
+        let mut snafu = vec![];
+        while n != 0 {
+            let digit = (n + 2) % 5 - 2;
+            snafu.push(['=', '-', '0', '1', '2'][(digit + 2) as usize]);
+            n = (n - digit) / 5;
+        }
+        snafu.into_iter().rev().collect::<String>()
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::registry;
+    use crate::runner::DAYS;
+
+    #[test]
+    fn test_rng() {
+        let mut rng = Rng::new(42);
+        let values = (0..1000).map(|_| rng.range(-3, 3)).collect_vec();
+        assert!(values.iter().all(|v| (-3..=3).contains(v)));
+        assert_eq!(7, values.iter().unique().count());
+        assert_eq!(values, {
+            let mut rng = Rng::new(42);
+            (0..1000).map(|_| rng.range(-3, 3)).collect_vec()
+        });
+    }
+
+    #[test]
+    fn test_deterministic() {
+        for day in DAYS {
+            assert_eq!(generate(day, 7, 10), generate(day, 7, 10));
+        }
+        assert_ne!(generate(1, 1, 10), generate(1, 2, 10));
+        assert_eq!(None, generate(26, 0, 10));
+    }
+
+    #[test]
+    fn test_round_trip() {
+        for day in DAYS {
+            let solution = registry().day(day).unwrap();
+            for seed in 0..5 {
+                for size in [1, 5, 20] {
+                    let sample = generate(day, seed, size).unwrap();
+                    let mut params = solution.params();
+                    for (key, value) in &sample.params {
+                        params.set(key, value).unwrap();
+                    }
+                    if let Err(e) = solution.parse(&sample.input) {
+                        panic!("seed {seed}, size {size}: {e}\n{}", sample.input);
+                    }
+                }
+            }
+        }
+    }
+}
@@ -1,30 +1,156 @@
+//! Solutions for Advent of Code 2022.
+//!
+//! Every `dayNN` module can be used as a library for its puzzle: it exposes the domain types,
+//! `parse` to parse the puzzle input into them and `solve_part1`/`solve_part2` to solve it, with
+//! `part1_with`/`part2_with` taking the [`PuzzleParams`](solution::PuzzleParams) where a day has
+//! any. None of these depend on aoc-runner, whose `input_generator`/`part1`/`part2` entry points
+//! just call them. All days can also be run type-erased through [`registry`].
+
+use std::collections::BTreeMap;
+
 use aoc_runner_derive::aoc_lib;
+use once_cell::sync::Lazy;
+
+use crate::solution::Solution;
 
+pub mod answer;
+pub mod bench;
 pub mod common;
-mod day01;
-mod day02;
-mod day03;
-mod day04;
-mod day05;
-mod day06;
-mod day07;
-mod day08;
-mod day09;
-mod day10;
-mod day11;
-mod day12;
-mod day13;
-mod day14;
-mod day15;
-mod day16;
-mod day17;
-mod day18;
-mod day19;
-mod day20;
-mod day21;
-mod day22;
-mod day23;
-mod day24;
-mod day25;
+pub mod day01;
+pub mod day02;
+pub mod day03;
+pub mod day04;
+pub mod day05;
+pub mod day06;
+pub mod day07;
+pub mod day08;
+pub mod day09;
+pub mod day10;
+pub mod day11;
+pub mod day12;
+pub mod day13;
+pub mod day14;
+pub mod day15;
+pub mod day16;
+pub mod day17;
+pub mod day18;
+pub mod day19;
+pub mod day20;
+pub mod day21;
+pub mod day22;
+pub mod day23;
+pub mod day24;
+pub mod day25;
+pub mod gen;
+#[cfg(test)]
+mod reference;
+pub mod runner;
+pub mod solution;
+pub mod verify;
+pub mod visualize;
+
+static SOLUTIONS: [&dyn Solution; 25] = [
+    &day01::SOLUTION,
+    &day02::SOLUTION,
+    &day03::SOLUTION,
+    &day04::SOLUTION,
+    &day05::SOLUTION,
+    &day06::SOLUTION,
+    &day07::SOLUTION,
+    &day08::SOLUTION,
+    &day09::SOLUTION,
+    &day10::SOLUTION,
+    &day11::SOLUTION,
+    &day12::SOLUTION,
+    &day13::SOLUTION,
+    &day14::SOLUTION,
+    &day15::SOLUTION,
+    &day16::SOLUTION,
+    &day17::SOLUTION,
+    &day18::SOLUTION,
+    &day19::SOLUTION,
+    &day20::SOLUTION,
+    &day21::SOLUTION,
+    &day22::SOLUTION,
+    &day23::SOLUTION,
+    &day24::SOLUTION,
+    &day25::SOLUTION,
+];
+
+/// All solutions keyed by `(day, part)`.
+pub struct Registry {
+    parts: BTreeMap<(u32, u32), &'static dyn Solution>,
+}
+
+impl Registry {
+    fn new(solutions: &[&'static dyn Solution]) -> Self {
+        let mut parts = BTreeMap::new();
+        for &solution in solutions {
+            parts.insert((solution.day(), 1), solution);
+            if solution.has_part2() {
+                parts.insert((solution.day(), 2), solution);
+            }
+        }
+
+        Self { parts }
+    }
+
+    pub fn get(&self, day: u32, part: u32) -> Option<&'static dyn Solution> {
+        self.parts.get(&(day, part)).copied()
+    }
+
+    pub fn day(&self, day: u32) -> Option<&'static dyn Solution> {
+        self.get(day, 1)
+    }
+
+    /// Iterates over all `(day, part)` pairs in order.
+    pub fn iter(&self) -> impl Iterator<Item = ((u32, u32), &'static dyn Solution)> + '_ {
+        self.parts.iter().map(|(&key, &solution)| (key, solution))
+    }
+
+    /// Iterates over the solutions of all days in order.
+    pub fn days(&self) -> impl Iterator<Item = &'static dyn Solution> + '_ {
+        self.iter()
+            .filter(|&((_, part), _)| part == 1)
+            .map(|(_, solution)| solution)
+    }
+
+    /// Iterates over the parts of `day` in order.
+    pub fn parts(&self, day: u32) -> impl Iterator<Item = u32> + '_ {
+        self.parts
+            .range((day, 0)..=(day, u32::MAX))
+            .map(|(&(_, part), _)| part)
+    }
+}
+
+pub fn registry() -> &'static Registry {
+    static REGISTRY: Lazy<Registry> = Lazy::new(|| Registry::new(&SOLUTIONS));
+    &REGISTRY
+}
 
 aoc_lib! { year = 2022 }
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::answer::Answer;
+
+    #[test]
+    fn test_registry() {
+        let registry = registry();
+        assert_eq!(
+            (1..=25).collect::<Vec<_>>(),
+            registry.days().map(|s| s.day()).collect::<Vec<_>>()
+        );
+        assert_eq!(49, registry.iter().count());
+        assert!(registry.get(25, 2).is_none());
+
+        let day = registry.get(1, 2).unwrap();
+        let input = day.parse("1000\n2000\n\n4000\n\n500\n").unwrap();
+        let params = day.params();
+        assert_eq!(Ok(Answer::Int(4000)), day.part1(&input, &*params));
+        assert_eq!(Some(Ok(Answer::Int(7500))), day.part2(&input, &*params));
+    }
+}
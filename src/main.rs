@@ -0,0 +1,690 @@
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use std::time::{Duration, Instant};
+use std::{fs, io};
+
+use anyhow::{anyhow, bail, Context, Result};
+
+use aoc2022::answer::Answer;
+use aoc2022::bench::{self, bench_day, Measurement};
+use aoc2022::gen::generate;
+use aoc2022::registry;
+use aoc2022::runner::{run, run_days, PartAnswer, RunError, DAYS};
+use aoc2022::verify::{format_table, load_fixtures, verify, Summary};
+use aoc2022::visualize::{self, simulate, ImageFormat, Recorder, VisualizeError};
+
+const USAGE: &str = "\
+usage:
+  aoc2022 run <day> [part] --input <file|-> [--expect <answer>]... [--param <key=value>]...
+  aoc2022 run --all [--input-dir <dir>] [--parallel]
+  aoc2022 verify [--fixtures <dir>]
+  aoc2022 bench [day] [--iterations <n>] [--input-dir <dir>] [--format <table|json|csv>]
+                [--baseline <file>] [--threshold <percent>]
+  aoc2022 gen <day> [--seed <n>] [--size <n>]
+  aoc2022 visualize <day> [part] --input <file|-> [--format <terminal|text|ppm|pgm>]
+                    [--output <dir>] [--every <n>] [--delay <ms>] [--scale <n>]
+
+options:
+  --input <file|->     read the puzzle input from <file>, '-' reads from stdin
+  --expect <answer>    compare the answers (in part order) with the expected ones, use '\\n'
+                       for line breaks
+  --param <key=value>  override a puzzle parameter of the day, e.g. row=10 for day 15
+  --all                run every day with the inputs from the input directory and print how
+                       long each day took
+  --parallel           solve the days concurrently, the answers are printed in order anyway
+  --input-dir <dir>    directory containing day<N>.txt files [default: input/2022]
+  --fixtures <dir>     directory containing the example fixtures [default: fixtures]
+  --iterations <n>     number of timed runs of each stage [default: 10]
+  --format <format>    output format of the measurements [default: table] or of the frames
+                       [default: terminal]
+  --baseline <file>    compare the median times with a file written by --format json
+  --threshold <pct>    slowdown in percent that counts as a regression [default: 10]
+  --seed <n>           seed of the generated input [default: 0]
+  --size <n>           rough size of the generated input, e.g. lines or grid width [default: 10]
+  --output <dir>       directory the ppm or pgm frames are written to [default: frames]
+  --every <n>          only keep every n-th frame [default: 1]
+  --delay <ms>         pause between frames in the terminal [default: 50]
+  --scale <n>          width and height of a character in the images in pixels [default: 4]
+
+exit codes:
+  0  success
+  1  usage or i/o error, or a puzzle without an answer for the given parameters
+  2  the puzzle input (or a fixture) could not be parsed
+  3  an answer did not match the expected answer
+  4  a benchmark regressed against the baseline";
+
+const EXIT_ERROR: u8 = 1;
+const EXIT_PARSE_ERROR: u8 = 2;
+const EXIT_WRONG_ANSWER: u8 = 3;
+const EXIT_REGRESSION: u8 = 4;
+
+#[derive(Debug, Copy, Clone)]
+enum Format {
+    Table,
+    Json,
+    Csv,
+}
+
+#[derive(Debug, Clone)]
+enum FrameFormat {
+    Terminal {
+        delay: Duration,
+    },
+    Text,
+    Image {
+        format: ImageFormat,
+        scale: usize,
+        output: PathBuf,
+    },
+}
+
+#[derive(Debug)]
+enum Command {
+    Run {
+        day: u32,
+        part: Option<u32>,
+        input: String,
+        expected: Vec<String>,
+        params: Vec<(String, String)>,
+    },
+    RunAll {
+        input_dir: PathBuf,
+        parallel: bool,
+    },
+    Verify {
+        fixtures: PathBuf,
+    },
+    Gen {
+        day: u32,
+        seed: u64,
+        size: usize,
+    },
+    Visualize {
+        day: u32,
+        part: u32,
+        input: String,
+        format: FrameFormat,
+        every: usize,
+    },
+    Bench {
+        day: Option<u32>,
+        iterations: usize,
+        input_dir: PathBuf,
+        format: Format,
+        baseline: Option<PathBuf>,
+        threshold: f64,
+    },
+}
+
+fn parse_args(mut args: impl Iterator<Item = String>) -> Result<Command> {
+    match args.next().as_deref() {
+        Some("run") => parse_run_args(args),
+        Some("verify") => parse_verify_args(args),
+        Some("bench") => parse_bench_args(args),
+        Some("gen") => parse_gen_args(args),
+        Some("visualize") => parse_visualize_args(args),
+        Some(cmd) => bail!("unknown command '{cmd}'"),
+        None => bail!("missing command"),
+    }
+}
+
+fn parse_verify_args(mut args: impl Iterator<Item = String>) -> Result<Command> {
+    let mut fixtures = None;
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--fixtures" => {
+                fixtures = Some(PathBuf::from(
+                    args.next()
+                        .ok_or_else(|| anyhow!("missing value for --fixtures"))?,
+                ))
+            }
+            _ if arg.starts_with("--") => bail!("unknown option '{arg}'"),
+            _ => bail!("too many arguments"),
+        }
+    }
+
+    Ok(Command::Verify {
+        fixtures: fixtures.unwrap_or_else(|| PathBuf::from("fixtures")),
+    })
+}
+
+fn parse_gen_args(mut args: impl Iterator<Item = String>) -> Result<Command> {
+    let mut day = None;
+    let mut seed = 0;
+    let mut size = 10;
+    while let Some(arg) = args.next() {
+        let mut value = |name: &str| {
+            args.next()
+                .ok_or_else(|| anyhow!("missing value for {name}"))
+        };
+        match arg.as_str() {
+            "--seed" => seed = value("--seed")?.parse().context("invalid --seed")?,
+            "--size" => size = value("--size")?.parse().context("invalid --size")?,
+            _ if arg.starts_with("--") => bail!("unknown option '{arg}'"),
+            _ if day.is_none() => {
+                day = Some(
+                    arg.parse()
+                        .with_context(|| format!("invalid day '{arg}'"))?,
+                )
+            }
+            _ => bail!("too many arguments"),
+        }
+    }
+
+    Ok(Command::Gen {
+        day: day.ok_or_else(|| anyhow!("missing day"))?,
+        seed,
+        size,
+    })
+}
+
+fn parse_visualize_args(mut args: impl Iterator<Item = String>) -> Result<Command> {
+    let mut positional = vec![];
+    let mut input = None;
+    let mut format = None;
+    let mut output = PathBuf::from("frames");
+    let mut every = 1;
+    let mut delay = Duration::from_millis(50);
+    let mut scale = 4;
+    while let Some(arg) = args.next() {
+        let mut value = |name: &str| {
+            args.next()
+                .ok_or_else(|| anyhow!("missing value for {name}"))
+        };
+        match arg.as_str() {
+            "--input" => input = Some(value("--input")?),
+            "--format" => format = Some(value("--format")?),
+            "--output" => output = PathBuf::from(value("--output")?),
+            "--every" => {
+                every = value("--every")?.parse().context("invalid --every")?;
+                if every == 0 {
+                    bail!("--every must be at least 1");
+                }
+            }
+            "--delay" => {
+                delay = Duration::from_millis(value("--delay")?.parse().context("invalid --delay")?)
+            }
+            "--scale" => {
+                scale = value("--scale")?.parse().context("invalid --scale")?;
+                if scale == 0 {
+                    bail!("--scale must be at least 1");
+                }
+            }
+            _ if arg.starts_with("--") => bail!("unknown option '{arg}'"),
+            _ => positional.push(arg),
+        }
+    }
+
+    let (day, part) = match positional.as_slice() {
+        [day] => (day, None),
+        [day, part] => (day, Some(part)),
+        [] => bail!("missing day"),
+        _ => bail!("too many arguments"),
+    };
+    let day = day
+        .parse()
+        .with_context(|| format!("invalid day '{day}'"))?;
+    let part = part
+        .map(|part| part.parse())
+        .transpose()
+        .context("invalid part")?
+        .unwrap_or(1);
+    let image = |format| FrameFormat::Image {
+        format,
+        scale,
+        output: output.clone(),
+    };
+    let format = match format.as_deref().unwrap_or("terminal") {
+        "terminal" => FrameFormat::Terminal { delay },
+        "text" => FrameFormat::Text,
+        "ppm" => image(ImageFormat::Ppm),
+        "pgm" => image(ImageFormat::Pgm),
+        format => bail!("unknown format '{format}'"),
+    };
+    Ok(Command::Visualize {
+        day,
+        part,
+        input: input.ok_or_else(|| anyhow!("missing --input"))?,
+        format,
+        every,
+    })
+}
+
+fn parse_bench_args(mut args: impl Iterator<Item = String>) -> Result<Command> {
+    let mut day = None;
+    let mut iterations = 10;
+    let mut input_dir = PathBuf::from("input/2022");
+    let mut format = Format::Table;
+    let mut baseline = None;
+    let mut threshold = 10.0;
+    while let Some(arg) = args.next() {
+        let mut value = |name: &str| {
+            args.next()
+                .ok_or_else(|| anyhow!("missing value for {name}"))
+        };
+        match arg.as_str() {
+            "--iterations" => {
+                iterations = value("--iterations")?
+                    .parse()
+                    .context("invalid --iterations")?;
+                if iterations == 0 {
+                    bail!("--iterations must be at least 1");
+                }
+            }
+            "--input-dir" => input_dir = PathBuf::from(value("--input-dir")?),
+            "--format" => {
+                format = match value("--format")?.as_str() {
+                    "table" => Format::Table,
+                    "json" => Format::Json,
+                    "csv" => Format::Csv,
+                    format => bail!("unknown format '{format}'"),
+                }
+            }
+            "--baseline" => baseline = Some(PathBuf::from(value("--baseline")?)),
+            "--threshold" => {
+                threshold = value("--threshold")?
+                    .parse()
+                    .context("invalid --threshold")?
+            }
+            _ if arg.starts_with("--") => bail!("unknown option '{arg}'"),
+            _ if day.is_none() => {
+                day = Some(
+                    arg.parse()
+                        .with_context(|| format!("invalid day '{arg}'"))?,
+                )
+            }
+            _ => bail!("too many arguments"),
+        }
+    }
+
+    Ok(Command::Bench {
+        day,
+        iterations,
+        input_dir,
+        format,
+        baseline,
+        threshold,
+    })
+}
+
+fn parse_run_args(mut args: impl Iterator<Item = String>) -> Result<Command> {
+    let mut positional = vec![];
+    let mut input = None;
+    let mut input_dir = None;
+    let mut expected = vec![];
+    let mut params = vec![];
+    let mut all = false;
+    let mut parallel = false;
+    while let Some(arg) = args.next() {
+        let mut value = |name: &str| {
+            args.next()
+                .ok_or_else(|| anyhow!("missing value for {name}"))
+        };
+        match arg.as_str() {
+            "--input" => input = Some(value("--input")?),
+            "--input-dir" => input_dir = Some(PathBuf::from(value("--input-dir")?)),
+            "--expect" => expected.push(value("--expect")?),
+            "--param" => {
+                let param = value("--param")?;
+                let (key, value) = param
+                    .split_once('=')
+                    .ok_or_else(|| anyhow!("expected key=value for --param but got '{param}'"))?;
+                params.push((key.to_string(), value.to_string()));
+            }
+            "--all" => all = true,
+            "--parallel" => parallel = true,
+            _ if arg.starts_with("--") => bail!("unknown option '{arg}'"),
+            _ => positional.push(arg),
+        }
+    }
+
+    if all {
+        if !positional.is_empty() || input.is_some() || !expected.is_empty() || !params.is_empty() {
+            bail!("--all cannot be combined with a day, --input, --expect or --param");
+        }
+
+        return Ok(Command::RunAll {
+            input_dir: input_dir.unwrap_or_else(|| PathBuf::from("input/2022")),
+            parallel,
+        });
+    }
+    if parallel {
+        bail!("--parallel can only be used with --all");
+    }
+
+    let (day, part) = match positional.as_slice() {
+        [day] => (day, None),
+        [day, part] => (day, Some(part)),
+        [] => bail!("missing day"),
+        _ => bail!("too many arguments"),
+    };
+    let day = day
+        .parse()
+        .with_context(|| format!("invalid day '{day}'"))?;
+    let part = part
+        .map(|part| part.parse())
+        .transpose()
+        .context("invalid part")?;
+    let input = input.ok_or_else(|| anyhow!("missing --input"))?;
+    Ok(Command::Run {
+        day,
+        part,
+        input,
+        expected,
+        params,
+    })
+}
+
+fn read_input(input: &str) -> io::Result<String> {
+    if input == "-" {
+        let mut s = String::new();
+        io::stdin().read_to_string(&mut s)?;
+        Ok(s)
+    } else {
+        fs::read_to_string(input)
+    }
+}
+
+fn print_answer(answer: &PartAnswer) {
+    if answer.answer.is_multiline() {
+        println!(
+            "day {} part {}:\n{}",
+            answer.day, answer.part, answer.answer
+        );
+    } else {
+        println!("day {} part {}: {}", answer.day, answer.part, answer.answer);
+    }
+}
+
+fn run_single(
+    day: u32,
+    part: Option<u32>,
+    input: &str,
+    expected: &[String],
+    params: &[(String, String)],
+) -> Result<u8> {
+    let input = read_input(input).with_context(|| format!("could not read input '{input}'"))?;
+    let params = params
+        .iter()
+        .map(|(key, value)| (key.as_str(), value.as_str()))
+        .collect::<Vec<_>>();
+    let answers = match run(day, part, &input, &params) {
+        Ok(answers) => answers,
+        Err(RunError::Parse(e)) => {
+            eprintln!("error: {e}");
+            return Ok(EXIT_PARSE_ERROR);
+        }
+        Err(e) => return Err(e.into()),
+    };
+    if !expected.is_empty() && expected.len() != answers.len() {
+        bail!(
+            "got {} expected answers for {} parts",
+            expected.len(),
+            answers.len()
+        );
+    }
+
+    let mut exit_code = 0;
+    for (i, answer) in answers.iter().enumerate() {
+        print_answer(answer);
+        if let Some(expected) = expected.get(i) {
+            if Answer::from_text(expected) != answer.answer {
+                eprintln!(
+                    "day {} part {}: wrong answer, expected {expected}",
+                    answer.day, answer.part
+                );
+                exit_code = EXIT_WRONG_ANSWER;
+            }
+        }
+    }
+
+    Ok(exit_code)
+}
+
+fn run_all(input_dir: &Path, parallel: bool) -> Result<u8> {
+    let mut exit_code = 0;
+    let mut inputs = vec![];
+    for day in DAYS {
+        let path = input_dir.join(format!("day{day}.txt"));
+        match fs::read_to_string(&path) {
+            Ok(input) => inputs.push((day, input)),
+            Err(e) => {
+                eprintln!("day {day}: could not read input {}: {e}", path.display());
+                exit_code = exit_code.max(EXIT_ERROR);
+            }
+        }
+    }
+
+    let start = Instant::now();
+    let runs = run_days(&inputs, parallel);
+    let wall_time = start.elapsed();
+    for run in &runs {
+        match &run.result {
+            Ok(answers) => answers.iter().for_each(print_answer),
+            Err(RunError::Parse(e)) => {
+                eprintln!("error: {e}");
+                exit_code = exit_code.max(EXIT_PARSE_ERROR);
+            }
+            Err(e @ RunError::Solve(..)) => {
+                eprintln!("error: {e}");
+                exit_code = exit_code.max(EXIT_ERROR);
+            }
+            Err(e) => bail!("{e}"),
+        }
+    }
+
+    println!();
+    for run in &runs {
+        println!("day {:>2}: {:>10.1?}", run.day, run.elapsed);
+    }
+    println!(
+        "total:  {:>10.1?} ({:.1?} wall time)",
+        runs.iter().map(|run| run.elapsed).sum::<Duration>(),
+        wall_time
+    );
+
+    Ok(exit_code)
+}
+
+fn run_verify(fixtures: &Path) -> Result<u8> {
+    let fixtures = load_fixtures(fixtures)?;
+    let outcomes = verify(&fixtures);
+    let summary = Summary::new(&outcomes);
+    print!("{}", format_table(&outcomes));
+    println!("\n{summary}");
+
+    Ok(if summary.failed > 0 || summary.panicked > 0 {
+        EXIT_WRONG_ANSWER
+    } else if summary.invalid > 0 {
+        EXIT_PARSE_ERROR
+    } else {
+        0
+    })
+}
+
+/// Prints the generated input to stdout and the params it needs as `--param` options to stderr.
+fn run_gen(day: u32, seed: u64, size: usize) -> Result<u8> {
+    let sample = generate(day, seed, size).ok_or(RunError::UnknownDay(day))?;
+    print!("{}", sample.input);
+    if !sample.params.is_empty() {
+        eprintln!(
+            "{}",
+            sample
+                .params
+                .iter()
+                .map(|(key, value)| format!("--param {key}={value}"))
+                .collect::<Vec<_>>()
+                .join(" ")
+        );
+    }
+
+    Ok(0)
+}
+
+fn run_visualize(
+    day: u32,
+    part: u32,
+    input: &str,
+    format: &FrameFormat,
+    every: usize,
+) -> Result<u8> {
+    let input = read_input(input).with_context(|| format!("could not read input '{input}'"))?;
+    let mut recorder = Recorder::every(every);
+    match simulate(day, part, &input, &mut recorder) {
+        Ok(()) => {}
+        Err(VisualizeError::Parse(e)) => {
+            eprintln!("error: {e}");
+            return Ok(EXIT_PARSE_ERROR);
+        }
+        Err(e) => return Err(e.into()),
+    }
+
+    let frames = recorder.into_frames();
+    match format {
+        FrameFormat::Terminal { delay } => {
+            visualize::play(&frames, *delay, &mut io::stdout().lock())?
+        }
+        FrameFormat::Text => print!("{}", visualize::to_text(&frames)),
+        FrameFormat::Image {
+            format,
+            scale,
+            output,
+        } => {
+            let paths = visualize::export_images(&frames, output, *format, *scale)
+                .with_context(|| format!("could not write frames to {}", output.display()))?;
+            println!("wrote {} frames to {}", paths.len(), output.display());
+        }
+    }
+
+    Ok(0)
+}
+
+fn run_bench(
+    day: Option<u32>,
+    iterations: usize,
+    input_dir: &Path,
+    format: Format,
+    baseline: Option<&Path>,
+    threshold: f64,
+) -> Result<u8> {
+    let baseline = baseline
+        .map(|path| -> Result<Vec<Measurement>> {
+            let baseline = fs::read_to_string(path)
+                .with_context(|| format!("could not read baseline {}", path.display()))?;
+            bench::from_json(&baseline)
+                .with_context(|| format!("invalid baseline {}", path.display()))
+        })
+        .transpose()?;
+
+    let days = match day {
+        Some(day) => vec![registry().day(day).ok_or(RunError::UnknownDay(day))?],
+        None => registry().days().collect(),
+    };
+
+    let mut exit_code = 0;
+    let mut measurements = vec![];
+    for solution in days {
+        let path = input_dir.join(format!("day{}.txt", solution.day()));
+        let input = match fs::read_to_string(&path) {
+            Ok(input) => input,
+            Err(e) if day.is_some() => {
+                bail!("could not read input {}: {e}", path.display())
+            }
+            Err(e) => {
+                eprintln!(
+                    "day {}: skipped, could not read input {}: {e}",
+                    solution.day(),
+                    path.display()
+                );
+                continue;
+            }
+        };
+
+        match bench_day(solution, &input, iterations) {
+            Ok(day_measurements) => measurements.extend(day_measurements),
+            Err(e) => {
+                eprintln!("error: {e}");
+                exit_code = exit_code.max(EXIT_PARSE_ERROR);
+            }
+        }
+    }
+
+    let comparisons = baseline
+        .map(|baseline| bench::compare(&measurements, &baseline, threshold / 100.0))
+        .unwrap_or_default();
+    match format {
+        Format::Table => print!("{}", bench::format_table(&measurements, &comparisons)),
+        Format::Json => println!("{}", bench::to_json(&measurements)),
+        Format::Csv => print!("{}", bench::to_csv(&measurements)),
+    }
+
+    for c in comparisons.iter().filter(|c| c.regression) {
+        eprintln!(
+            "day {} {}: regressed by {:.1}% ({:.1?} -> {:.1?})",
+            c.day,
+            c.stage,
+            c.change * 100.0,
+            c.baseline,
+            c.current
+        );
+        exit_code = exit_code.max(EXIT_REGRESSION);
+    }
+
+    Ok(exit_code)
+}
+
+fn main() -> ExitCode {
+    let command = match parse_args(std::env::args().skip(1)) {
+        Ok(command) => command,
+        Err(e) => {
+            eprintln!("error: {e}\n\n{USAGE}");
+            return ExitCode::from(EXIT_ERROR);
+        }
+    };
+
+    let result = match command {
+        Command::Run {
+            day,
+            part,
+            input,
+            expected,
+            params,
+        } => run_single(day, part, &input, &expected, &params),
+        Command::RunAll {
+            input_dir,
+            parallel,
+        } => run_all(&input_dir, parallel),
+        Command::Verify { fixtures } => run_verify(&fixtures),
+        Command::Gen { day, seed, size } => run_gen(day, seed, size),
+        Command::Visualize {
+            day,
+            part,
+            input,
+            format,
+            every,
+        } => run_visualize(day, part, &input, &format, every),
+        Command::Bench {
+            day,
+            iterations,
+            input_dir,
+            format,
+            baseline,
+            threshold,
+        } => run_bench(
+            day,
+            iterations,
+            &input_dir,
+            format,
+            baseline.as_deref(),
+            threshold,
+        ),
+    };
+    match result {
+        Ok(exit_code) => ExitCode::from(exit_code),
+        Err(e) => {
+            eprintln!("error: {e:#}");
+            ExitCode::from(EXIT_ERROR)
+        }
+    }
+}
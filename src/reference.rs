@@ -0,0 +1,436 @@
+//! Slow but straightforward reference implementations of the days whose solvers rely on
+//! pruning or shortcuts. They parse the raw input themselves and share no code with the
+//! solvers, so the property tests below can cross-check both on small random inputs.
+
+use itertools::Itertools;
+use num::{BigInt, BigRational, Zero};
+use rustc_hash::{FxHashMap, FxHashSet};
+
+/// All integers in `line`, including their sign.
+fn numbers(line: &str) -> Vec<i64> {
+    line.split(|c: char| !c.is_ascii_digit() && c != '-')
+        .filter_map(|s| s.parse().ok())
+        .collect()
+}
+
+fn manhattan(a: (i64, i64), b: (i64, i64)) -> i64 {
+    (a.0 - b.0).abs() + (a.1 - b.1).abs()
+}
+
+/// Checks every position of `row` and every position of the `max` square for sensor coverage.
+pub fn day15(input: &str, row: i64, max: i64) -> (usize, Option<i64>) {
+    let sensors = input
+        .lines()
+        .map(|l| {
+            let n = numbers(l);
+            ((n[0], n[1]), (n[2], n[3]))
+        })
+        .collect_vec();
+    let covered = |p: (i64, i64)| {
+        sensors
+            .iter()
+            .any(|&(s, b)| manhattan(s, p) <= manhattan(s, b))
+    };
+
+    let min_x = sensors.iter().map(|&(s, b)| s.0 - manhattan(s, b)).min();
+    let max_x = sensors.iter().map(|&(s, b)| s.0 + manhattan(s, b)).max();
+    let no_beacon = (min_x.unwrap_or(0)..=max_x.unwrap_or(-1))
+        .filter(|&x| covered((x, row)) && sensors.iter().all(|&(_, b)| b != (x, row)))
+        .count();
+
+    let distress = (0..=max)
+        .cartesian_product(0..=max)
+        .find(|&p| !covered(p))
+        .map(|(x, y)| x * 4000000 + y);
+
+    (no_beacon, distress)
+}
+
+/// Tries every move of every agent minute by minute, on the tunnels as given.
+pub fn day16(input: &str, part1_minutes: u32, part2_minutes: u32) -> (u32, u32) {
+    struct Valve<'a> {
+        name: &'a str,
+        flow: u32,
+        tunnels: Vec<&'a str>,
+    }
+
+    struct Search<'a> {
+        valves: FxHashMap<&'a str, Valve<'a>>,
+        bits: FxHashMap<&'a str, u64>,
+        memo: FxHashMap<(u32, Vec<&'a str>, u64), u32>,
+    }
+
+    impl<'a> Search<'a> {
+        fn release(&mut self, time_left: u32, agents: Vec<&'a str>, opened: u64) -> u32 {
+            if time_left == 0 {
+                return 0;
+            }
+            let key = (time_left, agents.clone(), opened);
+            if let Some(&pressure) = self.memo.get(&key) {
+                return pressure;
+            }
+
+            // every agent either opens its valve or walks through a tunnel
+            let mut max = 0;
+            let choices = agents
+                .iter()
+                .map(|&name| {
+                    let valve = &self.valves[name];
+                    let open = (valve.flow > 0 && opened & self.bits[name] == 0).then_some(None);
+                    open.into_iter()
+                        .chain(valve.tunnels.iter().map(|&to| Some(to)))
+                        .map(|to| (name, to))
+                        .collect_vec()
+                })
+                .multi_cartesian_product();
+            for choice in choices {
+                let mut next_opened = opened;
+                let mut pressure = 0;
+                for &(name, to) in &choice {
+                    if to.is_none() && next_opened & self.bits[name] == 0 {
+                        next_opened |= self.bits[name];
+                        pressure += self.valves[name].flow * (time_left - 1);
+                    }
+                }
+                let next = choice
+                    .iter()
+                    .map(|&(name, to)| to.unwrap_or(name))
+                    .collect();
+                max = max.max(pressure + self.release(time_left - 1, next, next_opened));
+            }
+
+            self.memo.insert(key, max);
+            max
+        }
+    }
+
+    let valves = input
+        .lines()
+        .map(|l| {
+            let name = l.split_whitespace().nth(1).unwrap();
+            let (_, tunnels) = l.split_once(" to valve").unwrap();
+            let tunnels = tunnels.trim_start_matches('s').split(',').map(str::trim);
+            let flow = numbers(l)[0] as u32;
+            (
+                name,
+                Valve {
+                    name,
+                    flow,
+                    tunnels: tunnels.collect(),
+                },
+            )
+        })
+        .collect::<FxHashMap<_, _>>();
+    let bits = valves
+        .values()
+        .filter(|v| v.flow > 0)
+        .enumerate()
+        .map(|(i, v)| (v.name, 1 << i))
+        .collect();
+    let mut search = Search {
+        valves,
+        bits,
+        memo: FxHashMap::default(),
+    };
+
+    let part1 = search.release(part1_minutes, vec!["AA"], 0);
+    let part2 = search.release(part2_minutes, vec!["AA", "AA"], 0);
+    (part1, part2)
+}
+
+/// Drops every single rock, without looking for a cycle.
+pub fn day17(input: &str, rocks: usize) -> u64 {
+    const ROCKS: [&[(i64, i64)]; 5] = [
+        &[(0, 0), (1, 0), (2, 0), (3, 0)],
+        &[(1, 0), (0, 1), (1, 1), (2, 1), (1, 2)],
+        &[(0, 0), (1, 0), (2, 0), (2, 1), (2, 2)],
+        &[(0, 0), (0, 1), (0, 2), (0, 3)],
+        &[(0, 0), (1, 0), (0, 1), (1, 1)],
+    ];
+
+    let jets = input.trim().as_bytes();
+    let mut filled = FxHashSet::default();
+    let mut height = 0;
+    let mut jet = 0;
+    let fits = |filled: &FxHashSet<(i64, i64)>, rock: &[(i64, i64)], x: i64, y: i64| {
+        rock.iter().all(|&(dx, dy)| {
+            (0..7).contains(&(x + dx)) && y + dy >= 0 && !filled.contains(&(x + dx, y + dy))
+        })
+    };
+    for rock in ROCKS.iter().cycle().take(rocks) {
+        let (mut x, mut y) = (2, height + 3);
+        loop {
+            let dx = if jets[jet] == b'<' { -1 } else { 1 };
+            jet = (jet + 1) % jets.len();
+            if fits(&filled, rock, x + dx, y) {
+                x += dx;
+            }
+            if !fits(&filled, rock, x, y - 1) {
+                break;
+            }
+            y -= 1;
+        }
+        for &(dx, dy) in *rock {
+            filled.insert((x + dx, y + dy));
+            height = height.max(y + dy + 1);
+        }
+    }
+
+    height as u64
+}
+
+/// Tries every order of building robots, without bounding the number of geodes.
+pub fn day19(
+    input: &str,
+    part1_minutes: u32,
+    part2_minutes: u32,
+    part2_blueprints: usize,
+) -> (u32, u32) {
+    // costs[robot][resource] with ore, clay, obsidian and geode in that order
+    fn max_geodes(costs: &[[u32; 4]; 4], time_left: u32, robots: [u32; 4], stock: [u32; 4]) -> u32 {
+        let mut best = stock[3] + robots[3] * time_left;
+        for (robot, cost) in costs.iter().enumerate() {
+            let wait = (0..4)
+                .map(|r| match (cost[r].saturating_sub(stock[r]), robots[r]) {
+                    (0, _) => Some(0),
+                    (_, 0) => None,
+                    (missing, count) => Some(missing.div_ceil(count)),
+                })
+                .try_fold(0, |acc, w| Some(acc.max(w?)));
+            if let Some(wait) = wait.filter(|&w| w + 1 < time_left) {
+                let mut next_robots = robots;
+                next_robots[robot] += 1;
+                let next_stock = [0, 1, 2, 3].map(|r| stock[r] + robots[r] * (wait + 1) - cost[r]);
+                best = best.max(max_geodes(
+                    costs,
+                    time_left - wait - 1,
+                    next_robots,
+                    next_stock,
+                ));
+            }
+        }
+        best
+    }
+
+    let blueprints = input
+        .lines()
+        .map(|l| {
+            let n = numbers(l).into_iter().map(|n| n as u32).collect_vec();
+            let costs = [
+                [n[1], 0, 0, 0],
+                [n[2], 0, 0, 0],
+                [n[3], n[4], 0, 0],
+                [n[5], 0, n[6], 0],
+            ];
+            (n[0], costs)
+        })
+        .collect_vec();
+    let part1 = blueprints
+        .iter()
+        .map(|(id, costs)| id * max_geodes(costs, part1_minutes, [1, 0, 0, 0], [0; 4]))
+        .sum();
+    let part2 = blueprints
+        .iter()
+        .take(part2_blueprints)
+        .map(|(_, costs)| max_geodes(costs, part2_minutes, [1, 0, 0, 0], [0; 4]))
+        .product();
+
+    (part1, part2)
+}
+
+/// Evaluates the tree recursively and solves part 2 by inverting the operations on the path
+/// from `root` down to `humn`.
+pub fn day21(input: &str) -> (BigRational, BigRational) {
+    let monkeys = input
+        .lines()
+        .map(|l| {
+            let (name, job) = l.split_once(": ").unwrap();
+            (name, job.split_whitespace().collect_vec())
+        })
+        .collect::<FxHashMap<_, _>>();
+
+    fn eval(monkeys: &FxHashMap<&str, Vec<&str>>, name: &str) -> BigRational {
+        match monkeys[name][..] {
+            [n] => BigRational::from(n.parse::<BigInt>().unwrap()),
+            [a, op, b] => {
+                let (a, b) = (eval(monkeys, a), eval(monkeys, b));
+                match op {
+                    "+" => a + b,
+                    "-" => a - b,
+                    "*" => a * b,
+                    _ => a / b,
+                }
+            }
+            _ => unreachable!(),
+        }
+    }
+
+    fn has_humn(monkeys: &FxHashMap<&str, Vec<&str>>, name: &str) -> bool {
+        name == "humn"
+            || matches!(monkeys[name][..], [a, _, b] if has_humn(monkeys, a) || has_humn(monkeys, b))
+    }
+
+    // the value `humn` has to yell so that `name` yells `target`
+    fn solve(monkeys: &FxHashMap<&str, Vec<&str>>, name: &str, target: BigRational) -> BigRational {
+        if name == "humn" {
+            return target;
+        }
+
+        let [a, op, b] = monkeys[name][..] else {
+            unreachable!("humn is below {name}")
+        };
+        if has_humn(monkeys, a) {
+            let b = eval(monkeys, b);
+            let target = match op {
+                "+" => target - b,
+                "-" => target + b,
+                "*" => target / b,
+                _ => target * b,
+            };
+            solve(monkeys, a, target)
+        } else {
+            let a = eval(monkeys, a);
+            let target = match op {
+                "+" => target - a,
+                "-" => a - target,
+                "*" => target / a,
+                _ => a / target,
+            };
+            solve(monkeys, b, target)
+        }
+    }
+
+    let part1 = eval(&monkeys, "root");
+    let [a, _, b] = monkeys["root"][..] else {
+        unreachable!("root has to compare two monkeys")
+    };
+    let part2 = if has_humn(&monkeys, a) {
+        solve(&monkeys, a, eval(&monkeys, b))
+    } else {
+        solve(&monkeys, b, eval(&monkeys, a))
+    };
+    assert!(!part2.denom().is_zero());
+
+    (part1, part2)
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::answer::Answer;
+    use crate::gen::generate;
+    use crate::registry;
+
+    const SEEDS: u64 = 8;
+
+    /// Generates inputs of every size for the first [`SEEDS`] seeds and asserts that the
+    /// solution of `day` agrees with `reference`, which gets the input and all params.
+    fn check(
+        day: u32,
+        sizes: &[usize],
+        params: &[(&str, &str)],
+        reference: impl Fn(&str, &FxHashMap<String, String>) -> (Answer, Answer),
+    ) {
+        let solution = registry().day(day).unwrap();
+        for seed in 0..SEEDS {
+            for &size in sizes {
+                let sample = generate(day, seed, size).unwrap();
+                let all_params = sample
+                    .params
+                    .iter()
+                    .map(|(k, v)| (k.clone(), v.clone()))
+                    .chain(params.iter().map(|(k, v)| (k.to_string(), v.to_string())))
+                    .collect::<FxHashMap<_, _>>();
+                let mut solution_params = solution.params();
+                for (key, value) in &all_params {
+                    solution_params.set(key, value).unwrap();
+                }
+
+                let input = solution.parse(&sample.input).unwrap();
+                let (part1, part2) = reference(&sample.input, &all_params);
+                let context = format!("day {day}, seed {seed}, size {size}:\n{}", sample.input);
+                assert_eq!(
+                    Ok(part1),
+                    solution.part1(&input, &*solution_params),
+                    "{context}"
+                );
+                assert_eq!(
+                    Some(Ok(part2)),
+                    solution.part2(&input, &*solution_params),
+                    "{context}"
+                );
+            }
+        }
+    }
+
+    fn param<T: std::str::FromStr>(params: &FxHashMap<String, String>, key: &str) -> T {
+        params[key].parse().ok().unwrap()
+    }
+
+    #[test]
+    fn test_day15() {
+        check(15, &[1, 10, 40], &[], |input, params| {
+            let (part1, part2) = day15(input, param(params, "row"), param(params, "max"));
+            (part1.into(), part2.unwrap().into())
+        });
+    }
+
+    #[test]
+    fn test_day16() {
+        let params = [("part1_minutes", "20"), ("part2_minutes", "16")];
+        check(16, &[2, 6, 12], &params, |input, params| {
+            let minutes = (
+                param(params, "part1_minutes"),
+                param(params, "part2_minutes"),
+            );
+            let (part1, part2) = day16(input, minutes.0, minutes.1);
+            (part1.into(), part2.into())
+        });
+    }
+
+    #[test]
+    fn test_day17() {
+        let params = [("part1_rocks", "500"), ("part2_rocks", "5000")];
+        check(17, &[1, 40, 100], &params, |input, params| {
+            let part1 = day17(input, param(params, "part1_rocks"));
+            let part2 = day17(input, param(params, "part2_rocks"));
+            (part1.into(), part2.into())
+        });
+    }
+
+    #[test]
+    fn test_day19() {
+        let params = [
+            ("part1_minutes", "16"),
+            ("part2_minutes", "19"),
+            ("part2_blueprints", "2"),
+        ];
+        check(19, &[1, 3], &params, |input, params| {
+            let (part1, part2) = day19(
+                input,
+                param(params, "part1_minutes"),
+                param(params, "part2_minutes"),
+                param(params, "part2_blueprints"),
+            );
+            (part1.into(), part2.into())
+        });
+    }
+
+    #[test]
+    fn test_day21() {
+        check(21, &[2, 5, 30], &[], |input, _| {
+            let (part1, part2) = day21(input);
+            (part1.into(), part2.into())
+        });
+    }
+
+    #[test]
+    fn test_numbers() {
+        assert_eq!(
+            vec![2, -18, -2, 15],
+            numbers("Sensor at x=2, y=-18: closest beacon is at x=-2, y=15")
+        );
+    }
+}
@@ -0,0 +1,151 @@
+//! Running a day's parse and solve steps on an input, one day or many in parallel, with timings.
+
+use std::time::{Duration, Instant};
+
+use rayon::prelude::*;
+use thiserror::Error;
+
+use crate::answer::Answer;
+use crate::common::ParseError;
+use crate::registry;
+use crate::solution::{ParamError, SolveError};
+
+pub const DAYS: std::ops::RangeInclusive<u32> = 1..=25;
+
+#[derive(Error, Debug)]
+pub enum RunError {
+    #[error("unknown day {0}")]
+    UnknownDay(u32),
+    #[error("day {0} has no part {1}")]
+    UnknownPart(u32, u32),
+    #[error(transparent)]
+    Parse(#[from] ParseError),
+    #[error("day {0}: {1}")]
+    Param(u32, ParamError),
+    #[error("day {0} part {1}: {2}")]
+    Solve(u32, u32, SolveError),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PartAnswer {
+    pub day: u32,
+    pub part: u32,
+    pub answer: Answer,
+}
+
+/// Solves `part` (or all parts if `None`) of `day` with the given puzzle input.
+///
+/// `params` are `(key, value)` pairs that override the default parameters of the day.
+pub fn run(
+    day: u32,
+    part: Option<u32>,
+    input: &str,
+    params: &[(&str, &str)],
+) -> Result<Vec<PartAnswer>, RunError> {
+    let registry = registry();
+    let solution = registry.day(day).ok_or(RunError::UnknownDay(day))?;
+    let parts = match part {
+        Some(part) if registry.get(day, part).is_none() => {
+            return Err(RunError::UnknownPart(day, part))
+        }
+        Some(part) => vec![part],
+        None => registry.parts(day).collect(),
+    };
+
+    let mut day_params = solution.params();
+    for (key, value) in params {
+        day_params
+            .set(key, value)
+            .map_err(|e| RunError::Param(day, e))?;
+    }
+
+    let input = solution.parse(input)?;
+    parts
+        .into_iter()
+        .filter_map(|part| Some((part, solution.solve(part, &input, &*day_params)?)))
+        .map(|(part, answer)| {
+            Ok(PartAnswer {
+                day,
+                part,
+                answer: answer.map_err(|e| RunError::Solve(day, part, e))?,
+            })
+        })
+        .collect()
+}
+
+/// The outcome of one day in [`run_days`].
+#[derive(Debug)]
+pub struct DayRun {
+    pub day: u32,
+    pub result: Result<Vec<PartAnswer>, RunError>,
+    /// time spent parsing the input and solving all parts
+    pub elapsed: Duration,
+}
+
+/// Solves all parts of each `(day, input)` with the default parameters, concurrently on all
+/// cores if `parallel` is set. The results are in the order of `inputs` either way.
+pub fn run_days(inputs: &[(u32, String)], parallel: bool) -> Vec<DayRun> {
+    let run_day = |(day, input): &(u32, String)| {
+        let start = Instant::now();
+        let result = run(*day, None, input, &[]);
+        DayRun {
+            day: *day,
+            result,
+            elapsed: start.elapsed(),
+        }
+    };
+
+    if parallel {
+        inputs.par_iter().map(run_day).collect()
+    } else {
+        inputs.iter().map(run_day).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_run_days() {
+        let inputs = vec![
+            (6, "mjqjpqmgbljsphdztnvjfqwrcgsmlb\n".to_string()),
+            (1, "1000\n2000\n\n4000\n".to_string()),
+            (2, "A Y\nB X\nC Z\n".to_string()),
+            (4, "oops\n".to_string()),
+        ];
+        let answers = |runs: Vec<DayRun>| {
+            runs.into_iter()
+                .map(|run| (run.day, run.result.map_err(|e| e.to_string())))
+                .collect::<Vec<_>>()
+        };
+
+        let sequential = answers(run_days(&inputs, false));
+        assert_eq!(sequential, answers(run_days(&inputs, true)));
+        assert_eq!(
+            vec![6, 1, 2, 4],
+            sequential.iter().map(|(day, _)| *day).collect::<Vec<_>>()
+        );
+        assert!(sequential[..3].iter().all(|(_, result)| result.is_ok()));
+        assert!(sequential[3].1.is_err());
+    }
+
+    #[test]
+    fn test_run_errors() {
+        let input = std::fs::read_to_string("fixtures/day15/example.txt").unwrap();
+        assert!(matches!(
+            run(15, Some(2), &input, &[("max", "5")]),
+            Err(RunError::Solve(15, 2, _))
+        ));
+        assert!(matches!(
+            run(15, Some(2), &input, &[("max", "-1")]),
+            Err(RunError::Param(15, ParamError::InvalidValue { .. }))
+        ));
+        assert!(matches!(
+            run(11, None, "", &[("part1_divisor", "0")]),
+            Err(RunError::Param(11, ParamError::InvalidValue { .. }))
+        ));
+    }
+}
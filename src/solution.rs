@@ -0,0 +1,185 @@
+//! The object-safe interface of a day's solution, its runtime parameters and errors.
+
+use std::any::Any;
+use std::fmt::{Debug, Display};
+use std::str::FromStr;
+
+use thiserror::Error;
+
+use crate::answer::Answer;
+use crate::common::ParseError;
+
+/// Type-erased puzzle input as returned by [`Solution::parse`].
+pub type Input = Box<dyn Any + Send + Sync>;
+
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum ParamError {
+    #[error("unknown parameter '{0}'")]
+    Unknown(String),
+    #[error("invalid value '{value}' for parameter '{key}': {reason}")]
+    InvalidValue {
+        key: String,
+        value: String,
+        reason: String,
+    },
+}
+
+/// Runtime parameters of a puzzle, e.g. the number of rounds to simulate.
+///
+/// The [`Default`] of each implementation matches the real puzzle.
+pub trait PuzzleParams: Any + Debug + Send + Sync {
+    /// Sets the parameter `key` from its textual representation.
+    fn set(&mut self, key: &str, value: &str) -> Result<(), ParamError>;
+
+    fn as_any(&self) -> &dyn Any;
+}
+
+/// Days without any parameters.
+impl PuzzleParams for () {
+    fn set(&mut self, key: &str, _value: &str) -> Result<(), ParamError> {
+        Err(ParamError::Unknown(key.to_string()))
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+/// Helper for [`PuzzleParams::set`] implementations.
+pub fn parse_param<T: FromStr>(key: &str, value: &str) -> Result<T, ParamError>
+where
+    <T as FromStr>::Err: Display,
+{
+    value.parse().map_err(|e: T::Err| ParamError::InvalidValue {
+        key: key.to_string(),
+        value: value.to_string(),
+        reason: e.to_string(),
+    })
+}
+
+/// Like [`parse_param`], for parameters that must be at least `min`.
+pub fn parse_param_at_least<T: FromStr + PartialOrd + Display>(
+    key: &str,
+    value: &str,
+    min: T,
+) -> Result<T, ParamError>
+where
+    <T as FromStr>::Err: Display,
+{
+    let parsed = parse_param(key, value)?;
+    if parsed < min {
+        return Err(ParamError::InvalidValue {
+            key: key.to_string(),
+            value: value.to_string(),
+            reason: format!("must be at least {min}"),
+        });
+    }
+
+    Ok(parsed)
+}
+
+/// A puzzle without an answer, e.g. because the parameters don't fit the input.
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+#[error("{0}")]
+pub struct SolveError(String);
+
+impl SolveError {
+    pub fn new(message: impl Into<String>) -> Self {
+        Self(message.into())
+    }
+}
+
+/// Object-safe interface to the solution of a single day.
+///
+/// The input returned by [`Solution::parse`] and the parameters returned by [`Solution::params`]
+/// can only be passed back to the same solution.
+pub trait Solution: Send + Sync {
+    fn day(&self) -> u32;
+
+    /// Returns the default parameters of this day.
+    fn params(&self) -> Box<dyn PuzzleParams>;
+
+    fn parse(&self, input: &str) -> Result<Input, ParseError>;
+
+    fn part1(&self, input: &Input, params: &dyn PuzzleParams) -> Result<Answer, SolveError>;
+
+    /// Returns `None` for days without a second part.
+    fn part2(&self, input: &Input, params: &dyn PuzzleParams)
+        -> Option<Result<Answer, SolveError>>;
+
+    fn has_part2(&self) -> bool;
+
+    fn solve(
+        &self,
+        part: u32,
+        input: &Input,
+        params: &dyn PuzzleParams,
+    ) -> Option<Result<Answer, SolveError>> {
+        match part {
+            1 => Some(self.part1(input, params)),
+            2 => self.part2(input, params),
+            _ => None,
+        }
+    }
+}
+
+/// Solves one part of a day from its parsed input and parameters.
+pub type Solver<I, P> = fn(&I, &P) -> Result<Answer, SolveError>;
+
+/// [`Solution`] backed by the plain functions of a day module.
+pub struct Day<I, P = ()> {
+    pub day: u32,
+    pub parse: fn(&str) -> Result<I, ParseError>,
+    pub part1: Solver<I, P>,
+    pub part2: Option<Solver<I, P>>,
+}
+
+impl<I: Any + Send + Sync, P: PuzzleParams> Day<I, P> {
+    fn downcast<'a>(&self, input: &'a Input, params: &'a dyn PuzzleParams) -> (&'a I, &'a P) {
+        let input = input.downcast_ref().unwrap_or_else(|| {
+            panic!(
+                "input passed to day {} was not parsed by this solution",
+                self.day
+            )
+        });
+        let params = params.as_any().downcast_ref().unwrap_or_else(|| {
+            panic!(
+                "params passed to day {} were not created by this solution",
+                self.day
+            )
+        });
+        (input, params)
+    }
+}
+
+impl<I: Any + Send + Sync, P: PuzzleParams + Default> Solution for Day<I, P> {
+    fn day(&self) -> u32 {
+        self.day
+    }
+
+    fn params(&self) -> Box<dyn PuzzleParams> {
+        Box::new(P::default())
+    }
+
+    fn parse(&self, input: &str) -> Result<Input, ParseError> {
+        Ok(Box::new((self.parse)(input)?))
+    }
+
+    fn part1(&self, input: &Input, params: &dyn PuzzleParams) -> Result<Answer, SolveError> {
+        let (input, params) = self.downcast(input, params);
+        (self.part1)(input, params)
+    }
+
+    fn part2(
+        &self,
+        input: &Input,
+        params: &dyn PuzzleParams,
+    ) -> Option<Result<Answer, SolveError>> {
+        let (input, params) = self.downcast(input, params);
+        self.part2.map(|part2| part2(input, params))
+    }
+
+    fn has_part2(&self) -> bool {
+        self.part2.is_some()
+    }
+}
@@ -0,0 +1,447 @@
+//! Checks the solutions against the example fixtures and their expected answers.
+
+use std::collections::BTreeMap;
+use std::fmt::{Display, Formatter};
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::path::{Path, PathBuf};
+use std::{fs, io};
+
+use thiserror::Error;
+
+use crate::answer::Answer;
+use crate::registry;
+use crate::solution::Solution;
+
+/// Name of the file in the fixture directory that holds the expected answers.
+pub const MANIFEST: &str = "manifest.txt";
+
+#[derive(Error, Debug)]
+pub enum FixtureError {
+    #[error("could not read {path}: {source}", path = .0.display(), source = .1)]
+    Io(PathBuf, #[source] io::Error),
+    #[error("{}, line {line}: {reason}", .path.display())]
+    Manifest {
+        path: PathBuf,
+        line: usize,
+        reason: String,
+    },
+}
+
+/// An example input of a day together with its expected answers.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Fixture {
+    pub day: u32,
+    pub name: String,
+    pub input: String,
+    /// Overrides for the puzzle parameters of the day.
+    pub params: Vec<(String, String)>,
+    /// Expected answers keyed by part.
+    pub expected: BTreeMap<u32, Answer>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Status {
+    Pass,
+    Fail {
+        expected: Answer,
+        actual: Answer,
+    },
+    /// There is no expected answer for this part.
+    Missing,
+    /// The fixture could not be parsed or its parameters were rejected.
+    InvalidInput(String),
+    Panicked(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Outcome {
+    pub day: u32,
+    pub part: u32,
+    /// `None` if the day has no fixture at all.
+    pub fixture: Option<String>,
+    pub status: Status,
+}
+
+/// Loads all fixtures from `dir`.
+///
+/// Every `dayNN/<name>.txt` file is a fixture, the expected answers and parameters are read
+/// from the [`MANIFEST`]. The fixtures are sorted by day and name.
+pub fn load_fixtures(dir: &Path) -> Result<Vec<Fixture>, FixtureError> {
+    let mut fixtures = BTreeMap::new();
+    let entries = fs::read_dir(dir).map_err(|e| FixtureError::Io(dir.to_path_buf(), e))?;
+    for entry in entries {
+        let entry = entry.map_err(|e| FixtureError::Io(dir.to_path_buf(), e))?;
+        let Some(day) = entry
+            .file_name()
+            .to_str()
+            .and_then(|name| name.strip_prefix("day"))
+            .and_then(|day| day.parse::<u32>().ok())
+        else {
+            continue;
+        };
+        if !entry.path().is_dir() {
+            continue;
+        }
+
+        let day_dir = entry.path();
+        let files = fs::read_dir(&day_dir).map_err(|e| FixtureError::Io(day_dir.clone(), e))?;
+        for file in files {
+            let path = file
+                .map_err(|e| FixtureError::Io(day_dir.clone(), e))?
+                .path();
+            if path.extension().is_none_or(|ext| ext != "txt") {
+                continue;
+            }
+
+            let Some(name) = path.file_stem().and_then(|name| name.to_str()) else {
+                continue;
+            };
+            let input = fs::read_to_string(&path).map_err(|e| FixtureError::Io(path.clone(), e))?;
+            fixtures.insert(
+                (day, name.to_string()),
+                Fixture {
+                    day,
+                    name: name.to_string(),
+                    input,
+                    ..Default::default()
+                },
+            );
+        }
+    }
+
+    let path = dir.join(MANIFEST);
+    let manifest = fs::read_to_string(&path).map_err(|e| FixtureError::Io(path.clone(), e))?;
+    apply_manifest(&manifest, &mut fixtures).map_err(|(line, reason)| FixtureError::Manifest {
+        path,
+        line,
+        reason,
+    })?;
+
+    Ok(fixtures.into_values().collect())
+}
+
+fn apply_manifest(
+    manifest: &str,
+    fixtures: &mut BTreeMap<(u32, String), Fixture>,
+) -> Result<(), (usize, String)> {
+    let mut current = None;
+    for (i, line) in manifest.lines().enumerate() {
+        let line_no = i + 1;
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        if let Some(section) = line.strip_prefix('[') {
+            let section = section
+                .strip_suffix(']')
+                .ok_or((line_no, "missing ']'".to_string()))?;
+            let (day, name) = section
+                .split_once('/')
+                .and_then(|(day, name)| Some((day.strip_prefix("day")?.parse().ok()?, name)))
+                .ok_or((
+                    line_no,
+                    format!("expected [dayNN/<name>] but got [{section}]"),
+                ))?;
+            let key = (day, name.to_string());
+            if !fixtures.contains_key(&key) {
+                return Err((line_no, format!("no fixture day{day:02}/{name}.txt")));
+            }
+
+            current = Some(key);
+            continue;
+        }
+
+        let fixture = current
+            .as_ref()
+            .and_then(|key| fixtures.get_mut(key))
+            .ok_or((line_no, "expected a [dayNN/<name>] section".to_string()))?;
+        let (key, value) = line
+            .split_once('=')
+            .ok_or((line_no, format!("expected key = value but got '{line}'")))?;
+        let (key, value) = (key.trim(), value.trim());
+        match key.strip_prefix("part").and_then(|part| part.parse().ok()) {
+            Some(part) => {
+                fixture.expected.insert(part, Answer::from_text(value));
+            }
+            None => fixture.params.push((key.to_string(), value.to_string())),
+        }
+    }
+
+    Ok(())
+}
+
+/// Solves every registered part with each fixture of its day.
+///
+/// Parts without an expected answer and days without any fixture are reported as
+/// [`Status::Missing`]. Panicking solutions are caught and reported as [`Status::Panicked`].
+pub fn verify(fixtures: &[Fixture]) -> Vec<Outcome> {
+    let registry = registry();
+    let mut outcomes = vec![];
+    for solution in registry.days() {
+        let day = solution.day();
+        let parts = registry.parts(day).collect::<Vec<_>>();
+        let mut day_fixtures = fixtures.iter().filter(|f| f.day == day).peekable();
+        if day_fixtures.peek().is_none() {
+            outcomes.extend(parts.iter().map(|&part| Outcome {
+                day,
+                part,
+                fixture: None,
+                status: Status::Missing,
+            }));
+            continue;
+        }
+
+        for fixture in day_fixtures {
+            outcomes.extend(verify_fixture(solution, &parts, fixture).into_iter().map(
+                |(part, status)| Outcome {
+                    day,
+                    part,
+                    fixture: Some(fixture.name.clone()),
+                    status,
+                },
+            ));
+        }
+    }
+
+    outcomes
+}
+
+fn verify_fixture(solution: &dyn Solution, parts: &[u32], fixture: &Fixture) -> Vec<(u32, Status)> {
+    let expected_parts = parts
+        .iter()
+        .filter(|part| fixture.expected.contains_key(part));
+    let with_status = |status: Status| {
+        parts
+            .iter()
+            .map(|&part| {
+                if fixture.expected.contains_key(&part) {
+                    (part, status.clone())
+                } else {
+                    (part, Status::Missing)
+                }
+            })
+            .collect()
+    };
+
+    let mut params = solution.params();
+    for (key, value) in &fixture.params {
+        if let Err(e) = params.set(key, value) {
+            return with_status(Status::InvalidInput(e.to_string()));
+        }
+    }
+
+    let input = match catch_unwind(AssertUnwindSafe(|| solution.parse(&fixture.input))) {
+        Ok(Ok(input)) => input,
+        Ok(Err(e)) => return with_status(Status::InvalidInput(e.to_string())),
+        Err(panic) => return with_status(Status::Panicked(panic_message(&*panic))),
+    };
+
+    let mut results = BTreeMap::new();
+    for &part in expected_parts {
+        let expected = &fixture.expected[&part];
+        let status = match catch_unwind(AssertUnwindSafe(|| solution.solve(part, &input, &*params)))
+        {
+            Ok(Some(Ok(actual))) if actual == *expected => Status::Pass,
+            Ok(Some(Ok(actual))) => Status::Fail {
+                expected: expected.clone(),
+                actual,
+            },
+            Ok(Some(Err(e))) => Status::InvalidInput(e.to_string()),
+            Ok(None) => Status::Missing,
+            Err(panic) => Status::Panicked(panic_message(&*panic)),
+        };
+        results.insert(part, status);
+    }
+
+    parts
+        .iter()
+        .map(|&part| (part, results.remove(&part).unwrap_or(Status::Missing)))
+        .collect()
+}
+
+fn panic_message(panic: &(dyn std::any::Any + Send)) -> String {
+    panic
+        .downcast_ref::<&str>()
+        .map(|s| s.to_string())
+        .or_else(|| panic.downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| "unknown panic".to_string())
+}
+
+impl Display for Status {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Status::Pass => write!(f, "pass"),
+            Status::Fail { expected, actual } => write!(
+                f,
+                "FAIL: expected {}, got {}",
+                expected.to_text(),
+                actual.to_text()
+            ),
+            Status::Missing => write!(f, "missing"),
+            Status::InvalidInput(reason) => write!(f, "INVALID: {reason}"),
+            Status::Panicked(reason) => write!(f, "PANIC: {reason}"),
+        }
+    }
+}
+
+/// Counts of the outcomes of [`verify`].
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub struct Summary {
+    pub passed: usize,
+    pub failed: usize,
+    pub missing: usize,
+    pub invalid: usize,
+    pub panicked: usize,
+}
+
+impl Summary {
+    pub fn new(outcomes: &[Outcome]) -> Self {
+        let mut summary = Self::default();
+        for outcome in outcomes {
+            match outcome.status {
+                Status::Pass => summary.passed += 1,
+                Status::Fail { .. } => summary.failed += 1,
+                Status::Missing => summary.missing += 1,
+                Status::InvalidInput(_) => summary.invalid += 1,
+                Status::Panicked(_) => summary.panicked += 1,
+            }
+        }
+
+        summary
+    }
+
+    /// Returns `true` if no part failed, i.e. all are either passing or missing.
+    pub fn is_success(&self) -> bool {
+        self.failed == 0 && self.invalid == 0 && self.panicked == 0
+    }
+}
+
+impl Display for Summary {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} passed, {} failed, {} missing, {} invalid, {} panicked",
+            self.passed, self.failed, self.missing, self.invalid, self.panicked
+        )
+    }
+}
+
+/// Renders the outcomes as a table with one row per `(day, part, fixture)`.
+pub fn format_table(outcomes: &[Outcome]) -> String {
+    let width = outcomes
+        .iter()
+        .filter_map(|o| o.fixture.as_ref())
+        .map(|name| name.len())
+        .chain(["fixture".len(), "-".len()])
+        .max()
+        .unwrap();
+
+    let mut table = format!("day  part  {:width$}  status\n", "fixture");
+    for outcome in outcomes {
+        table += &format!(
+            "{:>3}  {:>4}  {:width$}  {}\n",
+            outcome.day,
+            outcome.part,
+            outcome.fixture.as_deref().unwrap_or("-"),
+            outcome.status
+        );
+    }
+
+    table
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_manifest() {
+        let mut fixtures = BTreeMap::from([(
+            (15, "example".to_string()),
+            Fixture {
+                day: 15,
+                name: "example".to_string(),
+                ..Default::default()
+            },
+        )]);
+        apply_manifest(
+            "# comment\n\n[day15/example]\nrow = 10\npart1 = 26\npart2 = a\\nb\\\\\n",
+            &mut fixtures,
+        )
+        .unwrap();
+
+        let fixture = &fixtures[&(15, "example".to_string())];
+        assert_eq!(vec![("row".to_string(), "10".to_string())], fixture.params);
+        assert_eq!(
+            BTreeMap::from([(1, Answer::Int(26)), (2, Answer::from("a\nb\\"))]),
+            fixture.expected
+        );
+
+        assert_eq!(
+            Err((1, "no fixture day01/missing.txt".to_string())),
+            apply_manifest("[day01/missing]", &mut fixtures)
+        );
+        assert_eq!(
+            Err((1, "expected a [dayNN/<name>] section".to_string())),
+            apply_manifest("part1 = 1", &mut fixtures)
+        );
+    }
+
+    #[test]
+    fn test_verify() {
+        let fixture = Fixture {
+            day: 1,
+            name: "example".to_string(),
+            input: "1000\n2000\n\n4000\n\n500\n".to_string(),
+            params: vec![],
+            expected: BTreeMap::from([(1, Answer::Int(4000)), (2, Answer::Int(1))]),
+        };
+        let outcomes = verify(&[fixture]);
+        assert_eq!(Status::Pass, outcomes[0].status);
+        assert_eq!(
+            Status::Fail {
+                expected: Answer::Int(1),
+                actual: Answer::Int(7500)
+            },
+            outcomes[1].status
+        );
+        assert_eq!(
+            Outcome {
+                day: 2,
+                part: 1,
+                fixture: None,
+                status: Status::Missing
+            },
+            outcomes[2]
+        );
+    }
+
+    #[test]
+    fn test_fixtures() {
+        let fixtures =
+            load_fixtures(Path::new(concat!(env!("CARGO_MANIFEST_DIR"), "/fixtures"))).unwrap();
+        let outcomes = verify(&fixtures);
+        let summary = Summary::new(&outcomes);
+        assert!(summary.is_success(), "{}{summary}", format_table(&outcomes));
+    }
+
+    #[test]
+    fn test_fixtures_crlf() {
+        // as saved by an editor on Windows that adds a BOM and pads the lines
+        let fixtures =
+            load_fixtures(Path::new(concat!(env!("CARGO_MANIFEST_DIR"), "/fixtures"))).unwrap();
+        let fixtures = fixtures
+            .into_iter()
+            .map(|f| Fixture {
+                input: format!("\u{feff}{}", f.input.replace('\n', " \r\n")),
+                ..f
+            })
+            .collect::<Vec<_>>();
+        let outcomes = verify(&fixtures);
+        let summary = Summary::new(&outcomes);
+        assert!(summary.is_success(), "{}{summary}", format_table(&outcomes));
+    }
+}
@@ -0,0 +1,450 @@
+//! Frames of the simulation days, rendered to the terminal, text or images.
+
+use std::fmt::{Display, Formatter};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use std::{fs, thread};
+
+use thiserror::Error;
+
+use crate::common::ParseError;
+use crate::{day10, day14, day17, day22, day23, day24};
+
+/// The days with a simulation that can be visualised.
+pub const DAYS: [u32; 6] = [10, 14, 17, 22, 23, 24];
+
+pub type Rgb = [u8; 3];
+
+/// The colours of the characters in a frame.
+///
+/// Characters without a colour are drawn white, or black if they are whitespace.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Palette(pub &'static [(char, Rgb)]);
+
+impl Palette {
+    pub fn color(&self, c: char) -> Rgb {
+        match self.0.iter().find(|(p, _)| *p == c) {
+            Some(&(_, rgb)) => rgb,
+            None if c.is_whitespace() => [0, 0, 0],
+            None => [255, 255, 255],
+        }
+    }
+
+    /// The luma of the colour of `c`.
+    pub fn gray(&self, c: char) -> u8 {
+        let [r, g, b] = self.color(c).map(u32::from);
+        ((299 * r + 587 * g + 114 * b) / 1000) as u8
+    }
+}
+
+/// A snapshot of a simulation as a grid of characters.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Frame {
+    width: usize,
+    height: usize,
+    cells: Vec<char>,
+    palette: Palette,
+}
+
+impl Frame {
+    pub fn new(width: usize, height: usize, background: char, palette: Palette) -> Self {
+        Self {
+            width,
+            height,
+            cells: vec![background; width * height],
+            palette,
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn palette(&self) -> Palette {
+        self.palette
+    }
+
+    pub fn get(&self, x: usize, y: usize) -> Option<char> {
+        (x < self.width && y < self.height).then(|| self.cells[y * self.width + x])
+    }
+
+    /// Sets the character at `(x, y)`, positions outside of the frame are ignored.
+    pub fn set(&mut self, x: usize, y: usize, c: char) {
+        if x < self.width && y < self.height {
+            self.cells[y * self.width + x] = c;
+        }
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item = &[char]> {
+        self.cells.chunks(self.width.max(1)).take(self.height)
+    }
+}
+
+impl Display for Frame {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for (y, row) in self.rows().enumerate() {
+            if y > 0 {
+                writeln!(f)?;
+            }
+            f.write_str(&row.iter().collect::<String>())?;
+        }
+
+        Ok(())
+    }
+}
+
+/// A state of a simulation that can be drawn.
+pub trait Visualize {
+    fn frame(&self) -> Frame;
+}
+
+/// Receives the states of a simulation while it runs.
+///
+/// States are passed unrendered, so observers only pay for the frames they actually use.
+pub trait Observer {
+    fn observe(&mut self, state: &dyn Visualize);
+}
+
+/// Passes `state` to `observer` if there is one.
+pub fn notify(observer: &mut Option<&mut dyn Observer>, state: &dyn Visualize) {
+    if let Some(observer) = observer {
+        observer.observe(state);
+    }
+}
+
+/// Records every `every`-th frame of a simulation, starting with the first one.
+#[derive(Debug, Clone)]
+pub struct Recorder {
+    every: usize,
+    seen: usize,
+    frames: Vec<Frame>,
+}
+
+impl Recorder {
+    pub fn new() -> Self {
+        Self::every(1)
+    }
+
+    pub fn every(every: usize) -> Self {
+        assert!(every > 0, "every must be at least 1");
+        Self {
+            every,
+            seen: 0,
+            frames: vec![],
+        }
+    }
+
+    pub fn frames(&self) -> &[Frame] {
+        &self.frames
+    }
+
+    pub fn into_frames(self) -> Vec<Frame> {
+        self.frames
+    }
+}
+
+impl Default for Recorder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Observer for Recorder {
+    fn observe(&mut self, state: &dyn Visualize) {
+        if self.seen.is_multiple_of(self.every) {
+            self.frames.push(state.frame());
+        }
+        self.seen += 1;
+    }
+}
+
+#[derive(Error, Debug)]
+pub enum VisualizeError {
+    #[error("day {0} has no visualisation")]
+    Unsupported(u32),
+    #[error("day {0} has no part {1}")]
+    UnknownPart(u32, u32),
+    #[error(transparent)]
+    Parse(#[from] ParseError),
+}
+
+/// Runs the simulation of `part` of `day` on `input` with default params and passes its
+/// states to `observer`.
+pub fn simulate(
+    day: u32,
+    part: u32,
+    input: &str,
+    observer: &mut dyn Observer,
+) -> Result<(), VisualizeError> {
+    if !DAYS.contains(&day) {
+        return Err(VisualizeError::Unsupported(day));
+    }
+    if !(1..=2).contains(&part) {
+        return Err(VisualizeError::UnknownPart(day, part));
+    }
+
+    match day {
+        10 => day10::visualize(&day10::parse(input)?, part, observer),
+        14 => day14::visualize(&day14::parse(input)?, part, observer),
+        17 => day17::visualize(&day17::parse(input)?, part, observer),
+        22 => day22::visualize(&day22::parse(input)?, part, observer),
+        23 => day23::visualize(&day23::parse(input)?, part, observer),
+        24 => day24::visualize(&day24::parse(input)?, part, observer),
+        _ => unreachable!(),
+    }
+
+    Ok(())
+}
+
+/// Renders the frames as plain text, each one preceded by its number.
+pub fn to_text(frames: &[Frame]) -> String {
+    frames
+        .iter()
+        .enumerate()
+        .map(|(i, frame)| format!("frame {}\n{frame}\n", i + 1))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Renders the frame with 24-bit ANSI colours.
+pub fn to_ansi(frame: &Frame) -> String {
+    let mut ansi = String::new();
+    for row in frame.rows() {
+        let mut current = None;
+        for &c in row {
+            let color = frame.palette.color(c);
+            if current != Some(color) {
+                let [r, g, b] = color;
+                ansi += &format!("\x1b[38;2;{r};{g};{b}m");
+                current = Some(color);
+            }
+            ansi.push(c);
+        }
+        ansi += "\x1b[0m\n";
+    }
+
+    ansi
+}
+
+/// Plays the frames in a terminal, waiting `delay` after each of them.
+pub fn play(frames: &[Frame], delay: Duration, out: &mut impl Write) -> io::Result<()> {
+    for frame in frames {
+        // moves the cursor to the top left and clears the screen
+        write!(out, "\x1b[H\x1b[2J{}", to_ansi(frame))?;
+        out.flush()?;
+        thread::sleep(delay);
+    }
+
+    Ok(())
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ImageFormat {
+    /// Binary portable pixmap (P6) with the colours of the palette.
+    Ppm,
+    /// Binary portable graymap (P5) with the luma of the colours of the palette.
+    Pgm,
+}
+
+impl ImageFormat {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ImageFormat::Ppm => "ppm",
+            ImageFormat::Pgm => "pgm",
+        }
+    }
+}
+
+/// Writes the frame as image, with each character drawn as a `scale` by `scale` square.
+pub fn write_image(
+    frame: &Frame,
+    format: ImageFormat,
+    scale: usize,
+    out: &mut impl Write,
+) -> io::Result<()> {
+    assert!(scale > 0, "scale must be at least 1");
+    let magic = match format {
+        ImageFormat::Ppm => "P6",
+        ImageFormat::Pgm => "P5",
+    };
+    write!(
+        out,
+        "{magic}\n{} {}\n255\n",
+        frame.width * scale,
+        frame.height * scale
+    )?;
+
+    let mut line = Vec::with_capacity(frame.width * scale * 3);
+    for row in frame.rows() {
+        line.clear();
+        for &c in row {
+            for _ in 0..scale {
+                match format {
+                    ImageFormat::Ppm => line.extend(frame.palette.color(c)),
+                    ImageFormat::Pgm => line.push(frame.palette.gray(c)),
+                }
+            }
+        }
+        for _ in 0..scale {
+            out.write_all(&line)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Writes the frames as numbered images `frame_00001.ppm`, ... into `dir`.
+pub fn export_images(
+    frames: &[Frame],
+    dir: &Path,
+    format: ImageFormat,
+    scale: usize,
+) -> io::Result<Vec<PathBuf>> {
+    fs::create_dir_all(dir)?;
+    frames
+        .iter()
+        .enumerate()
+        .map(|(i, frame)| {
+            let path = dir.join(format!("frame_{:05}.{}", i + 1, format.extension()));
+            let mut out = io::BufWriter::new(fs::File::create(&path)?);
+            write_image(frame, format, scale, &mut out)?;
+            out.flush()?;
+            Ok(path)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    const PALETTE: Palette = Palette(&[('#', [255, 0, 0]), ('.', [0, 0, 0])]);
+
+    fn frame() -> Frame {
+        let mut frame = Frame::new(3, 2, '.', PALETTE);
+        frame.set(0, 0, '#');
+        frame.set(2, 1, '#');
+        frame.set(3, 0, '#');
+        frame
+    }
+
+    struct Counter(usize);
+
+    impl Visualize for Counter {
+        fn frame(&self) -> Frame {
+            Frame::new(self.0, 1, '.', PALETTE)
+        }
+    }
+
+    #[test]
+    fn test_frame() {
+        let frame = frame();
+        assert_eq!("#..\n..#", frame.to_string());
+        assert_eq!(Some('#'), frame.get(2, 1));
+        assert_eq!(None, frame.get(3, 0));
+        assert_eq!([255, 0, 0], PALETTE.color('#'));
+        assert_eq!([255, 255, 255], PALETTE.color('@'));
+        assert_eq!(76, PALETTE.gray('#'));
+    }
+
+    #[test]
+    fn test_recorder() {
+        let mut recorder = Recorder::every(2);
+        for i in 1..=5 {
+            recorder.observe(&Counter(i));
+        }
+        assert_eq!(
+            vec![1, 3, 5],
+            recorder
+                .frames()
+                .iter()
+                .map(Frame::width)
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_exporters() {
+        assert_eq!(
+            "frame 1\n#..\n..#\n\nframe 2\n#..\n..#\n",
+            to_text(&[frame(), frame()])
+        );
+        assert_eq!(
+            "\x1b[38;2;255;0;0m#\x1b[38;2;0;0;0m..\x1b[0m\n\x1b[38;2;0;0;0m..\x1b[38;2;255;0;0m#\x1b[0m\n",
+            to_ansi(&frame())
+        );
+
+        let mut ppm = vec![];
+        write_image(&frame(), ImageFormat::Ppm, 1, &mut ppm).unwrap();
+        assert_eq!(b"P6\n3 2\n255\n", &ppm[..11]);
+        assert_eq!(11 + 3 * 2 * 3, ppm.len());
+        assert_eq!([255, 0, 0, 0, 0, 0], ppm[11..17]);
+
+        let mut pgm = vec![];
+        write_image(&frame(), ImageFormat::Pgm, 2, &mut pgm).unwrap();
+        assert_eq!(b"P5\n6 4\n255\n", &pgm[..11]);
+        assert_eq!([76, 76, 0, 0, 0, 0], pgm[11..17]);
+        assert_eq!(pgm[11..17], pgm[17..23]);
+    }
+
+    #[test]
+    fn test_days() {
+        let fixtures = Path::new(env!("CARGO_MANIFEST_DIR")).join("fixtures");
+        for day in DAYS {
+            let input =
+                fs::read_to_string(fixtures.join(format!("day{day:02}/example.txt"))).unwrap();
+            for part in [1, 2] {
+                let mut recorder = Recorder::new();
+                simulate(day, part, &input, &mut recorder).unwrap();
+                assert!(!recorder.frames().is_empty(), "day {day} part {part}");
+            }
+        }
+
+        let input = fs::read_to_string(fixtures.join("day14/example.txt")).unwrap();
+        let mut recorder = Recorder::new();
+        simulate(14, 1, &input, &mut recorder).unwrap();
+        assert_eq!(24, recorder.frames().len());
+        assert_eq!(
+            "\
+.......+....
+............
+.......o....
+......ooo...
+.....#ooo##.
+....o#ooo#..
+...###ooo#..
+.....oooo#..
+..o.ooooo#..
+.#########..",
+            recorder.frames()[23].to_string()
+        );
+    }
+
+    #[test]
+    fn test_simulate() {
+        let mut recorder = Recorder::new();
+        simulate(
+            23,
+            1,
+            ".....\n..##.\n..#..\n.....\n..##.\n.....\n",
+            &mut recorder,
+        )
+        .unwrap();
+        assert_eq!("##\n#.\n..\n##", recorder.frames()[0].to_string(),);
+        assert!(matches!(
+            simulate(1, 1, "", &mut recorder),
+            Err(VisualizeError::Unsupported(1))
+        ));
+        assert!(matches!(
+            simulate(23, 3, "", &mut recorder),
+            Err(VisualizeError::UnknownPart(23, 3))
+        ));
+    }
+}